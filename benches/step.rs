@@ -0,0 +1,71 @@
+//! Stepping speed of the renderer-independent core, reported as steps per second.
+//!
+//! ```shell
+//! cargo bench --bench step
+//! ```
+//!
+//! Baseline on a single core of the development machine, flat storage unless noted:
+//!
+//! | case               | steps/s |
+//! |--------------------|---------|
+//! | RL, 1k steps       | 41.5 M  |
+//! | RL, 100k steps     | 33.0 M  |
+//! | RL, 1M steps       | 26.2 M  |
+//! | LLRR, 100k steps   | 45.4 M  |
+//! | 12-state, 100k     | 41.2 M  |
+//! | RL, 100k, chunked  | 41.9 M  |
+//!
+//! Longer RL runs are slower on flat storage because the hash map keeps growing.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use langtons_ant::{
+    lattice::SquareLattice,
+    pattern::Pattern,
+    sim::{simulate, AntState, GridState},
+    SimRng,
+};
+
+/// Rule, number of steps and storage of every case
+const CASES: &[(&str, u64, bool)] = &[
+    ("RL", 1_000, false),
+    ("RL", 100_000, false),
+    ("RL", 1_000_000, false),
+    ("LLRR", 100_000, false),
+    ("LLRRRLRLRLLR", 100_000, false),
+    ("RL", 100_000, true),
+    ("RLR", 100_000, false),
+    ("RLR", 100_000, true),
+];
+
+fn step_patterns(c: &mut Criterion) {
+    let mut group = c.benchmark_group("step");
+    let mut rng = SimRng::seeded(0);
+
+    for &(rule, steps, chunked) in CASES {
+        let pattern = Pattern::parse(rule.to_owned(), &mut rng.0).unwrap();
+        let grid = if chunked {
+            GridState::chunked()
+        } else {
+            GridState::default()
+        };
+        let storage = if chunked { "chunked" } else { "flat" };
+
+        group.throughput(Throughput::Elements(steps));
+        group.bench_with_input(
+            BenchmarkId::new(format!("{storage}/{rule}"), steps),
+            &pattern,
+            |b, pattern| {
+                b.iter_batched_ref(
+                    || (grid.clone(), AntState::<SquareLattice>::default()),
+                    |(grid, ant)| simulate(grid, ant, pattern, steps),
+                    BatchSize::LargeInput,
+                );
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, step_patterns);
+criterion_main!(benches);
@@ -0,0 +1,51 @@
+//! Runs a short `RL` simulation without Bevy and prints the grid as text.
+//!
+//! ```shell
+//! cargo run --example ascii
+//! ```
+
+use anyhow::Result;
+use bevy::math::IVec2;
+use langtons_ant::{
+    lattice::SquareLattice,
+    pattern::Pattern,
+    sim::{step, AntState, GridState},
+};
+
+const STEPS: usize = 500;
+const SYMBOLS: &[u8] = b".#ox+*";
+
+fn main() -> Result<()> {
+    let pattern = Pattern::parse("RL".to_owned(), &mut rand::thread_rng())?;
+    let mut grid = GridState::default();
+    let mut ant = AntState::<SquareLattice>::default();
+
+    for _ in 0..STEPS {
+        step(&mut grid, &mut ant, &pattern);
+    }
+
+    let (min, max) = grid
+        .cells()
+        .fold((ant.cell, ant.cell), |(min, max), (cell, _)| {
+            (min.min(cell), max.max(cell))
+        });
+
+    for y in (min.y..=max.y).rev() {
+        let row: String = (min.x..=max.x)
+            .map(|x| {
+                let cell = IVec2::new(x, y);
+                if cell == ant.cell {
+                    '@'
+                } else {
+                    match grid.get(cell) {
+                        Some(state) => SYMBOLS[state % SYMBOLS.len()] as char,
+                        None => ' ',
+                    }
+                }
+            })
+            .collect();
+        println!("{row}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+//! Measures the frame time of a board with a million painted cells, drawn either as one mesh
+//! per tile or as chunk textures.
+//!
+//! Renders off-screen into an image, so no window is needed, but a GPU adapter is:
+//!
+//! ```shell
+//! cargo run --release --example render_bench -- mesh
+//! cargo run --release --example render_bench -- texture
+//! ```
+
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use bevy::{
+    app::ScheduleRunnerPlugin,
+    log::LogPlugin,
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+    },
+    window::ExitCondition,
+    winit::WinitPlugin,
+};
+use langtons_ant::{
+    board::{Board, BoardPlugin, StepsPerTick},
+    lattice::SquareLattice,
+    pattern::Pattern,
+    sim::{step, AntState},
+    tile_texture::ChunkTexturePlugin,
+    AppState, TILE_SIZE,
+};
+
+const PAINTED_CELLS: usize = 1_000_000;
+const WARMUP_FRAMES: u32 = 10;
+const MEASURED_FRAMES: u32 = 60;
+const TARGET_SIZE: UVec2 = UVec2::new(1280, 720);
+
+#[derive(Resource, Default)]
+struct FrameTimes {
+    last: Option<Instant>,
+    frames: u32,
+    total: Duration,
+}
+
+fn main() -> Result<()> {
+    let texture = match std::env::args().nth(1).as_deref() {
+        Some("mesh") => false,
+        Some("texture") => true,
+        _ => bail!("usage: render_bench mesh|texture"),
+    };
+
+    let pattern = Pattern::parse("RL".to_owned(), &mut rand::thread_rng())?;
+    let mut board = Board::default();
+    let mut ant = AntState::<SquareLattice>::default();
+    while board.grid.len() < PAINTED_CELLS {
+        step(&mut board.grid, &mut ant, &pattern);
+    }
+
+    let mut app = App::new();
+    app.add_plugins((
+        DefaultPlugins
+            .set(LogPlugin {
+                level: bevy::log::Level::WARN,
+                ..Default::default()
+            })
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+            })
+            .disable::<WinitPlugin>(),
+        ScheduleRunnerPlugin::run_loop(Duration::ZERO),
+        BoardPlugin::<SquareLattice>::default(),
+    ))
+    // Paused, so only drawing the board is measured
+    .insert_state(AppState::Paused)
+    .insert_resource(board)
+    .insert_resource(StepsPerTick(1))
+    .insert_resource(pattern)
+    .init_resource::<FrameTimes>()
+    .add_systems(Startup, setup)
+    .add_systems(Last, record_frame_time);
+
+    if texture {
+        app.add_plugins(ChunkTexturePlugin);
+    }
+
+    app.run();
+
+    Ok(())
+}
+
+/// Points an off-screen camera at the whole painted area
+fn setup(mut commands: Commands, mut images: ResMut<Assets<Image>>, board: Res<Board>) {
+    let mut target = Image::new_fill(
+        Extent3d {
+            width: TARGET_SIZE.x,
+            height: TARGET_SIZE.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0; 4],
+        TextureFormat::Bgra8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    target.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_SRC | TextureUsages::RENDER_ATTACHMENT;
+
+    let (min, max) = board
+        .grid
+        .cells()
+        .fold((IVec2::ZERO, IVec2::ZERO), |(min, max), (cell, _)| {
+            (min.min(cell), max.max(cell))
+        });
+    let min = min.as_vec2() * TILE_SIZE;
+    let max = max.as_vec2() * TILE_SIZE;
+
+    let mut camera = Camera2dBundle::default();
+    camera.camera.target = RenderTarget::Image(images.add(target));
+    camera.projection.scale = ((max - min) / TARGET_SIZE.as_vec2()).max_element();
+    camera.transform.translation = ((min + max) / 2.).extend(camera.transform.translation.z);
+    commands.spawn(camera);
+}
+
+fn record_frame_time(mut times: ResMut<FrameTimes>, mut exit: EventWriter<AppExit>) {
+    let now = Instant::now();
+    let Some(last) = times.last.replace(now) else {
+        return;
+    };
+
+    times.frames += 1;
+    if times.frames > WARMUP_FRAMES {
+        times.total += now - last;
+    }
+    if times.frames == WARMUP_FRAMES + MEASURED_FRAMES {
+        println!(
+            "mean frame time over {MEASURED_FRAMES} frames: {:.2?}",
+            times.total / MEASURED_FRAMES
+        );
+        exit.send(AppExit::Success);
+    }
+}
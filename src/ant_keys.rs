@@ -0,0 +1,122 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+use bevy_pancam::PanCam;
+
+use crate::{
+    board::{spawn_ant, AntId, WorldOrigin, ANT_TEXTURE},
+    compare::CompareAnt,
+    keybindings::{Action, KeyBindings},
+    lattice::Lattice,
+    pattern_input::pattern_input_closed,
+    sim::AntState,
+    Shortcuts,
+};
+
+/// Adds an ant at the center of the view and removes the newest one from the keyboard.
+/// New ants step after the existing ones, and a board reset removes them again.
+/// `Action::ToggleAnts` hides every ant sprite, those spawned later included, for
+/// captures of the painted cells alone; the ants keep stepping.
+pub struct AntKeysPlugin<L: Lattice>(PhantomData<L>);
+
+impl<L: Lattice> Default for AntKeysPlugin<L> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<L: Lattice> Plugin for AntKeysPlugin<L> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyBindings>()
+            .init_resource::<AntsHidden>()
+            .add_systems(
+                Update,
+                (
+                    (add_ant::<L>, remove_ant, toggle_ants)
+                        .chain()
+                        .run_if(pattern_input_closed)
+                        .in_set(Shortcuts),
+                    hide_ants,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Whether the ant sprites are hidden
+#[derive(Resource, Default)]
+pub struct AntsHidden(pub bool);
+
+fn add_ant<L: Lattice>(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    asset_server: Res<AssetServer>,
+    origin: Res<WorldOrigin>,
+    ant_query: Query<&AntId>,
+    camera_query: Query<&Transform, With<PanCam>>,
+) {
+    if !bindings.just_pressed(Action::AddAnt, &keys) {
+        return;
+    }
+    let Ok(camera) = camera_query.get_single() else {
+        return;
+    };
+
+    let cell = origin.world_to_cell::<L>(camera.translation.truncate());
+    let id = ant_query.iter().map(|id| id.0 + 1).max().unwrap_or(0);
+    info!("ant {id} added at {cell}");
+    spawn_ant(
+        &mut commands,
+        asset_server.load(ANT_TEXTURE),
+        *origin,
+        id,
+        AntState::<L> {
+            cell,
+            heading: L::start_heading(cell),
+            mirrored: false,
+            inverse: false,
+        },
+    );
+}
+
+fn remove_ant(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    ant_query: Query<(Entity, &AntId)>,
+) {
+    if !bindings.just_pressed(Action::RemoveAnt, &keys) {
+        return;
+    }
+    if let Some((entity, id)) = ant_query.iter().max_by_key(|(_, id)| **id) {
+        info!("ant {} removed", id.0);
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn toggle_ants(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut hidden: ResMut<AntsHidden>,
+) {
+    if bindings.just_pressed(Action::ToggleAnts, &keys) {
+        hidden.0 = !hidden.0;
+    }
+}
+
+/// Runs every frame so ants spawned by a reset or a key press follow the setting too
+#[allow(clippy::type_complexity)]
+fn hide_ants(
+    hidden: Res<AntsHidden>,
+    mut ant_query: Query<&mut Visibility, Or<(With<AntId>, With<CompareAnt>)>>,
+) {
+    let visibility = if hidden.0 {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    };
+    for mut ant_visibility in ant_query.iter_mut() {
+        ant_visibility.set_if_neq(visibility);
+    }
+}
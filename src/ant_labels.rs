@@ -0,0 +1,135 @@
+use bevy::prelude::*;
+use bevy_pancam::PanCam;
+
+use crate::{
+    board::AntId,
+    keybindings::{Action, KeyBindings},
+    pattern_input::pattern_input_closed,
+    theme::foreground,
+    Shortcuts, TILE_SIZE,
+};
+
+/// Height of the label text in screen pixels
+const LABEL_SIZE: f32 = 14.;
+
+/// Gap between the top of the ant's cell and its label, in screen pixels
+const LABEL_GAP: f32 = 4.;
+
+/// Writes each ant's `AntId` above it. Labels keep the same size on screen whatever the
+/// zoom and stay upright as the ants turn. `Action::ToggleAntLabels` shows and hides
+/// them.
+pub struct AntLabelsPlugin {
+    /// Whether the labels are drawn from the start
+    pub shown: bool,
+}
+
+impl Plugin for AntLabelsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AntLabels(self.shown))
+            .init_resource::<KeyBindings>()
+            .init_resource::<ClearColor>()
+            .add_systems(
+                Update,
+                (
+                    toggle_ant_labels
+                        .run_if(pattern_input_closed)
+                        .in_set(Shortcuts),
+                    label_ants,
+                )
+                    .chain(),
+            )
+            // Ants move on fixed ticks, the labels follow them before transforms propagate
+            .add_systems(
+                PostUpdate,
+                place_labels.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Whether the ant labels are shown
+#[derive(Resource, Clone, Copy, PartialEq, Eq)]
+pub struct AntLabels(pub bool);
+
+/// Text child of an ant showing its id
+#[derive(Component)]
+pub struct AntLabel;
+
+/// Marks an ant that got its label
+#[derive(Component)]
+struct Labelled;
+
+fn toggle_ant_labels(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut labels: ResMut<AntLabels>,
+) {
+    if bindings.just_pressed(Action::ToggleAntLabels, &keys) {
+        labels.0 = !labels.0;
+    }
+}
+
+/// Gives every new ant its label, hidden while the labels are off
+fn label_ants(
+    mut commands: Commands,
+    labels: Res<AntLabels>,
+    background: Res<ClearColor>,
+    ant_query: Query<(Entity, &AntId), Without<Labelled>>,
+    mut label_query: Query<&mut Visibility, With<AntLabel>>,
+) {
+    let visibility = if labels.0 {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    for mut label_visibility in label_query.iter_mut() {
+        label_visibility.set_if_neq(visibility);
+    }
+
+    for (ant, id) in ant_query.iter() {
+        commands
+            .entity(ant)
+            .insert(Labelled)
+            .with_children(|parent| {
+                parent.spawn((
+                    Text2dBundle {
+                        text: Text::from_section(
+                            id.0.to_string(),
+                            TextStyle {
+                                font_size: LABEL_SIZE,
+                                color: foreground(background.0),
+                                ..default()
+                            },
+                        ),
+                        visibility,
+                        ..default()
+                    },
+                    AntLabel,
+                ));
+            });
+    }
+}
+
+/// Puts each label above its ant, undoing the ant's rotation and the camera zoom
+#[allow(clippy::type_complexity)]
+fn place_labels(
+    camera_query: Query<&OrthographicProjection, With<PanCam>>,
+    ant_query: Query<&Transform, (With<AntId>, Without<AntLabel>)>,
+    mut label_query: Query<(&Parent, &mut Transform), With<AntLabel>>,
+) {
+    let scale = camera_query
+        .get_single()
+        .map_or(1., |projection| projection.scale);
+    for (parent, mut label_transform) in label_query.iter_mut() {
+        let Ok(ant_transform) = ant_query.get(parent.get()) else {
+            continue;
+        };
+        let upright = ant_transform.rotation.inverse();
+        let offset = Vec3::Y * (TILE_SIZE / 2. + (LABEL_GAP + LABEL_SIZE / 2.) * scale);
+        // Slightly in front of the ant sprite
+        *label_transform = Transform {
+            translation: upright * offset + Vec3::Z,
+            rotation: upright,
+            scale: Vec3::splat(scale),
+        };
+    }
+}
@@ -0,0 +1,1135 @@
+use std::marker::PhantomData;
+
+use anyhow::Result;
+use bevy::{
+    asset::LoadState,
+    ecs::system::SystemParam,
+    prelude::*,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    utils::{HashMap, HashSet},
+};
+use bevy_pancam::PanCam;
+
+use crate::{
+    heatmap::{heat_level, Heatmap, HEAT_LEVELS},
+    lattice::Lattice,
+    palette::distinct,
+    pattern::Pattern,
+    sim::{
+        chunk_of, count_visits, step_colliding, AntState, Border, Collision, GridState,
+        VisitCounts, CHUNK_SIZE,
+    },
+    theme::foreground,
+    tile_texture::ChunkTextures,
+    AppState, SimRng, ANT_Z, TILE_SIZE, TILE_Z,
+};
+
+/// Tint of the anti-ant's sprite
+const INVERSE_TINT: Color = Color::srgb(0.3, 0.5, 1.);
+
+/// Ant sprite, drawn pointing up
+pub(crate) const ANT_TEXTURE: &str = "ant.png";
+
+/// Runs and draws the simulation on lattice `L`.
+///
+/// The rule is the `Pattern` resource, which the app inserts itself or leaves to
+/// `BoardPlugin::with_pattern`. `Board` and `StepsPerTick` default to an unbounded flat
+/// grid and one step per tick unless the app inserts its own.
+pub struct BoardPlugin<L: Lattice> {
+    /// Inserted as the `Pattern` resource if the app has none when the plugin is built
+    pattern: Option<Pattern>,
+    lattice: PhantomData<L>,
+}
+
+impl<L: Lattice> Default for BoardPlugin<L> {
+    fn default() -> Self {
+        Self {
+            pattern: None,
+            lattice: PhantomData,
+        }
+    }
+}
+
+impl<L: Lattice> BoardPlugin<L> {
+    /// Plugin running `pattern`, in the syntax of `Pattern::parse`, with the colors
+    /// `--seed 0` would give it
+    pub fn with_pattern(pattern: &str) -> Result<Self> {
+        let pattern = Pattern::parse(pattern.to_owned(), &mut SimRng::seeded(0).0)?;
+        Ok(Self {
+            pattern: Some(pattern),
+            lattice: PhantomData,
+        })
+    }
+}
+
+impl<L: Lattice> Plugin for BoardPlugin<L> {
+    fn build(&self, app: &mut App) {
+        if let Some(pattern) = &self.pattern {
+            if !app.world().contains_resource::<Pattern>() {
+                app.insert_resource(pattern.clone());
+            }
+        }
+        app.add_event::<ResetBoard>()
+            .add_event::<TogglePause>()
+            .add_event::<TilePainted>()
+            .add_event::<AntStepped>()
+            .add_event::<AutoPaused>()
+            .init_resource::<Board>()
+            .init_resource::<StepsPerTick>()
+            .init_resource::<StepCounter>()
+            .init_resource::<SingleStep>()
+            .init_resource::<RadiusLimits>()
+            .init_resource::<StopOnOrigin>()
+            .init_resource::<MaxSteps>()
+            .init_resource::<Warmup>()
+            .init_resource::<PendingWarmup>()
+            .init_resource::<MirrorAnts>()
+            .init_resource::<AntiAnt>()
+            .init_resource::<CollisionRule>()
+            .init_resource::<TileCap>()
+            .init_resource::<CountVisits>()
+            .init_resource::<WorldOrigin>()
+            .init_resource::<ClearColor>()
+            .add_event::<WorldShifted>()
+            .add_systems(Startup, setup::<L>)
+            .add_systems(
+                Update,
+                (
+                    (reset_board::<L>, sync_tiles::<L>.in_set(SyncTiles)).chain(),
+                    toggle_pause,
+                    check_ant_texture.run_if(resource_exists::<AntTexture>),
+                    update_tile_materials.run_if(resource_changed::<Pattern>),
+                    draw_border::<L>,
+                    cull_chunks::<L>.run_if(|board: Res<Board>| board.visible_chunks.is_some()),
+                ),
+            )
+            .add_systems(
+                FixedUpdate,
+                (run_rotation::<L>, recenter_world::<L>).chain(),
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct Ant<L: Lattice>(pub AntState<L>);
+
+/// Order in which ants apply their writes within a step
+#[derive(Component, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AntId(pub usize);
+
+/// Steps an ant took since it was spawned or the board was last reset
+#[derive(Component, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct AntSteps(pub u64);
+
+/// Whether an ant stepped off the origin since it was last on it, for `StopOnOrigin`
+#[derive(Component, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct LeftOrigin(pub bool);
+
+impl LeftOrigin {
+    /// Follows a step of the ant from `from` to `to`, true if it came back onto the origin
+    pub fn returned(&mut self, from: IVec2, to: IVec2) -> bool {
+        if from == IVec2::ZERO && to != IVec2::ZERO {
+            self.0 = true;
+        } else if to == IVec2::ZERO && self.0 {
+            self.0 = false;
+            return true;
+        }
+        false
+    }
+}
+
+#[derive(Component)]
+pub struct Tile;
+
+/// Brings the tiles up to date with the grid once per frame, however many fixed ticks
+/// ran in it. Systems reading `TilePainted` or the tiles go after it.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SyncTiles;
+
+/// Total number of steps simulated since the last reset
+#[derive(Resource, Default)]
+pub struct StepCounter(pub u64);
+
+/// How many steps are simulated on every fixed tick
+#[derive(Resource)]
+pub struct StepsPerTick(pub u32);
+
+impl Default for StepsPerTick {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+/// Requests exactly one step on the next fixed tick while paused
+#[derive(Resource, Default)]
+pub struct SingleStep(pub bool);
+
+/// Distances from the origin, as measured by `Lattice::radius`, at which the run stops
+#[derive(Resource, Default)]
+pub struct RadiusLimits {
+    /// Pause once any ant gets further than this
+    pub pause: Option<u32>,
+    /// Quit once any ant gets further than this
+    pub exit: Option<u32>,
+}
+
+impl RadiusLimits {
+    /// Whether moving from `from` to `to` crosses `limit`
+    fn crossed<L: Lattice>(limit: Option<u32>, from: IVec2, to: IVec2) -> bool {
+        limit.is_some_and(|limit| L::radius(from) <= limit && L::radius(to) > limit)
+    }
+}
+
+/// Pauses once the step counter reaches this, so a shared run stops where it was shared.
+/// Resuming carries on past it.
+#[derive(Resource, Default)]
+pub struct MaxSteps(pub Option<u64>);
+
+/// Steps simulated during startup, before anything is drawn. From the start menu they
+/// are simulated once a run starts instead, see `PendingWarmup`.
+#[derive(Resource, Default)]
+pub struct Warmup(pub u64);
+
+/// Set to simulate the `Warmup` steps again on the next `ResetBoard`, as starting a run
+/// from the start menu does. Other resets start from the first step.
+#[derive(Resource, Default)]
+pub struct PendingWarmup(pub bool);
+
+/// Pauses whenever an ant steps back onto the origin after having stepped off it. Ants
+/// stopped by a border or blocked by another ant don't leave, and arriving there for the
+/// first time, like an anti-ant might, isn't a return. See `LeftOrigin`.
+#[derive(Resource, Default)]
+pub struct StopOnOrigin(pub bool);
+
+/// Adds the mirror images of the ant across both axes and the origin, four ants in total
+#[derive(Resource, Default)]
+pub struct MirrorAnts(pub bool);
+
+/// Adds an inverse ant `ANTI_ANT_OFFSET` cells east of the origin, see `AntState::inverse`
+#[derive(Resource, Default)]
+pub struct AntiAnt(pub bool);
+
+/// Cells between the origin and the start of the anti-ant, far enough for the first ant
+/// to build something before they meet
+pub const ANTI_ANT_OFFSET: i32 = 16;
+
+/// Ants marked with this step backwards, `AntState::inverse` is set on them
+#[derive(Component)]
+pub struct Inverse;
+
+/// Where the ants start, in `AntId` order: the ant, its mirror images, then the anti-ant
+pub fn initial_ants<L: Lattice>(mirror: bool, anti_ant: bool) -> Vec<AntState<L>> {
+    let mut ants = if mirror {
+        AntState::default().mirror_images().to_vec()
+    } else {
+        vec![AntState::default()]
+    };
+    if anti_ant {
+        let cell = IVec2::new(ANTI_ANT_OFFSET, 0);
+        ants.push(AntState {
+            cell,
+            heading: L::start_heading(cell),
+            mirrored: false,
+            inverse: true,
+        });
+    }
+    ants
+}
+
+/// Ants to start with instead of those of `initial_ants`, such as the ants of an
+/// imported snapshot. A reset still brings back the usual starting ants.
+#[derive(Resource)]
+pub struct StartingAnts<L: Lattice>(pub Vec<AntState<L>>);
+
+/// How the ants treat each other when they meet
+#[derive(Resource, Default)]
+pub struct CollisionRule(pub Collision);
+
+/// Most painted cells kept, past it the least recently visited ones are forgotten as if
+/// never painted. Unlike the display settings this changes what the ants do, as they
+/// find background where a forgotten cell was.
+#[derive(Resource, Default)]
+pub struct TileCap {
+    pub max: Option<usize>,
+    /// Cells forgotten since the last reset
+    pub evicted: u64,
+}
+
+/// Counts how often the ants visit each cell into `Board::visits`, for the heatmap. Off
+/// unless something shows the counts, as it costs a map update on every step.
+#[derive(Resource, Default)]
+pub struct CountVisits(pub bool);
+
+/// Share of `TileCap::max` an eviction leaves painted, so evictions stay rare
+const EVICT_TO: f64 = 0.9;
+
+/// Clears all tiles and puts the ants back where they started
+#[derive(Event)]
+pub struct ResetBoard;
+
+/// Pauses a running board and resumes a paused one. Every control sends this rather
+/// than setting `AppState` itself, so an embedder's own button only has to send it too.
+#[derive(Event)]
+pub struct TogglePause;
+
+/// Distance in cells, along either lattice axis, the first ant may get from the world
+/// origin before everything is moved back around it
+const RECENTER_DISTANCE: i32 = 1 << 15;
+
+/// Lattice cell drawn at the world origin.
+///
+/// World positions are `f32`, which gets too coarse to place tiles exactly a few million
+/// cells out. When the first ant gets `RECENTER_DISTANCE` cells away, the origin moves
+/// onto it and every drawn entity and the camera shift by the same amount, so what is
+/// on screen always stays close to the world origin. The origin stays on chunk corners,
+/// which keeps chunks, texture texels and triangle orientations lined up.
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WorldOrigin(pub IVec2);
+
+impl WorldOrigin {
+    /// Chunk corner nearest below `cell`
+    fn around(cell: IVec2) -> Self {
+        Self(cell.div_euclid(IVec2::splat(CHUNK_SIZE)) * CHUNK_SIZE)
+    }
+
+    /// Center of `cell` in world units
+    pub fn cell_to_world<L: Lattice>(self, cell: IVec2) -> Vec2 {
+        L::cell_to_world(cell - self.0)
+    }
+
+    /// Cell whose area contains the world `position`
+    pub fn world_to_cell<L: Lattice>(self, position: Vec2) -> IVec2 {
+        L::world_to_cell(position) + self.0
+    }
+}
+
+/// The world origin moved, everything drawn was moved by `-offset` in world units
+#[derive(Event)]
+pub struct WorldShifted {
+    pub offset: Vec2,
+}
+
+/// A tile was spawned for a cell the ants painted for the first time
+#[derive(Event)]
+pub struct TilePainted {
+    pub tile: Entity,
+}
+
+/// The ants took `steps` steps this tick, sent once per tick rather than per step
+#[derive(Event)]
+pub struct AntStepped {
+    pub steps: u32,
+}
+
+/// A stop condition paused the run, such as an ant returning to the origin with
+/// `StopOnOrigin`
+#[derive(Event)]
+pub struct AutoPaused;
+
+/// ECS-side mirror of the simulation: the logical grid plus the entity drawn for each painted cell
+#[derive(Resource, Default)]
+pub struct Board {
+    pub grid: GridState,
+    pub tiles: HashMap<IVec2, Entity>,
+    /// Cells changed since the tiles were last synced, see `SyncTiles`
+    pub dirty: HashSet<IVec2>,
+    /// Step at which each painted cell was last visited, only kept under a `TileCap`
+    pub last_visit: HashMap<IVec2, u64>,
+    /// Times each cell was visited, only counted with `CountVisits`
+    pub visits: VisitCounts,
+    /// Chunks currently drawn when culling to the camera view, `None` draws every tile
+    pub visible_chunks: Option<HashSet<IVec2>>,
+}
+
+impl Board {
+    pub fn chunked() -> Self {
+        Self {
+            grid: GridState::chunked(),
+            tiles: HashMap::default(),
+            dirty: HashSet::default(),
+            last_visit: HashMap::default(),
+            visits: VisitCounts::default(),
+            visible_chunks: Some(HashSet::default()),
+        }
+    }
+
+    /// Forgets the least recently visited painted cells until `keep` are left, sparing
+    /// the cells in `occupied`, and returns the forgotten ones
+    fn evict(&mut self, keep: usize, occupied: &[IVec2]) -> Vec<IVec2> {
+        let mut candidates: Vec<(u64, i32, i32)> = self
+            .grid
+            .cells()
+            .filter(|(cell, _)| !occupied.contains(cell))
+            .map(|(cell, _)| {
+                let visit = self.last_visit.get(&cell).copied().unwrap_or_default();
+                (visit, cell.x, cell.y)
+            })
+            .collect();
+        let count = self.grid.len().saturating_sub(keep).min(candidates.len());
+        if count == 0 {
+            return Vec::new();
+        }
+        candidates.select_nth_unstable(count - 1);
+
+        let cells: Vec<IVec2> = candidates[..count]
+            .iter()
+            .map(|&(_, x, y)| IVec2::new(x, y))
+            .collect();
+        self.grid.remove_cells(&cells);
+        for cell in &cells {
+            self.last_visit.remove(cell);
+            self.visits.remove(cell);
+        }
+        cells
+    }
+
+    fn is_drawn(&self, cell: IVec2) -> bool {
+        match &self.visible_chunks {
+            Some(visible) => visible.contains(&chunk_of(cell).0),
+            None => true,
+        }
+    }
+}
+
+/// Ant sprite still being loaded
+#[derive(Resource)]
+struct AntTexture(Handle<Image>);
+
+/// Mesh shared by every tile, one material per pattern state and one per heat level
+#[derive(Resource)]
+pub(crate) struct TileAssets {
+    mesh: Mesh2dHandle,
+    pub(crate) materials: Vec<Handle<ColorMaterial>>,
+    /// Colored by the `Heatmap`, which may not be there to do it
+    pub(crate) heat_materials: Vec<Handle<ColorMaterial>>,
+}
+
+impl TileAssets {
+    /// Material of the tile of `cell` in `state`, its heat level's while the heatmap is shown
+    pub(crate) fn material(
+        &self,
+        heatmap: Option<&Heatmap>,
+        visits: &VisitCounts,
+        cell: IVec2,
+        state: usize,
+    ) -> Handle<ColorMaterial> {
+        if heatmap.is_some_and(|heatmap| heatmap.shown) {
+            let visits = visits.get(&cell).copied().unwrap_or_default();
+            self.heat_materials[heat_level(visits)].clone()
+        } else {
+            self.materials[state].clone()
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn setup<L: Lattice>(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut board: ResMut<Board>,
+    pattern: Res<Pattern>,
+    // Bevy systems take at most 16 parameters
+    (mirror, anti_ant, collision, counting): (
+        Res<MirrorAnts>,
+        Res<AntiAnt>,
+        Res<CollisionRule>,
+        Res<CountVisits>,
+    ),
+    warmup: Res<Warmup>,
+    mut origin: ResMut<WorldOrigin>,
+    mut step_counter: ResMut<StepCounter>,
+    state: Res<State<AppState>>,
+    chunk_textures: Option<Res<ChunkTextures>>,
+    starting_ants: Option<Res<StartingAnts<L>>>,
+    heatmap: Option<Res<Heatmap>>,
+) {
+    let ants = match &starting_ants {
+        Some(starting_ants) => starting_ants.0.clone(),
+        None => initial_ants::<L>(mirror.0, anti_ant.0),
+    };
+    if starting_ants.is_some() {
+        if let Some(ant) = ants.first() {
+            *origin = WorldOrigin::around(ant.cell);
+        }
+    }
+    // From the start menu the warmup waits for the run to start, see `PendingWarmup`
+    let steps = match state.get() {
+        AppState::Menu => 0,
+        _ => warmup.0,
+    };
+    let warmed = warm_up(&mut board, ants, &pattern, collision.0, counting.0, steps);
+    if steps > 0 {
+        step_counter.0 = steps;
+        if let Some(ant) = warmed.ants.first() {
+            *origin = WorldOrigin::around(ant.cell);
+        }
+    }
+
+    let tile_assets = TileAssets {
+        mesh: Mesh2dHandle(meshes.add(L::tile_mesh())),
+        materials: pattern
+            .colors
+            .iter()
+            .map(|color| materials.add(*color))
+            .collect(),
+        heat_materials: (0..HEAT_LEVELS)
+            .map(|_| materials.add(Color::WHITE))
+            .collect(),
+    };
+
+    // Draw a grid that was painted before the app started, culling draws its own chunks
+    if chunk_textures.is_none() && board.visible_chunks.is_none() {
+        let Board {
+            grid,
+            tiles,
+            visits,
+            ..
+        } = &mut *board;
+        for (cell, state) in grid.cells() {
+            let material = tile_assets.material(heatmap.as_deref(), visits, cell, state);
+            tiles.insert(
+                cell,
+                spawn_tile::<L>(&mut commands, &tile_assets, *origin, cell, material),
+            );
+        }
+    }
+    commands.insert_resource(tile_assets);
+
+    let texture = asset_server.load(ANT_TEXTURE);
+    commands.insert_resource(AntTexture(texture.clone()));
+    for ((id, ant), left) in warmed
+        .ids
+        .into_iter()
+        .zip(warmed.ants)
+        .zip(warmed.left_origin)
+    {
+        let ant = spawn_ant(&mut commands, texture.clone(), *origin, id, ant);
+        commands.entity(ant).insert((AntSteps(steps), left));
+    }
+}
+
+/// Ants left after a warmup, with their `AntId`s and `LeftOrigin`s
+struct WarmedUp<L: Lattice> {
+    ants: Vec<AntState<L>>,
+    ids: Vec<usize>,
+    left_origin: Vec<LeftOrigin>,
+}
+
+/// Steps `ants`, numbered from 0, `steps` times on `board`. Stop conditions aren't
+/// checked, the run starts wherever the warmup ends.
+fn warm_up<L: Lattice>(
+    board: &mut Board,
+    mut ants: Vec<AntState<L>>,
+    pattern: &Pattern,
+    collision: Collision,
+    counting: bool,
+    steps: u64,
+) -> WarmedUp<L> {
+    let mut ids: Vec<usize> = (0..ants.len()).collect();
+    let mut left_origin = vec![LeftOrigin::default(); ants.len()];
+    for _ in 0..steps {
+        let (deltas, annihilated) = step_colliding(&mut board.grid, &mut ants, pattern, collision);
+        if counting {
+            count_visits(&mut board.visits, &deltas);
+        }
+        for ((left, delta), ant) in left_origin.iter_mut().zip(&deltas).zip(&ants) {
+            left.returned(delta.cell, ant.cell);
+        }
+        for index in annihilated.into_iter().rev() {
+            ants.remove(index);
+            ids.remove(index);
+            left_origin.remove(index);
+        }
+    }
+    if steps > 0 {
+        info!(
+            "warmed up for {steps} steps, {} cells painted",
+            board.grid.len()
+        );
+    }
+    WarmedUp {
+        ants,
+        ids,
+        left_origin,
+    }
+}
+
+pub(crate) fn spawn_ant<L: Lattice>(
+    commands: &mut Commands,
+    texture: Handle<Image>,
+    origin: WorldOrigin,
+    id: usize,
+    ant: AntState<L>,
+) -> Entity {
+    let mut entity = commands.spawn((
+        SpriteBundle {
+            texture,
+            transform: transform_for(&ant, origin),
+            sprite: ant_sprite(&ant, id),
+            ..default()
+        },
+        Ant(ant),
+        AntId(id),
+        AntSteps::default(),
+        LeftOrigin::default(),
+    ));
+    if ant.inverse {
+        entity.insert(Inverse);
+    }
+    entity.id()
+}
+
+/// Tint of the sprite of ant `id`. The first ant keeps the sprite's own colors and the
+/// others get hues a golden angle apart, so several ants can be told apart.
+pub fn ant_tint(id: usize) -> Color {
+    match id {
+        0 => Color::WHITE,
+        id => distinct(id - 1),
+    }
+}
+
+/// Sprite of ant `id`, tinted by its id or as an anti-ant
+pub(crate) fn ant_sprite<L: Lattice>(ant: &AntState<L>, id: usize) -> Sprite {
+    Sprite {
+        color: if ant.inverse {
+            INVERSE_TINT
+        } else {
+            ant_tint(id)
+        },
+        ..default()
+    }
+}
+
+/// Gives the ants a triangle marker if their sprite can't be loaded, so they stay visible
+fn check_ant_texture(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    texture: Res<AntTexture>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    ant_query: Query<Entity, With<AntId>>,
+) {
+    match asset_server.get_load_state(&texture.0) {
+        Some(LoadState::Failed(err)) => {
+            error!("failed to load {ANT_TEXTURE}, drawing the ant as a triangle: {err}");
+            let size = TILE_SIZE / 2.;
+            let mesh = Mesh2dHandle(meshes.add(Triangle2d::new(
+                Vec2::new(0., size),
+                Vec2::new(-size * 0.7, -size),
+                Vec2::new(size * 0.7, -size),
+            )));
+            let material = materials.add(Color::srgb(0.8, 0.1, 0.1));
+            for ant in ant_query.iter() {
+                commands.entity(ant).with_children(|parent| {
+                    parent.spawn(MaterialMesh2dBundle {
+                        mesh: mesh.clone(),
+                        material: material.clone(),
+                        ..default()
+                    });
+                });
+            }
+        }
+        Some(LoadState::Loaded) => (),
+        _ => return,
+    }
+    commands.remove_resource::<AntTexture>();
+}
+
+fn transform_for<L: Lattice>(ant: &AntState<L>, origin: WorldOrigin) -> Transform {
+    Transform::from_translation(origin.cell_to_world::<L>(ant.cell).extend(ANT_Z))
+        .with_rotation(L::heading_to_rotation(ant.heading))
+}
+
+fn spawn_tile<L: Lattice>(
+    commands: &mut Commands,
+    tile_assets: &TileAssets,
+    origin: WorldOrigin,
+    cell: IVec2,
+    material: Handle<ColorMaterial>,
+) -> Entity {
+    commands
+        .spawn((
+            MaterialMesh2dBundle {
+                mesh: tile_assets.mesh.clone(),
+                material,
+                transform: Transform::from_translation(
+                    origin.cell_to_world::<L>(cell).extend(TILE_Z),
+                )
+                .with_rotation(L::tile_rotation(cell)),
+                ..default()
+            },
+            Tile,
+        ))
+        .id()
+}
+
+fn draw_border<L: Lattice>(
+    mut gizmos: Gizmos,
+    board: Res<Board>,
+    origin: Res<WorldOrigin>,
+    background: Res<ClearColor>,
+) {
+    let Some(border) = board.grid.border() else {
+        return;
+    };
+    let border = Border {
+        min: border.min - origin.0,
+        max: border.max - origin.0,
+        ..border
+    };
+    gizmos.linestrip_2d(L::border_outline(&border), foreground(background.0));
+}
+
+/// Spawns tiles for chunks entering the camera view and despawns those of chunks leaving it
+fn cull_chunks<L: Lattice>(
+    mut commands: Commands,
+    mut board: ResMut<Board>,
+    tile_assets: Res<TileAssets>,
+    origin: Res<WorldOrigin>,
+    heatmap: Option<Res<Heatmap>>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<PanCam>>,
+) {
+    let Ok((camera_transform, projection)) = camera_query.get_single() else {
+        return;
+    };
+
+    // Lattice coordinates are not axis-aligned in world space, so bound all four corners
+    let center = camera_transform.translation.truncate();
+    let area = projection.area;
+    let corners = [
+        area.min,
+        area.max,
+        Vec2::new(area.min.x, area.max.y),
+        Vec2::new(area.max.x, area.min.y),
+    ]
+    .map(|corner| chunk_of(origin.world_to_cell::<L>(center + corner)).0);
+    let min_chunk = corners.into_iter().reduce(IVec2::min).unwrap();
+    let max_chunk = corners.into_iter().reduce(IVec2::max).unwrap();
+
+    let mut visible = HashSet::new();
+    for x in min_chunk.x..=max_chunk.x {
+        for y in min_chunk.y..=max_chunk.y {
+            visible.insert(IVec2::new(x, y));
+        }
+    }
+
+    let Board {
+        grid,
+        tiles,
+        visits,
+        visible_chunks,
+        ..
+    } = &mut *board;
+    let Some(drawn) = visible_chunks else {
+        return;
+    };
+    if *drawn == visible {
+        return;
+    }
+
+    tiles.retain(|cell, tile| {
+        let keep = visible.contains(&chunk_of(*cell).0);
+        if !keep {
+            commands.entity(*tile).despawn();
+        }
+        keep
+    });
+
+    for chunk in visible.difference(drawn) {
+        for (cell, state) in grid.chunk_cells(*chunk) {
+            let material = tile_assets.material(heatmap.as_deref(), visits, cell, state);
+            tiles.insert(
+                cell,
+                spawn_tile::<L>(&mut commands, &tile_assets, *origin, cell, material),
+            );
+        }
+    }
+
+    *drawn = visible;
+}
+
+/// Settings that decide how the ants interact and when a run stops
+#[derive(SystemParam)]
+struct RunRules<'w> {
+    limits: Res<'w, RadiusLimits>,
+    stop_on_origin: Res<'w, StopOnOrigin>,
+    max_steps: Res<'w, MaxSteps>,
+    collision: Res<'w, CollisionRule>,
+}
+
+#[derive(SystemParam)]
+struct RunEvents<'w> {
+    stepped: EventWriter<'w, AntStepped>,
+    auto_paused: EventWriter<'w, AutoPaused>,
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn run_rotation<L: Lattice>(
+    mut commands: Commands,
+    mut board: ResMut<Board>,
+    mut step_counter: ResMut<StepCounter>,
+    mut single_step: ResMut<SingleStep>,
+    state: Res<State<AppState>>,
+    steps_per_tick: Res<StepsPerTick>,
+    pattern: Res<Pattern>,
+    rules: RunRules,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut exit: EventWriter<AppExit>,
+    mut events: RunEvents,
+    origin: Res<WorldOrigin>,
+    chunk_textures: Option<ResMut<ChunkTextures>>,
+    mut cap: ResMut<TileCap>,
+    counting: Res<CountVisits>,
+    mut ant_query: Query<(
+        Entity,
+        &AntId,
+        &mut Ant<L>,
+        &mut Transform,
+        &mut AntSteps,
+        &mut LeftOrigin,
+    )>,
+) {
+    let steps = match state.get() {
+        AppState::Running => steps_per_tick.0,
+        AppState::Paused if single_step.0 => 1,
+        AppState::Paused | AppState::Menu => return,
+    };
+    single_step.0 = false;
+
+    // Query order is arbitrary, sort so the outcome never depends on it
+    let mut ants: Vec<_> = ant_query.iter_mut().collect();
+    ants.sort_by_key(|(_, id, _, _, _, _)| **id);
+
+    let mut ant_states: Vec<AntState<L>> = ants.iter().map(|(_, _, ant, _, _, _)| ant.0).collect();
+    let mut touched = Vec::new();
+    let mut taken = 0;
+    let mut paused = false;
+    #[cfg(feature = "trace")]
+    let simulate_span = info_span!("simulate", steps).entered();
+    for _ in 0..steps {
+        let (deltas, annihilated) = step_colliding(
+            &mut board.grid,
+            &mut ant_states,
+            &pattern,
+            rules.collision.0,
+        );
+        step_counter.0 += 1;
+        taken += 1;
+        if counting.0 {
+            count_visits(&mut board.visits, &deltas);
+        }
+
+        let mut stop = false;
+        for ((delta, ant), (_, _, _, _, _, left)) in
+            deltas.iter().zip(ant_states.iter()).zip(ants.iter_mut())
+        {
+            touched.push(delta.cell);
+            let returned = left.returned(delta.cell, ant.cell);
+            if cap.max.is_some() {
+                board.last_visit.insert(delta.cell, step_counter.0);
+            }
+
+            if RadiusLimits::crossed::<L>(rules.limits.exit, delta.cell, ant.cell) {
+                warn!("ant left the exit radius at step {}", step_counter.0);
+                exit.send(AppExit::Success);
+                stop = true;
+            } else if RadiusLimits::crossed::<L>(rules.limits.pause, delta.cell, ant.cell) {
+                warn!("ant left the pause radius at step {}", step_counter.0);
+                next_state.set(AppState::Paused);
+                paused = true;
+                stop = true;
+            } else if rules.stop_on_origin.0 && returned {
+                info!("ant returned to the origin at step {}", step_counter.0);
+                next_state.set(AppState::Paused);
+                paused = true;
+                stop = true;
+            }
+        }
+
+        if rules.max_steps.0 == Some(step_counter.0) {
+            info!("reached step {}", step_counter.0);
+            next_state.set(AppState::Paused);
+            paused = true;
+            stop = true;
+        }
+
+        for index in annihilated.into_iter().rev() {
+            let (entity, id, _, _, _, _) = ants.remove(index);
+            info!("ant {} annihilated at step {}", id.0, step_counter.0);
+            commands.entity(entity).despawn_recursive();
+            ant_states.remove(index);
+        }
+        if stop {
+            break;
+        }
+    }
+    #[cfg(feature = "trace")]
+    drop(simulate_span);
+    if taken > 0 {
+        events.stepped.send(AntStepped { steps: taken });
+    }
+    if paused {
+        events.auto_paused.send(AutoPaused);
+    }
+
+    if let Some(max) = cap.max.filter(|max| board.grid.len() > *max) {
+        #[cfg(feature = "trace")]
+        let _span = info_span!("evict_tiles").entered();
+        if cap.evicted == 0 {
+            warn!(
+                "more than {max} cells painted at step {}, forgetting the least recently \
+                 visited ones from now on",
+                step_counter.0
+            );
+        }
+        let occupied: Vec<IVec2> = ant_states.iter().map(|ant| ant.cell).collect();
+        let evicted = board.evict((max as f64 * EVICT_TO) as usize, &occupied);
+        cap.evicted += evicted.len() as u64;
+        for cell in evicted {
+            if let Some(tile) = board.tiles.remove(&cell) {
+                commands.entity(tile).despawn();
+            }
+            touched.push(cell);
+        }
+    }
+
+    // Only the final state of each touched cell needs to reach the renderer, which
+    // catches up once per frame
+    match chunk_textures {
+        Some(mut chunk_textures) => chunk_textures.dirty.extend(touched),
+        None => board.dirty.extend(touched),
+    }
+
+    for ((_, _, ant, ant_transform, ant_steps, _), state) in ants.iter_mut().zip(ant_states) {
+        ant.0 = state;
+        // Ants still here took every step of the tick, annihilated ones are gone
+        ant_steps.0 += taken as u64;
+        ant_transform.rotation = L::heading_to_rotation(state.heading);
+        ant_transform.translation = origin
+            .cell_to_world::<L>(state.cell)
+            .extend(ant_transform.translation.z);
+    }
+}
+
+/// Spawns or recolors the tile of every cell changed since the last frame
+fn sync_tiles<L: Lattice>(
+    mut commands: Commands,
+    mut board: ResMut<Board>,
+    mut painted_events: EventWriter<TilePainted>,
+    tile_assets: Res<TileAssets>,
+    origin: Res<WorldOrigin>,
+    heatmap: Option<Res<Heatmap>>,
+    mut tile_query: Query<&mut Handle<ColorMaterial>, With<Tile>>,
+) {
+    // Sorted into tiles to recolor and cells to spawn first, so each part gets a span
+    let dirty = std::mem::take(&mut board.dirty);
+    let (recolor, spawn) = {
+        #[cfg(feature = "trace")]
+        let _span = info_span!("tile_lookup", cells = dirty.len()).entered();
+        let mut recolor = Vec::new();
+        let mut spawn = Vec::new();
+        for cell in dirty {
+            let Some(state) = board.grid.get(cell) else {
+                continue;
+            };
+            let material = || tile_assets.material(heatmap.as_deref(), &board.visits, cell, state);
+            match board.tiles.get(&cell) {
+                Some(&tile) => recolor.push((tile, material())),
+                None if board.is_drawn(cell) => spawn.push((cell, material())),
+                None => (),
+            }
+        }
+        (recolor, spawn)
+    };
+
+    {
+        #[cfg(feature = "trace")]
+        let _span = info_span!("material_update", tiles = recolor.len()).entered();
+        for (tile, material) in recolor {
+            if let Ok(mut tile_material) = tile_query.get_mut(tile) {
+                *tile_material = material;
+            }
+        }
+    }
+
+    #[cfg(feature = "trace")]
+    let _span = info_span!("tile_spawn", tiles = spawn.len()).entered();
+    for (cell, material) in spawn {
+        let tile = spawn_tile::<L>(&mut commands, &tile_assets, *origin, cell, material);
+        board.tiles.insert(cell, tile);
+        painted_events.send(TilePainted { tile });
+    }
+}
+
+/// Rebuilds the per-state materials when the pattern resource is replaced
+fn update_tile_materials(
+    pattern: Res<Pattern>,
+    board: Res<Board>,
+    heatmap: Option<Res<Heatmap>>,
+    mut tile_assets: ResMut<TileAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut tile_query: Query<&mut Handle<ColorMaterial>, With<Tile>>,
+) {
+    tile_assets.materials = pattern
+        .colors
+        .iter()
+        .map(|color| materials.add(*color))
+        .collect();
+
+    for (cell, &tile) in board.tiles.iter() {
+        let Some(state) = board.grid.get(*cell) else {
+            continue;
+        };
+        if state >= tile_assets.materials.len() {
+            continue;
+        }
+        if let Ok(mut tile_material) = tile_query.get_mut(tile) {
+            *tile_material = tile_assets.material(heatmap.as_deref(), &board.visits, *cell, state);
+        }
+    }
+}
+
+fn toggle_pause(
+    mut toggle_events: EventReader<TogglePause>,
+    state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    // Two toggles in one frame cancel out
+    if toggle_events.read().count() % 2 == 1 {
+        next_state.set(match state.get() {
+            AppState::Paused => AppState::Running,
+            AppState::Running => AppState::Paused,
+            // Only the menu's start button leaves the menu
+            AppState::Menu => return,
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn reset_board<L: Lattice>(
+    mut commands: Commands,
+    mut reset_events: EventReader<ResetBoard>,
+    mut board: ResMut<Board>,
+    mut step_counter: ResMut<StepCounter>,
+    mut cap: ResMut<TileCap>,
+    asset_server: Res<AssetServer>,
+    pattern: Res<Pattern>,
+    (mirror, anti_ant, collision, counting): (
+        Res<MirrorAnts>,
+        Res<AntiAnt>,
+        Res<CollisionRule>,
+        Res<CountVisits>,
+    ),
+    warmup: Res<Warmup>,
+    mut pending_warmup: ResMut<PendingWarmup>,
+    mut origin: ResMut<WorldOrigin>,
+    chunk_textures: Option<Res<ChunkTextures>>,
+    mut ant_query: Query<(Entity, &AntId, &mut Ant<L>, &mut Transform)>,
+) {
+    if reset_events.read().count() == 0 {
+        return;
+    }
+
+    info!(
+        "board reset after {} steps, {} cells painted",
+        step_counter.0,
+        board.grid.len()
+    );
+    for (_, tile) in board.tiles.drain() {
+        commands.entity(tile).despawn();
+    }
+    board.dirty.clear();
+    board.last_visit.clear();
+    board.visits.clear();
+    board.grid.clear();
+    if let Some(drawn) = &mut board.visible_chunks {
+        drawn.clear();
+    }
+    cap.evicted = 0;
+
+    let steps = if std::mem::take(&mut pending_warmup.0) {
+        warmup.0
+    } else {
+        0
+    };
+    let warmed = warm_up(
+        &mut board,
+        initial_ants::<L>(mirror.0, anti_ant.0),
+        &pattern,
+        collision.0,
+        counting.0,
+        steps,
+    );
+    step_counter.0 = steps;
+    // The last run may have moved the origin far out, bring it to where this one starts
+    *origin = match warmed.ants.first() {
+        Some(ant) if steps > 0 => WorldOrigin::around(ant.cell),
+        _ => WorldOrigin::default(),
+    };
+    if steps > 0 {
+        // Chunk textures repaint the whole grid after a reset themselves
+        if chunk_textures.is_none() {
+            let Board { grid, dirty, .. } = &mut *board;
+            dirty.extend(grid.cells().map(|(cell, _)| cell));
+        }
+    }
+
+    let mut starts: HashMap<usize, (AntState<L>, LeftOrigin)> = warmed
+        .ids
+        .into_iter()
+        .zip(warmed.ants.into_iter().zip(warmed.left_origin))
+        .collect();
+    for (entity, id, mut ant, mut ant_transform) in ant_query.iter_mut() {
+        // Ants added at runtime go, the starting ones go back to their start
+        let Some((start, left)) = starts.remove(&id.0) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        ant.0 = start;
+        *ant_transform = transform_for(&ant.0, *origin);
+        commands.entity(entity).insert((AntSteps(steps), left));
+    }
+
+    // Bring back ants that were annihilated or lost during the last run
+    for (id, (start, left)) in starts {
+        let ant = spawn_ant(
+            &mut commands,
+            asset_server.load(ANT_TEXTURE),
+            *origin,
+            id,
+            start,
+        );
+        commands.entity(ant).insert((AntSteps(steps), left));
+    }
+}
+
+/// Moves the world origin onto the first ant once it gets `RECENTER_DISTANCE` cells away,
+/// shifting every top-level entity, cameras included, along with it
+#[allow(clippy::type_complexity)]
+fn recenter_world<L: Lattice>(
+    mut origin: ResMut<WorldOrigin>,
+    mut shifted_events: EventWriter<WorldShifted>,
+    ant_query: Query<(&AntId, &Ant<L>)>,
+    mut transform_query: Query<&mut Transform, (Without<Parent>, Without<Node>)>,
+) {
+    let Some((_, ant)) = ant_query.iter().min_by_key(|(id, _)| **id) else {
+        return;
+    };
+    if (ant.0.cell - origin.0).abs().max_element() <= RECENTER_DISTANCE {
+        return;
+    }
+
+    let recentered = WorldOrigin::around(ant.0.cell);
+    let offset = L::cell_to_world(recentered.0 - origin.0);
+    info!(
+        "moving the world origin from {} to {}",
+        origin.0, recentered.0
+    );
+    for mut transform in transform_query.iter_mut() {
+        transform.translation -= offset.extend(0.);
+    }
+    *origin = recentered;
+    shifted_events.send(WorldShifted { offset });
+}
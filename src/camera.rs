@@ -0,0 +1,140 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_pancam::PanCam;
+
+use crate::{
+    board::AntId,
+    keybindings::{Action, KeyBindings},
+    pattern_input::pattern_input_closed,
+    theme::foreground,
+    view::RestoreView,
+    Shortcuts, TILE_SIZE,
+};
+
+/// Keyboard shortcuts that move the camera, `PanCam` handles the mouse.
+/// `Action::ToggleCameraLock` turns `PanCam` off so stray drags and scrolls can't move
+/// or zoom the view during a demo, while the simulation carries on. The touch and
+/// gamepad controls respect the lock too, and a label shows while it is on.
+pub struct CameraKeysPlugin;
+
+impl Plugin for CameraKeysPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyBindings>()
+            .init_resource::<ClearColor>()
+            .add_systems(Startup, setup_lock_indicator)
+            .add_systems(
+                Update,
+                (center_on_ant, toggle_camera_lock)
+                    .run_if(pattern_input_closed)
+                    .in_set(Shortcuts),
+            );
+    }
+}
+
+#[derive(Component)]
+struct LockIndicator;
+
+/// Zooms the camera at startup so about `cells_across` cells fit across the window,
+/// within the `PanCam` zoom limits. A restored view takes precedence.
+pub struct InitialZoomPlugin {
+    pub cells_across: f32,
+}
+
+impl Plugin for InitialZoomPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(InitialZoom(self.cells_across))
+            // The window and the camera both exist by `PostStartup`
+            .add_systems(PostStartup, initial_zoom.before(RestoreView));
+    }
+}
+
+#[derive(Resource)]
+struct InitialZoom(f32);
+
+fn initial_zoom(
+    zoom: Res<InitialZoom>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut camera_query: Query<(&PanCam, &mut OrthographicProjection)>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    if window.width() <= 0. {
+        return;
+    }
+
+    // The projection scale is world units per pixel
+    let scale = zoom.0 * TILE_SIZE / window.width();
+    for (cam, mut projection) in camera_query.iter_mut() {
+        let mut scale = scale.max(cam.min_scale);
+        if let Some(max_scale) = cam.max_scale {
+            scale = scale.min(max_scale);
+        }
+        projection.scale = scale;
+    }
+}
+
+/// Moves the camera onto the first ant once, keeping the zoom
+fn center_on_ant(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    ant_query: Query<(&AntId, &Transform), Without<PanCam>>,
+    mut camera_query: Query<&mut Transform, With<PanCam>>,
+) {
+    if !bindings.just_pressed(Action::CenterOnAnt, &keys) {
+        return;
+    }
+    let Some((_, ant_transform)) = ant_query.iter().min_by_key(|(id, _)| **id) else {
+        return;
+    };
+
+    for mut transform in camera_query.iter_mut() {
+        let z = transform.translation.z;
+        transform.translation = ant_transform.translation.truncate().extend(z);
+    }
+}
+
+fn setup_lock_indicator(mut commands: Commands, background: Res<ClearColor>) {
+    commands.spawn((
+        TextBundle {
+            visibility: Visibility::Hidden,
+            ..TextBundle::from_section(
+                "Camera locked",
+                TextStyle {
+                    font_size: 16.,
+                    color: foreground(background.0),
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                // Under the rate feedback
+                top: Val::Px(36.),
+                left: Val::Percent(50.),
+                ..default()
+            })
+        },
+        LockIndicator,
+    ));
+}
+
+/// Turns `PanCam` panning and zooming off or back on
+fn toggle_camera_lock(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut camera_query: Query<&mut PanCam>,
+    mut indicator_query: Query<&mut Visibility, With<LockIndicator>>,
+) {
+    if !bindings.just_pressed(Action::ToggleCameraLock, &keys) {
+        return;
+    }
+    for mut cam in camera_query.iter_mut() {
+        cam.enabled = !cam.enabled;
+        for mut visibility in indicator_query.iter_mut() {
+            *visibility = if cam.enabled {
+                Visibility::Hidden
+            } else {
+                Visibility::Inherited
+            };
+        }
+    }
+}
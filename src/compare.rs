@@ -0,0 +1,327 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    prelude::*,
+    render::{camera::Viewport, view::RenderLayers},
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    utils::{HashMap, HashSet},
+    window::PrimaryWindow,
+};
+use bevy_pancam::PanCam;
+
+use crate::{
+    board::{
+        ant_sprite, initial_ants, AntiAnt, Board, CollisionRule, MirrorAnts, ResetBoard,
+        StepCounter, SyncTiles, WorldOrigin,
+    },
+    lattice::Lattice,
+    pattern::Pattern,
+    sim::{step_colliding, AntState, GridState},
+    ANT_Z, TILE_Z,
+};
+
+/// Layer the second simulation is drawn on, so each camera only sees its own board
+const COMPARE_LAYER: RenderLayers = RenderLayers::layer(1);
+
+/// Runs a second pattern on its own board, drawn in the right half of the window while
+/// the main board takes the left half.
+///
+/// The second board steps as many times as the main one did on every tick, so pausing,
+/// the rate, steps per tick, warmup and resets all apply to both. Its camera copies the
+/// main camera, so panning and zooming move both views together.
+pub struct ComparePlugin<L: Lattice> {
+    lattice: PhantomData<L>,
+}
+
+impl<L: Lattice> Default for ComparePlugin<L> {
+    fn default() -> Self {
+        Self {
+            lattice: PhantomData,
+        }
+    }
+}
+
+impl<L: Lattice> Plugin for ComparePlugin<L> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_compare::<L>)
+            .add_systems(FixedPostUpdate, step_compare::<L>)
+            .add_systems(
+                Update,
+                (
+                    // After the main board's reset, which moves the world origin back
+                    reset_compare::<L>.after(SyncTiles),
+                    sync_cameras,
+                ),
+            );
+    }
+}
+
+/// Pattern of the second simulation
+#[derive(Resource)]
+pub struct ComparePattern(pub Pattern);
+
+#[derive(Resource)]
+struct CompareBoard<L: Lattice> {
+    grid: GridState,
+    ants: Vec<AntState<L>>,
+    /// `CompareAnt` of each ant, annihilated ants leave gaps
+    ids: Vec<usize>,
+    /// Main step count this board has caught up with
+    steps: u64,
+    tiles: HashMap<IVec2, Entity>,
+    mesh: Mesh2dHandle,
+    materials: Vec<Handle<ColorMaterial>>,
+}
+
+#[derive(Component)]
+struct CompareCamera;
+
+/// Ant of the compared board, by its index among the starting ants
+#[derive(Component)]
+pub struct CompareAnt(usize);
+
+#[allow(clippy::too_many_arguments)]
+fn setup_compare<L: Lattice>(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    pattern: Res<ComparePattern>,
+    mirror: Res<MirrorAnts>,
+    anti_ant: Res<AntiAnt>,
+    main_board: Res<Board>,
+    origin: Res<WorldOrigin>,
+) {
+    // Same storage and border as the main grid
+    let mut grid = main_board.grid.clone();
+    grid.clear();
+
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                order: 1,
+                ..default()
+            },
+            ..default()
+        },
+        COMPARE_LAYER,
+        CompareCamera,
+    ));
+
+    let ants = initial_ants::<L>(mirror.0, anti_ant.0);
+    let texture = asset_server.load("ant.png");
+    for (id, ant) in ants.iter().enumerate() {
+        spawn_compare_ant(&mut commands, texture.clone(), *origin, id, ant);
+    }
+
+    commands.insert_resource(CompareBoard {
+        grid,
+        ids: (0..ants.len()).collect(),
+        ants,
+        steps: 0,
+        tiles: HashMap::default(),
+        mesh: Mesh2dHandle(meshes.add(L::tile_mesh())),
+        materials: pattern
+            .0
+            .colors
+            .iter()
+            .map(|color| materials.add(*color))
+            .collect(),
+    });
+}
+
+fn spawn_compare_ant<L: Lattice>(
+    commands: &mut Commands,
+    texture: Handle<Image>,
+    origin: WorldOrigin,
+    id: usize,
+    ant: &AntState<L>,
+) {
+    commands.spawn((
+        SpriteBundle {
+            texture,
+            transform: ant_transform(ant, origin),
+            sprite: ant_sprite(ant, id),
+            ..default()
+        },
+        COMPARE_LAYER,
+        CompareAnt(id),
+    ));
+}
+
+fn ant_transform<L: Lattice>(ant: &AntState<L>, origin: WorldOrigin) -> Transform {
+    Transform::from_translation(origin.cell_to_world::<L>(ant.cell).extend(ANT_Z))
+        .with_rotation(L::heading_to_rotation(ant.heading))
+}
+
+/// Catches up with the steps the main board took this tick
+#[allow(clippy::too_many_arguments)]
+fn step_compare<L: Lattice>(
+    mut commands: Commands,
+    mut board: ResMut<CompareBoard<L>>,
+    pattern: Res<ComparePattern>,
+    step_counter: Res<StepCounter>,
+    collision: Res<CollisionRule>,
+    origin: Res<WorldOrigin>,
+    mut tile_query: Query<&mut Handle<ColorMaterial>>,
+    mut ant_query: Query<(Entity, &CompareAnt, &mut Transform)>,
+) {
+    // A reset puts the main counter back, `reset_compare` clears this board
+    let steps = step_counter.0.saturating_sub(board.steps);
+    if steps == 0 {
+        return;
+    }
+
+    let CompareBoard {
+        grid,
+        ants,
+        ids,
+        tiles,
+        mesh,
+        materials,
+        ..
+    } = &mut *board;
+    let mut touched = HashSet::new();
+    for _ in 0..steps {
+        let (deltas, annihilated) = step_colliding(grid, ants, &pattern.0, collision.0);
+        touched.extend(deltas.iter().map(|delta| delta.cell));
+        for index in annihilated.into_iter().rev() {
+            ants.remove(index);
+            ids.remove(index);
+        }
+    }
+
+    for cell in touched {
+        let Some(state) = grid.get(cell) else {
+            continue;
+        };
+        let material = materials[state].clone();
+        match tiles.get(&cell) {
+            Some(&tile) => {
+                if let Ok(mut tile_material) = tile_query.get_mut(tile) {
+                    *tile_material = material;
+                }
+            }
+            None => {
+                let tile = commands
+                    .spawn((
+                        MaterialMesh2dBundle {
+                            mesh: mesh.clone(),
+                            material,
+                            transform: Transform::from_translation(
+                                origin.cell_to_world::<L>(cell).extend(TILE_Z),
+                            )
+                            .with_rotation(L::tile_rotation(cell)),
+                            ..default()
+                        },
+                        COMPARE_LAYER,
+                    ))
+                    .id();
+                tiles.insert(cell, tile);
+            }
+        }
+    }
+
+    for (entity, id, mut transform) in ant_query.iter_mut() {
+        match ids.iter().position(|ant_id| *ant_id == id.0) {
+            Some(index) => *transform = ant_transform(&ants[index], *origin),
+            None => commands.entity(entity).despawn(),
+        }
+    }
+    board.steps = step_counter.0;
+}
+
+#[allow(clippy::too_many_arguments)]
+fn reset_compare<L: Lattice>(
+    mut commands: Commands,
+    mut reset_events: EventReader<ResetBoard>,
+    asset_server: Res<AssetServer>,
+    mut board: ResMut<CompareBoard<L>>,
+    mirror: Res<MirrorAnts>,
+    anti_ant: Res<AntiAnt>,
+    origin: Res<WorldOrigin>,
+    mut ant_query: Query<(&CompareAnt, &mut Transform)>,
+) {
+    if reset_events.read().count() == 0 {
+        return;
+    }
+
+    for (_, tile) in board.tiles.drain() {
+        commands.entity(tile).despawn();
+    }
+    board.grid.clear();
+    board.ants = initial_ants::<L>(mirror.0, anti_ant.0);
+    board.ids = (0..board.ants.len()).collect();
+    board.steps = 0;
+    let mut missing: HashSet<usize> = board.ids.iter().copied().collect();
+    for (id, mut transform) in ant_query.iter_mut() {
+        if let Some(ant) = board.ants.get(id.0) {
+            *transform = ant_transform(ant, *origin);
+        }
+        missing.remove(&id.0);
+    }
+
+    for id in missing {
+        spawn_compare_ant(
+            &mut commands,
+            asset_server.load("ant.png"),
+            *origin,
+            id,
+            &board.ants[id],
+        );
+    }
+}
+
+/// Splits the window between the two cameras and keeps the second view on the first
+#[allow(clippy::type_complexity)]
+fn sync_cameras(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut main_query: Query<
+        (&mut Camera, &Transform, &OrthographicProjection),
+        (With<PanCam>, Without<CompareCamera>),
+    >,
+    mut compare_query: Query<
+        (&mut Camera, &mut Transform, &mut OrthographicProjection),
+        With<CompareCamera>,
+    >,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let Ok((main_camera, main_transform, main_projection)) = main_query.get_single_mut() else {
+        return;
+    };
+    let Ok((camera, mut transform, mut projection)) = compare_query.get_single_mut() else {
+        return;
+    };
+
+    *transform = *main_transform;
+    projection.scale = main_projection.scale;
+
+    // Zero-sized viewports are invalid, a minimized window keeps the previous split
+    let size = window.physical_size();
+    if size.x < 2 || size.y == 0 {
+        return;
+    }
+    let left_width = size.x / 2;
+    set_viewport(main_camera, UVec2::ZERO, UVec2::new(left_width, size.y));
+    set_viewport(
+        camera,
+        UVec2::new(left_width, 0),
+        UVec2::new(size.x - left_width, size.y),
+    );
+}
+
+/// Only writes on change, every write has the renderer set the camera up again
+fn set_viewport(mut camera: Mut<Camera>, position: UVec2, size: UVec2) {
+    let unchanged = camera.viewport.as_ref().is_some_and(|viewport| {
+        viewport.physical_position == position && viewport.physical_size == size
+    });
+    if !unchanged {
+        camera.viewport = Some(Viewport {
+            physical_position: position,
+            physical_size: size,
+            ..default()
+        });
+    }
+}
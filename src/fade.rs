@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::board::{SyncTiles, TileAssets, TilePainted};
+
+/// Fades tiles painted for the first time in from the background color. Each fading
+/// tile gets its own material until it reaches its state color and goes back to the
+/// shared one. Purely visual, the grid already holds the new state. Without a window
+/// there is nothing to watch, so tiles keep their state color right away.
+pub struct FadeInPlugin {
+    pub duration: Duration,
+}
+
+impl Plugin for FadeInPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FadeDuration(self.duration))
+            .add_systems(
+                Update,
+                (
+                    start_fades.run_if(any_with_component::<PrimaryWindow>),
+                    fade_tiles,
+                )
+                    .chain()
+                    .after(SyncTiles)
+                    .run_if(resource_exists::<TileAssets>),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct FadeDuration(Duration);
+
+/// Tile fading in, drawn with `material` until `timer` ends
+#[derive(Component)]
+struct FadeIn {
+    target: Handle<ColorMaterial>,
+    material: Handle<ColorMaterial>,
+    timer: Timer,
+}
+
+fn start_fades(
+    mut commands: Commands,
+    mut painted_events: EventReader<TilePainted>,
+    duration: Res<FadeDuration>,
+    background: Res<ClearColor>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut tile_query: Query<&mut Handle<ColorMaterial>>,
+) {
+    for painted in painted_events.read() {
+        let Ok(mut tile_material) = tile_query.get_mut(painted.tile) else {
+            continue;
+        };
+        let material = materials.add(background.0);
+        let target = std::mem::replace(&mut *tile_material, material.clone());
+        commands.entity(painted.tile).insert(FadeIn {
+            target,
+            material,
+            timer: Timer::new(duration.0, TimerMode::Once),
+        });
+    }
+}
+
+fn fade_tiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    background: Res<ClearColor>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut tile_query: Query<(Entity, &mut FadeIn, &mut Handle<ColorMaterial>)>,
+) {
+    for (tile, mut fade, mut tile_material) in tile_query.iter_mut() {
+        // The tile was repainted or recolored meanwhile, its new material wins
+        if *tile_material != fade.material {
+            commands.entity(tile).remove::<FadeIn>();
+            continue;
+        }
+
+        if fade.timer.tick(time.delta()).finished() {
+            *tile_material = fade.target.clone();
+            commands.entity(tile).remove::<FadeIn>();
+            continue;
+        }
+
+        let Some(target) = materials.get(&fade.target).map(|target| target.color) else {
+            continue;
+        };
+        let from = LinearRgba::from(background.0);
+        let color = from.mix(&target.into(), fade.timer.fraction());
+        if let Some(material) = materials.get_mut(&fade.material) {
+            material.color = color.into();
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use bevy::{
+    input::gamepad::{GamepadConnection, GamepadConnectionEvent},
+    prelude::*,
+};
+use bevy_pancam::PanCam;
+
+use crate::{
+    board::{ResetBoard, TogglePause},
+    format_rate, MAX_RATE, MIN_RATE, RATE_STEP,
+};
+
+/// Stick deflection below which input is ignored
+const DEAD_ZONE: f32 = 0.15;
+/// Panning speed at full deflection, in screen pixels per second
+const PAN_SPEED: f32 = 800.;
+/// Zoom factor per second at full deflection
+const ZOOM_SPEED: f32 = 2.;
+
+/// Left stick pans, right stick or triggers zoom, South pauses, East resets and
+/// D-pad left/right change the rate.
+///
+/// Every connected gamepad is read each frame, so controllers can be plugged in or out
+/// at any time.
+pub struct GamepadPlugin;
+
+impl Plugin for GamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (log_connections, gamepad_camera, gamepad_simulation),
+        );
+    }
+}
+
+fn dead_zone(value: f32) -> f32 {
+    if value.abs() < DEAD_ZONE {
+        0.
+    } else {
+        value
+    }
+}
+
+fn log_connections(mut connection_events: EventReader<GamepadConnectionEvent>) {
+    for event in connection_events.read() {
+        match &event.connection {
+            GamepadConnection::Connected(info) => {
+                info!("gamepad {} connected: {}", event.gamepad.id, info.name);
+            }
+            GamepadConnection::Disconnected => {
+                info!("gamepad {} disconnected", event.gamepad.id);
+            }
+        }
+    }
+}
+
+fn gamepad_camera(
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    button_axes: Res<Axis<GamepadButton>>,
+    time: Res<Time<Real>>,
+    mut camera_query: Query<(&PanCam, &mut Transform, &mut OrthographicProjection)>,
+) {
+    let axis = |gamepad, axis_type| {
+        dead_zone(
+            axes.get(GamepadAxis::new(gamepad, axis_type))
+                .unwrap_or_default(),
+        )
+    };
+    let trigger = |gamepad, button_type| {
+        button_axes
+            .get(GamepadButton::new(gamepad, button_type))
+            .unwrap_or_default()
+    };
+
+    let mut pan = Vec2::ZERO;
+    let mut zoom = 0.;
+    for gamepad in gamepads.iter() {
+        pan += Vec2::new(
+            axis(gamepad, GamepadAxisType::LeftStickX),
+            axis(gamepad, GamepadAxisType::LeftStickY),
+        );
+        zoom += axis(gamepad, GamepadAxisType::RightStickY)
+            + trigger(gamepad, GamepadButtonType::RightTrigger2)
+            - trigger(gamepad, GamepadButtonType::LeftTrigger2);
+    }
+    if pan == Vec2::ZERO && zoom == 0. {
+        return;
+    }
+
+    let delta = time.delta_seconds();
+    for (cam, mut transform, mut projection) in camera_query.iter_mut() {
+        if !cam.enabled {
+            continue;
+        }
+
+        // Scaled by the zoom so a full deflection crosses the screen at the same pace
+        transform.translation += (pan * PAN_SPEED * projection.scale * delta).extend(0.);
+
+        let mut scale = (projection.scale * ZOOM_SPEED.powf(-zoom * delta)).max(cam.min_scale);
+        if let Some(max_scale) = cam.max_scale {
+            scale = scale.min(max_scale);
+        }
+        projection.scale = scale;
+    }
+}
+
+fn gamepad_simulation(
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    mut toggle_events: EventWriter<TogglePause>,
+    mut reset_events: EventWriter<ResetBoard>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+) {
+    let pressed = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+
+    if pressed(GamepadButtonType::South) {
+        toggle_events.send(TogglePause);
+    }
+    if pressed(GamepadButtonType::East) {
+        reset_events.send(ResetBoard);
+    }
+
+    let rate_change = match (
+        pressed(GamepadButtonType::DPadLeft),
+        pressed(GamepadButtonType::DPadRight),
+    ) {
+        (true, false) => 1. / RATE_STEP,
+        (false, true) => RATE_STEP,
+        _ => return,
+    };
+    let rate = 1. / fixed_time.timestep().as_secs_f64();
+    let rate = (rate * rate_change).clamp(MIN_RATE, MAX_RATE);
+    fixed_time.set_timestep_hz(rate);
+    info!("rate set to {}", format_rate(rate));
+}
@@ -0,0 +1,107 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+use bevy_pancam::PanCam;
+
+use crate::{
+    keybindings::{Action, KeyBindings},
+    lattice::Lattice,
+    pattern_input::pattern_input_closed,
+    Shortcuts, TILE_SIZE,
+};
+
+/// Screen pixels per cell at which the lines are fully drawn
+const OPAQUE_CELL_PIXELS: f32 = 24.;
+/// Screen pixels per cell below which the lines have faded out and aren't drawn
+const HIDDEN_CELL_PIXELS: f32 = 10.;
+/// Most cells outlined in one frame, a safety net for very large windows
+const MAX_OUTLINED_CELLS: i32 = 40_000;
+
+/// Outlines the cells inside the camera view with gizmos, so neighbouring tiles of the
+/// same color stay apart. Zooming out fades the lines and then stops drawing them,
+/// before they would cost thousands of lines per frame.
+pub struct GridLinesPlugin<L: Lattice> {
+    /// Whether the lines are drawn from the start, `Action::ToggleGridLines` flips it
+    pub enabled: bool,
+    lattice: PhantomData<L>,
+}
+
+impl<L: Lattice> GridLinesPlugin<L> {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            lattice: PhantomData,
+        }
+    }
+}
+
+impl<L: Lattice> Plugin for GridLinesPlugin<L> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GridLines(self.enabled))
+            .init_resource::<KeyBindings>()
+            .add_systems(
+                Update,
+                (
+                    toggle_grid_lines
+                        .run_if(pattern_input_closed)
+                        .in_set(Shortcuts),
+                    draw_grid_lines::<L>.run_if(|grid_lines: Res<GridLines>| grid_lines.0),
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct GridLines(pub bool);
+
+fn toggle_grid_lines(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut grid_lines: ResMut<GridLines>,
+) {
+    if bindings.just_pressed(Action::ToggleGridLines, &keys) {
+        grid_lines.0 = !grid_lines.0;
+    }
+}
+
+fn draw_grid_lines<L: Lattice>(
+    mut gizmos: Gizmos,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<PanCam>>,
+) {
+    let Ok((camera_transform, projection)) = camera_query.get_single() else {
+        return;
+    };
+
+    // The projection scale is world units per pixel
+    let cell_pixels = TILE_SIZE / projection.scale;
+    let alpha = ((cell_pixels - HIDDEN_CELL_PIXELS) / (OPAQUE_CELL_PIXELS - HIDDEN_CELL_PIXELS))
+        .clamp(0., 1.);
+    if alpha == 0. {
+        return;
+    }
+
+    // Same bounds as chunk culling, lattice coordinates aren't axis-aligned in world space
+    let center = camera_transform.translation.truncate();
+    let area = projection.area;
+    let corners = [
+        area.min,
+        area.max,
+        Vec2::new(area.min.x, area.max.y),
+        Vec2::new(area.max.x, area.min.y),
+    ]
+    .map(|corner| L::world_to_cell(center + corner));
+    let min = corners.into_iter().reduce(IVec2::min).unwrap() - IVec2::ONE;
+    let max = corners.into_iter().reduce(IVec2::max).unwrap() + IVec2::ONE;
+    let size = max - min + IVec2::ONE;
+    if size.x.saturating_mul(size.y) > MAX_OUTLINED_CELLS {
+        return;
+    }
+
+    let color = Color::srgba(0., 0., 0., 0.4 * alpha);
+    for x in min.x..=max.x {
+        for y in min.y..=max.y {
+            gizmos.linestrip_2d(L::cell_outline(IVec2::new(x, y)), color);
+        }
+    }
+}
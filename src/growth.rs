@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+use bevy_pancam::PanCam;
+
+use crate::{
+    board::{Board, ResetBoard, StepCounter},
+    keybindings::{Action, KeyBindings},
+    pattern_input::pattern_input_closed,
+    Shortcuts,
+};
+
+/// Samples kept, past this every other one is dropped so the whole run still fits
+const MAX_SAMPLES: usize = 256;
+/// Size of the plot in screen pixels
+const GRAPH_SIZE: Vec2 = Vec2::new(240., 120.);
+/// Gap between the plot and the bottom right corner of the window, in pixels
+const GRAPH_MARGIN: f32 = 10.;
+
+/// Plots the painted cells against the step count in the bottom right corner, so the
+/// switch from chaotic to linear growth when a highway starts shows as a bend in the
+/// line. Samples are taken once per fixed tick at most, and ever more sparsely as the
+/// run gets longer, so keeping them costs next to nothing at any step rate.
+pub struct GrowthGraphPlugin {
+    /// Whether the graph is drawn from the start, `Action::ToggleGrowthGraph` flips it
+    pub enabled: bool,
+}
+
+impl Plugin for GrowthGraphPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GrowthGraph {
+            enabled: self.enabled,
+            samples: Vec::with_capacity(MAX_SAMPLES + 1),
+            every: 1,
+        })
+        .init_resource::<KeyBindings>()
+        .add_systems(FixedPostUpdate, record_growth)
+        .add_systems(
+            Update,
+            (
+                toggle_growth_graph
+                    .run_if(pattern_input_closed)
+                    .in_set(Shortcuts),
+                clear_growth,
+                draw_growth_graph,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct GrowthGraph {
+    pub enabled: bool,
+    /// Step count and painted cells at that step, oldest first
+    pub samples: Vec<(u64, usize)>,
+    /// Fewest steps between two samples, doubles whenever the samples are thinned out
+    every: u64,
+}
+
+fn toggle_growth_graph(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut graph: ResMut<GrowthGraph>,
+) {
+    if bindings.just_pressed(Action::ToggleGrowthGraph, &keys) {
+        graph.enabled = !graph.enabled;
+    }
+}
+
+fn clear_growth(mut reset_events: EventReader<ResetBoard>, mut graph: ResMut<GrowthGraph>) {
+    if reset_events.read().count() > 0 {
+        graph.samples.clear();
+        graph.every = 1;
+    }
+}
+
+/// Runs whether or not the graph is shown, so showing it brings up the whole run
+fn record_growth(
+    mut graph: ResMut<GrowthGraph>,
+    step_counter: Res<StepCounter>,
+    board: Res<Board>,
+) {
+    let steps = step_counter.0;
+    if graph
+        .samples
+        .last()
+        .is_some_and(|(last, _)| steps < last + graph.every)
+    {
+        return;
+    }
+
+    graph.samples.push((steps, board.grid.len()));
+    if graph.samples.len() > MAX_SAMPLES {
+        let mut index = 0;
+        graph.samples.retain(|_| {
+            index += 1;
+            index % 2 == 1
+        });
+        graph.every *= 2;
+    }
+}
+
+fn draw_growth_graph(
+    graph: Res<GrowthGraph>,
+    mut gizmos: Gizmos,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<PanCam>>,
+) {
+    if !graph.enabled || graph.samples.len() < 2 {
+        return;
+    }
+    let Ok((camera_transform, projection)) = camera_query.get_single() else {
+        return;
+    };
+
+    // Gizmos live in world space, the projection scale turns pixels into world units
+    let area = projection.area;
+    let corner = camera_transform.translation.truncate() + Vec2::new(area.max.x, area.min.y);
+    let size = GRAPH_SIZE * projection.scale;
+    let origin = corner + Vec2::new(-GRAPH_MARGIN - GRAPH_SIZE.x, GRAPH_MARGIN) * projection.scale;
+
+    let (first_steps, _) = graph.samples[0];
+    let (last_steps, _) = graph.samples[graph.samples.len() - 1];
+    let max_cells = graph
+        .samples
+        .iter()
+        .map(|(_, cells)| *cells)
+        .max()
+        .unwrap_or(0);
+    let step_span = (last_steps - first_steps).max(1) as f32;
+    let cell_span = max_cells.max(1) as f32;
+
+    let axes = Color::srgba(0., 0., 0., 0.6);
+    gizmos.linestrip_2d(
+        [origin + Vec2::Y * size.y, origin, origin + Vec2::X * size.x],
+        axes,
+    );
+    gizmos.linestrip_2d(
+        graph.samples.iter().map(|(steps, cells)| {
+            origin
+                + Vec2::new(
+                    (steps - first_steps) as f32 / step_span,
+                    *cells as f32 / cell_span,
+                ) * size
+        }),
+        Color::srgb(0.85, 0.1, 0.1),
+    );
+}
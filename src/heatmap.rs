@@ -0,0 +1,165 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::{Board, CountVisits, SyncTiles, Tile, TileAssets},
+    keybindings::{Action, KeyBindings},
+    palette::{gradient, grayscale, MAGMA, VIRIDIS},
+    pattern_input::pattern_input_closed,
+    tile_texture::ChunkTextures,
+    Shortcuts,
+};
+
+/// Heat levels a visit count can reach, one per power of two a `u32` holds
+pub const HEAT_LEVELS: usize = u32::BITS as usize;
+
+/// Heat level of a cell visited `visits` times: every level takes twice the visits of
+/// the one below, so a few hot cells don't wash out the rest
+pub fn heat_level(visits: u32) -> usize {
+    visits.max(1).ilog2() as usize
+}
+
+/// Colors the heat levels run through, least visited first
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum HeatGradient {
+    /// Black through purple and red to pale yellow
+    #[default]
+    Magma,
+    /// Dark blue through green to yellow
+    Viridis,
+    /// Black up to light gray
+    Grayscale,
+}
+
+impl HeatGradient {
+    /// Color of heat `level` out of `levels`
+    pub fn color(self, level: usize, levels: usize) -> Color {
+        match self {
+            HeatGradient::Magma => gradient(&MAGMA, level, levels),
+            HeatGradient::Viridis => gradient(&VIRIDIS, level, levels),
+            HeatGradient::Grayscale => grayscale(levels.saturating_sub(level + 1), levels),
+        }
+    }
+}
+
+/// Tints every tile by how often the ants visited its cell instead of by its state,
+/// spreading the gradient from the least visited level to the most visited one seen so
+/// far. Turns on `CountVisits`, and `Action::ToggleHeatmap` switches between the heatmap
+/// and the state colors.
+pub struct HeatmapPlugin {
+    pub gradient: HeatGradient,
+    /// Whether the heatmap is drawn from the start
+    pub shown: bool,
+}
+
+impl Plugin for HeatmapPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Heatmap {
+            shown: self.shown,
+            gradient: self.gradient,
+            top: 0,
+        })
+        .insert_resource(CountVisits(true))
+        .init_resource::<KeyBindings>()
+        .add_systems(
+            Update,
+            (
+                toggle_heatmap
+                    .run_if(pattern_input_closed)
+                    .in_set(Shortcuts),
+                raise_top_level,
+                redraw_heatmap.run_if(resource_changed::<Heatmap>),
+            )
+                .chain()
+                .before(SyncTiles),
+        );
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Heatmap {
+    pub shown: bool,
+    pub gradient: HeatGradient,
+    /// Highest heat level painted since the last reset, where the gradient ends
+    pub top: usize,
+}
+
+impl Heatmap {
+    /// Color of a cell visited `visits` times
+    pub fn color(&self, visits: u32) -> Color {
+        self.level_color(heat_level(visits))
+    }
+
+    fn level_color(&self, level: usize) -> Color {
+        self.gradient.color(level.min(self.top), self.top + 1)
+    }
+}
+
+fn toggle_heatmap(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut heatmap: ResMut<Heatmap>,
+) {
+    if bindings.just_pressed(Action::ToggleHeatmap, &keys) {
+        heatmap.shown = !heatmap.shown;
+    }
+}
+
+/// Follows the most visited cell among those changed since the last frame. The top
+/// level only drops when a reset clears the counts, an evicted hot cell keeps it.
+fn raise_top_level(
+    heatmap: ResMut<Heatmap>,
+    board: Res<Board>,
+    chunk_textures: Option<Res<ChunkTextures>>,
+) {
+    let dirty = match &chunk_textures {
+        Some(chunk_textures) => &chunk_textures.dirty,
+        None => &board.dirty,
+    };
+    let top = if board.visits.is_empty() {
+        0
+    } else {
+        dirty
+            .iter()
+            .filter_map(|cell| board.visits.get(cell))
+            .map(|visits| heat_level(*visits))
+            .fold(heatmap.top, usize::max)
+    };
+    heatmap
+        .map_unchanged(|heatmap| &mut heatmap.top)
+        .set_if_neq(top);
+}
+
+/// Recolors the heat materials and puts every tile on the material it now needs, or
+/// repaints every texel when drawing chunk textures
+fn redraw_heatmap(
+    heatmap: Res<Heatmap>,
+    board: Res<Board>,
+    tile_assets: Option<Res<TileAssets>>,
+    chunk_textures: Option<ResMut<ChunkTextures>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut tile_query: Query<&mut Handle<ColorMaterial>, With<Tile>>,
+) {
+    if let Some(mut chunk_textures) = chunk_textures {
+        chunk_textures
+            .dirty
+            .extend(board.grid.cells().map(|(cell, _)| cell));
+        return;
+    }
+    let Some(tile_assets) = tile_assets else {
+        return;
+    };
+
+    for (level, handle) in tile_assets.heat_materials.iter().enumerate() {
+        if let Some(material) = materials.get_mut(handle) {
+            material.color = heatmap.level_color(level);
+        }
+    }
+    for (cell, &tile) in board.tiles.iter() {
+        let Some(state) = board.grid.get(*cell) else {
+            continue;
+        };
+        if let Ok(mut tile_material) = tile_query.get_mut(tile) {
+            *tile_material = tile_assets.material(Some(&heatmap), &board.visits, *cell, state);
+        }
+    }
+}
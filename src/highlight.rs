@@ -0,0 +1,84 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+use crate::{
+    board::{Ant, WorldOrigin},
+    keybindings::{Action, KeyBindings},
+    lattice::Lattice,
+    pattern_input::pattern_input_closed,
+    Shortcuts,
+};
+
+/// Outlines the cell each ant stands on, the one its next step repaints. It follows the
+/// ants every frame, so it stays right while paused and when stepping by hand.
+pub struct AntHighlightPlugin<L: Lattice> {
+    /// Whether the outlines are drawn from the start, `Action::ToggleAntHighlight` flips it
+    pub enabled: bool,
+    lattice: PhantomData<L>,
+}
+
+impl<L: Lattice> AntHighlightPlugin<L> {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            lattice: PhantomData,
+        }
+    }
+}
+
+impl<L: Lattice> Plugin for AntHighlightPlugin<L> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AntHighlight(self.enabled))
+            .init_resource::<KeyBindings>()
+            .add_systems(
+                Update,
+                (
+                    toggle_ant_highlight
+                        .run_if(pattern_input_closed)
+                        .in_set(Shortcuts),
+                    draw_ant_highlight::<L>.run_if(|highlight: Res<AntHighlight>| highlight.0),
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct AntHighlight(pub bool);
+
+fn toggle_ant_highlight(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut highlight: ResMut<AntHighlight>,
+) {
+    if bindings.just_pressed(Action::ToggleAntHighlight, &keys) {
+        highlight.0 = !highlight.0;
+    }
+}
+
+/// Opposite hue at the other end of the lightness range, with enough chroma to stand
+/// apart from black and white tiles
+pub fn contrasting(background: Color) -> Color {
+    let background = Oklcha::from(background);
+    let lightness = if background.lightness > 0.6 { 0.3 } else { 0.9 };
+    Oklcha::new(
+        lightness,
+        background.chroma.max(0.15),
+        (background.hue + 180.) % 360.,
+        1.,
+    )
+    .into()
+}
+
+fn draw_ant_highlight<L: Lattice>(
+    mut gizmos: Gizmos,
+    background: Res<ClearColor>,
+    origin: Res<WorldOrigin>,
+    ant_query: Query<&Ant<L>>,
+) {
+    let color = contrasting(background.0);
+    for ant in ant_query.iter() {
+        gizmos.linestrip_2d(L::cell_outline(ant.0.cell - origin.0), color);
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::VecDeque;
+
+use bevy::{
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+use bevy_pancam::PanCam;
+
+use crate::{
+    board::StepCounter,
+    keybindings::{Action, KeyBindings},
+    pattern_input::pattern_input_closed,
+    theme::foreground,
+    Shortcuts,
+};
+
+/// How often the readout is refreshed, in seconds of wall time
+const HUD_REFRESH: f64 = 0.5;
+/// Frames the frame time graph spans
+const GRAPH_FRAMES: usize = 120;
+/// Frame time at the top of the graph, in milliseconds, longer frames are cut off there
+const GRAPH_MAX_MS: f32 = 50.;
+/// Size of the graph in screen pixels
+const GRAPH_SIZE: Vec2 = Vec2::new(240., 80.);
+/// Gap between the graph and the top right corner of the window, below the readout
+const GRAPH_OFFSET: Vec2 = Vec2::new(10., 60.);
+
+/// Rendering FPS and achieved simulation steps per second, toggled with `Action::ToggleHud`,
+/// and a graph of the last frame times, toggled with `Action::ToggleFrameGraph`, to spot
+/// stutters without a profiler
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(FrameTimeDiagnosticsPlugin)
+            .init_resource::<KeyBindings>()
+            .init_resource::<FrameGraph>()
+            .init_resource::<ClearColor>()
+            .add_systems(Startup, setup_hud)
+            .add_systems(
+                Update,
+                (
+                    toggle_hud.run_if(pattern_input_closed).in_set(Shortcuts),
+                    update_hud,
+                    toggle_frame_graph
+                        .run_if(pattern_input_closed)
+                        .in_set(Shortcuts),
+                    (record_frame_time, draw_frame_graph).chain(),
+                ),
+            );
+    }
+}
+
+#[derive(Component)]
+struct HudText;
+
+fn setup_hud(mut commands: Commands, background: Res<ClearColor>) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.,
+                color: foreground(background.0),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.),
+            right: Val::Px(10.),
+            ..default()
+        }),
+        HudText,
+    ));
+}
+
+fn toggle_hud(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut hud_query: Query<&mut Visibility, With<HudText>>,
+) {
+    if bindings.just_pressed(Action::ToggleHud, &keys) {
+        for mut visibility in hud_query.iter_mut() {
+            *visibility = match *visibility {
+                Visibility::Hidden => Visibility::Visible,
+                _ => Visibility::Hidden,
+            };
+        }
+    }
+}
+
+/// Steps per second are measured over wall time between refreshes, so they show the
+/// throughput actually achieved rather than the configured rate
+fn update_hud(
+    time: Res<Time<Real>>,
+    diagnostics: Res<DiagnosticsStore>,
+    step_counter: Res<StepCounter>,
+    mut last_sample: Local<Option<(f64, u64)>>,
+    mut hud_query: Query<&mut Text, With<HudText>>,
+) {
+    let now = time.elapsed_seconds_f64();
+    let (since, steps_before) = *last_sample.get_or_insert((now, step_counter.0));
+    let elapsed = now - since;
+    if elapsed < HUD_REFRESH {
+        return;
+    }
+    *last_sample = Some((now, step_counter.0));
+
+    // A reset puts the counter back to zero, count from there
+    let steps = step_counter.0.saturating_sub(steps_before);
+    let steps_per_second = steps as f64 / elapsed;
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or_default();
+
+    for mut text in hud_query.iter_mut() {
+        text.sections[0].value = format!("FPS: {fps:.0}\nSteps/s: {steps_per_second:.0}");
+    }
+}
+
+/// Wall time of the last `GRAPH_FRAMES` frames, recorded while hidden too
+#[derive(Resource)]
+pub struct FrameGraph {
+    pub enabled: bool,
+    /// Frame times in milliseconds, oldest first
+    pub times: VecDeque<f32>,
+}
+
+impl Default for FrameGraph {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            times: VecDeque::with_capacity(GRAPH_FRAMES),
+        }
+    }
+}
+
+fn toggle_frame_graph(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut graph: ResMut<FrameGraph>,
+) {
+    if bindings.just_pressed(Action::ToggleFrameGraph, &keys) {
+        graph.enabled = !graph.enabled;
+    }
+}
+
+fn record_frame_time(time: Res<Time<Real>>, mut graph: ResMut<FrameGraph>) {
+    if graph.times.len() == GRAPH_FRAMES {
+        graph.times.pop_front();
+    }
+    graph.times.push_back(time.delta_seconds() * 1000.);
+}
+
+fn draw_frame_graph(
+    graph: Res<FrameGraph>,
+    mut gizmos: Gizmos,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<PanCam>>,
+) {
+    if !graph.enabled {
+        return;
+    }
+    let Ok((camera_transform, projection)) = camera_query.get_single() else {
+        return;
+    };
+
+    // Gizmos live in world space, the projection scale turns pixels into world units
+    let area = projection.area;
+    let corner = camera_transform.translation.truncate() + area.max;
+    let size = GRAPH_SIZE * projection.scale;
+    let origin = corner - (GRAPH_OFFSET + GRAPH_SIZE) * projection.scale;
+    let point = |frame: usize, ms: f32| {
+        origin
+            + Vec2::new(
+                frame as f32 / (GRAPH_FRAMES - 1) as f32,
+                ms.min(GRAPH_MAX_MS) / GRAPH_MAX_MS,
+            ) * size
+    };
+
+    gizmos.linestrip_2d(
+        [origin + Vec2::Y * size.y, origin, origin + Vec2::X * size.x],
+        Color::srgba(0., 0., 0., 0.6),
+    );
+    // 60 FPS budget
+    gizmos.line_2d(
+        point(0, 1000. / 60.),
+        point(GRAPH_FRAMES - 1, 1000. / 60.),
+        Color::srgba(0., 0.5, 0., 0.6),
+    );
+    gizmos.linestrip_2d(
+        graph
+            .times
+            .iter()
+            .enumerate()
+            .map(|(frame, ms)| point(frame, *ms)),
+        Color::srgb(0.1, 0.1, 0.85),
+    );
+}
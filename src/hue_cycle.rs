@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::TileAssets,
+    keybindings::{Action, KeyBindings},
+    pattern::Pattern,
+    pattern_input::pattern_input_closed,
+    Shortcuts,
+};
+
+/// Seconds for a full turn of the color wheel
+const HUE_PERIOD: f32 = 10.;
+
+/// Slowly rotates the hue of every state color while keeping their offsets, so a
+/// settled pattern shimmers. Only the drawn tile materials change, `Pattern` keeps the
+/// base colors and they come back exactly when cycling stops.
+pub struct HueCyclePlugin {
+    /// Whether colors cycle from the start, `Action::ToggleHueCycle` flips it
+    pub enabled: bool,
+}
+
+impl Plugin for HueCyclePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HueCycle(self.enabled))
+            .init_resource::<KeyBindings>()
+            .add_systems(
+                Update,
+                (
+                    toggle_hue_cycle
+                        .run_if(pattern_input_closed)
+                        .in_set(Shortcuts),
+                    cycle_hues.run_if(resource_exists::<TileAssets>),
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource, Clone, Copy, PartialEq, Eq)]
+pub struct HueCycle(pub bool);
+
+fn toggle_hue_cycle(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut hue_cycle: ResMut<HueCycle>,
+) {
+    if bindings.just_pressed(Action::ToggleHueCycle, &keys) {
+        hue_cycle.0 = !hue_cycle.0;
+    }
+}
+
+fn cycle_hues(
+    hue_cycle: Res<HueCycle>,
+    time: Res<Time>,
+    pattern: Res<Pattern>,
+    tile_assets: Res<TileAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    // Nothing to do while off, unless the base colors still have to be put back
+    if !hue_cycle.0 && !hue_cycle.is_changed() {
+        return;
+    }
+
+    let shift = time.elapsed_seconds() % HUE_PERIOD / HUE_PERIOD * 360.;
+    for (base, handle) in pattern.colors.iter().zip(tile_assets.materials.iter()) {
+        let Some(material) = materials.get_mut(handle) else {
+            continue;
+        };
+        material.color = if hue_cycle.0 {
+            base.rotate_hue(shift)
+        } else {
+            *base
+        };
+    }
+}
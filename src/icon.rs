@@ -0,0 +1,34 @@
+use anyhow::Result;
+use bevy::{prelude::*, winit::WinitWindows};
+use winit::window::Icon;
+
+/// Gives the window the ant sprite as its icon. An unusable image is reported and the
+/// window keeps the default icon.
+pub struct WindowIconPlugin;
+
+impl Plugin for WindowIconPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, set_window_icon);
+    }
+}
+
+/// Decodes an image file into a window icon
+pub fn window_icon(bytes: &[u8]) -> Result<Icon> {
+    let image = image::load_from_memory(bytes)?.into_rgba8();
+    let (width, height) = image.dimensions();
+    Ok(Icon::from_rgba(image.into_raw(), width, height)?)
+}
+
+fn set_window_icon(windows: NonSend<WinitWindows>) {
+    let icon = match window_icon(include_bytes!("../assets/ant.png")) {
+        Ok(icon) => icon,
+        Err(err) => {
+            warn!("no window icon, assets/ant.png is unusable: {err:#}");
+            return;
+        }
+    };
+
+    for window in windows.windows.values() {
+        window.set_window_icon(Some(icon.clone()));
+    }
+}
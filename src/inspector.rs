@@ -0,0 +1,156 @@
+use std::marker::PhantomData;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_pancam::PanCam;
+
+use crate::{
+    board::{Board, CountVisits, WorldOrigin},
+    lattice::Lattice,
+    pattern::Pattern,
+    sim::{GridState, VisitCounts},
+    theme::foreground,
+};
+
+/// Gap between the cursor and the top left corner of the tooltip, in pixels
+const TOOLTIP_OFFSET: Vec2 = Vec2::new(14., 14.);
+
+/// Shows the coordinates, state and turn of the cell under the mouse in a tooltip next
+/// to the cursor, with its visit count while visits are counted. Read-only, so it works
+/// the same paused or running. The tooltip hides while the cursor is outside the board
+/// and while the camera is being dragged.
+pub struct InspectorPlugin<L: Lattice> {
+    lattice: PhantomData<L>,
+}
+
+impl<L: Lattice> Default for InspectorPlugin<L> {
+    fn default() -> Self {
+        Self {
+            lattice: PhantomData,
+        }
+    }
+}
+
+impl<L: Lattice> Plugin for InspectorPlugin<L> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ClearColor>()
+            .add_systems(Startup, setup_inspector)
+            .add_systems(Update, inspect_hovered_cell::<L>);
+    }
+}
+
+#[derive(Component)]
+struct InspectorText;
+
+fn setup_inspector(mut commands: Commands, background: Res<ClearColor>) {
+    commands.spawn((
+        TextBundle {
+            visibility: Visibility::Hidden,
+            ..TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 16.,
+                    color: foreground(background.0),
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                padding: UiRect::all(Val::Px(4.)),
+                ..default()
+            })
+            .with_background_color(background.0.with_alpha(0.85))
+        },
+        InspectorText,
+    ));
+}
+
+/// One line about `cell`: its coordinates, then its state with the state's turn and
+/// color, or `unvisited`. `visits` adds how often the ants visited it.
+pub fn describe_cell(
+    grid: &GridState,
+    pattern: &Pattern,
+    visits: Option<&VisitCounts>,
+    cell: IVec2,
+) -> String {
+    let Some(state) = grid.get(cell) else {
+        return format!("({}, {}): unvisited", cell.x, cell.y);
+    };
+    let mut turn = pattern
+        .turns
+        .get(state)
+        .map(ToString::to_string)
+        .unwrap_or_default();
+    if let Some(distance) = pattern
+        .distances
+        .get(state)
+        .filter(|distance| **distance != 1)
+    {
+        turn.push_str(&format!("*{distance}"));
+    }
+    let color = pattern
+        .colors
+        .get(state)
+        .map(|color| color.to_srgba().to_hex())
+        .unwrap_or_default();
+    let mut line = format!("({}, {}): state {state} {turn} {color}", cell.x, cell.y);
+    if let Some(visits) = visits {
+        let count = visits.get(&cell).copied().unwrap_or_default();
+        line.push_str(&format!(", {count} visits"));
+    }
+    line
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn inspect_hovered_cell<L: Lattice>(
+    board: Res<Board>,
+    pattern: Res<Pattern>,
+    origin: Res<WorldOrigin>,
+    counting: Res<CountVisits>,
+    mouse: Option<Res<ButtonInput<MouseButton>>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform, &PanCam)>,
+    mut text_query: Query<(&mut Text, &mut Style, &mut Visibility), With<InspectorText>>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let Ok((camera, camera_transform, pancam)) = camera_query.get_single() else {
+        return;
+    };
+
+    // A drag moves the board under a still cursor, the tooltip would flicker between cells
+    let dragging = pancam.enabled
+        && mouse.is_some_and(|mouse| mouse.any_pressed(pancam.grab_buttons.iter().copied()));
+    // Nothing to show while the cursor is outside the board's part of the window
+    let hovered = window
+        .cursor_position()
+        .filter(|_| !dragging)
+        .filter(|cursor| {
+            camera
+                .logical_viewport_rect()
+                .is_some_and(|viewport| viewport.contains(*cursor))
+        })
+        .and_then(|cursor| {
+            let position = camera.viewport_to_world_2d(camera_transform, cursor)?;
+            let cell = origin.world_to_cell::<L>(position);
+            let visits = counting.0.then_some(&board.visits);
+            Some((cursor, describe_cell(&board.grid, &pattern, visits, cell)))
+        });
+
+    for (mut text, mut style, mut visibility) in text_query.iter_mut() {
+        let Some((cursor, description)) = &hovered else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        visibility.set_if_neq(Visibility::Inherited);
+        if text.sections[0].value != *description {
+            text.sections[0].value.clone_from(description);
+        }
+        let corner = *cursor + TOOLTIP_OFFSET;
+        let (left, top) = (Val::Px(corner.x), Val::Px(corner.y));
+        if (style.left, style.top) != (left, top) {
+            style.left = left;
+            style.top = top;
+        }
+    }
+}
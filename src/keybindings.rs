@@ -0,0 +1,233 @@
+use bevy::{
+    prelude::*,
+    reflect::{DynamicEnum, DynamicVariant, TypeInfo, Typed, VariantInfo},
+    utils::HashMap,
+};
+
+/// Something a key press can trigger
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Pause,
+    PatternInput,
+    NextPreset,
+    ToggleHud,
+    CenterOnAnt,
+    ToggleTrail,
+    ToggleHueCycle,
+    ToggleGridLines,
+    ToggleAntHighlight,
+    ToggleMute,
+    AddAnt,
+    RemoveAnt,
+    ToggleGrowthGraph,
+    ToggleAnts,
+    ToggleFrameGraph,
+    ToggleHeatmap,
+    ToggleAntLabels,
+    ToggleMinimap,
+    OpenMenu,
+    ToggleCameraLock,
+    /// Held with Control, see `Action::with_control`
+    CopyShareLink,
+}
+
+impl Action {
+    pub const ALL: [Action; 21] = [
+        Action::Pause,
+        Action::PatternInput,
+        Action::NextPreset,
+        Action::ToggleHud,
+        Action::CenterOnAnt,
+        Action::ToggleTrail,
+        Action::ToggleHueCycle,
+        Action::ToggleGridLines,
+        Action::ToggleAntHighlight,
+        Action::ToggleMute,
+        Action::AddAnt,
+        Action::RemoveAnt,
+        Action::ToggleGrowthGraph,
+        Action::ToggleAnts,
+        Action::ToggleFrameGraph,
+        Action::ToggleHeatmap,
+        Action::ToggleAntLabels,
+        Action::ToggleMinimap,
+        Action::OpenMenu,
+        Action::ToggleCameraLock,
+        Action::CopyShareLink,
+    ];
+
+    /// Key used in the keymap file
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Pause => "pause",
+            Action::PatternInput => "pattern_input",
+            Action::NextPreset => "next_preset",
+            Action::ToggleHud => "toggle_hud",
+            Action::CenterOnAnt => "center_on_ant",
+            Action::ToggleTrail => "toggle_trail",
+            Action::ToggleHueCycle => "toggle_hue_cycle",
+            Action::ToggleGridLines => "toggle_grid_lines",
+            Action::ToggleAntHighlight => "toggle_ant_highlight",
+            Action::ToggleMute => "toggle_mute",
+            Action::AddAnt => "add_ant",
+            Action::RemoveAnt => "remove_ant",
+            Action::ToggleGrowthGraph => "toggle_growth_graph",
+            Action::ToggleAnts => "toggle_ants",
+            Action::ToggleFrameGraph => "toggle_frame_graph",
+            Action::ToggleHeatmap => "toggle_heatmap",
+            Action::ToggleAntLabels => "toggle_ant_labels",
+            Action::ToggleMinimap => "toggle_minimap",
+            Action::OpenMenu => "open_menu",
+            Action::ToggleCameraLock => "toggle_camera_lock",
+            Action::CopyShareLink => "copy_share_link",
+        }
+    }
+
+    pub fn default_key(self) -> KeyCode {
+        match self {
+            Action::Pause => KeyCode::Space,
+            Action::PatternInput => KeyCode::KeyP,
+            Action::NextPreset => KeyCode::Tab,
+            Action::ToggleHud => KeyCode::KeyF,
+            Action::CenterOnAnt => KeyCode::KeyC,
+            Action::ToggleTrail => KeyCode::KeyT,
+            Action::ToggleHueCycle => KeyCode::KeyK,
+            Action::ToggleGridLines => KeyCode::KeyG,
+            Action::ToggleAntHighlight => KeyCode::KeyO,
+            Action::ToggleMute => KeyCode::KeyM,
+            Action::AddAnt => KeyCode::KeyA,
+            Action::RemoveAnt => KeyCode::KeyX,
+            Action::ToggleGrowthGraph => KeyCode::KeyV,
+            Action::ToggleAnts => KeyCode::KeyH,
+            Action::ToggleFrameGraph => KeyCode::F3,
+            Action::ToggleHeatmap => KeyCode::KeyE,
+            Action::ToggleAntLabels => KeyCode::KeyL,
+            Action::ToggleMinimap => KeyCode::KeyN,
+            Action::OpenMenu => KeyCode::Escape,
+            Action::ToggleCameraLock => KeyCode::KeyZ,
+            Action::CopyShareLink => KeyCode::KeyC,
+        }
+    }
+
+    /// Whether the key of the action is pressed with Control. Such an action can share its
+    /// key with a plain one, as Ctrl+C and C do.
+    pub fn with_control(self) -> bool {
+        matches!(self, Action::CopyShareLink)
+    }
+}
+
+/// Key bound to each action, every input system looks its keys up here
+#[derive(Resource)]
+pub struct KeyBindings {
+    keys: HashMap<Action, KeyCode>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            keys: Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_key()))
+                .collect(),
+        }
+    }
+}
+
+/// `KeyCode` variant by name, `A` and `1` are accepted for `KeyA` and `Digit1`
+fn parse_key(name: &str) -> Option<KeyCode> {
+    let name = match name.chars().collect::<Vec<_>>()[..] {
+        [c] if c.is_ascii_alphabetic() => format!("Key{}", c.to_ascii_uppercase()),
+        [c] if c.is_ascii_digit() => format!("Digit{c}"),
+        _ => name.to_owned(),
+    };
+    // `from_reflect` panics on variants that don't exist
+    let TypeInfo::Enum(info) = KeyCode::type_info() else {
+        return None;
+    };
+    match info.variant(&name)? {
+        VariantInfo::Unit(_) => {
+            KeyCode::from_reflect(&DynamicEnum::new(name, DynamicVariant::Unit))
+        }
+        _ => None,
+    }
+}
+
+impl KeyBindings {
+    pub fn key(&self, action: Action) -> KeyCode {
+        self.keys
+            .get(&action)
+            .copied()
+            .unwrap_or(action.default_key())
+    }
+
+    /// Whether the key of `action` went down this frame, with Control held exactly when
+    /// the action is `Action::with_control`, so Ctrl+C doesn't trigger C's action as well
+    pub fn just_pressed(&self, action: Action, keys: &ButtonInput<KeyCode>) -> bool {
+        keys.just_pressed(self.key(action))
+            && keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+                == action.with_control()
+    }
+
+    /// Reads a keymap such as `pause = "Space"`. Entries that can't be used are reported
+    /// with `warn!` and leave their action on its default key.
+    pub fn parse(text: &str) -> Self {
+        let mut bindings = Self::default();
+        let table = match text.parse::<toml::Table>() {
+            Ok(table) => table,
+            Err(err) => {
+                warn!("invalid keymap, using the default keys: {err}");
+                return bindings;
+            }
+        };
+
+        let mut assigned = Vec::new();
+        for (name, value) in table.iter() {
+            let Some(action) = Action::ALL.into_iter().find(|action| action.name() == name) else {
+                warn!("unknown action `{name}` in keymap");
+                continue;
+            };
+            let Some(key) = value.as_str().and_then(parse_key) else {
+                warn!("invalid key {value} for `{name}` in keymap");
+                continue;
+            };
+            bindings.keys.insert(action, key);
+            assigned.push(action);
+        }
+
+        // Reverting one action can collide with another, repeat until every key is unique
+        loop {
+            let conflicts: Vec<(Action, Action)> = assigned
+                .iter()
+                .filter_map(|&action| {
+                    let key = bindings.key(action);
+                    Action::ALL
+                        .into_iter()
+                        .find(|&other| {
+                            other != action
+                                && bindings.key(other) == key
+                                && other.with_control() == action.with_control()
+                        })
+                        .map(|other| (action, other))
+                })
+                .collect();
+            if conflicts.is_empty() {
+                break;
+            }
+
+            for (action, other) in conflicts {
+                warn!(
+                    "`{}` and `{}` are both bound to {:?} in keymap, `{}` keeps its default {:?}",
+                    action.name(),
+                    other.name(),
+                    bindings.key(action),
+                    action.name(),
+                    action.default_key()
+                );
+                bindings.keys.insert(action, action.default_key());
+                assigned.retain(|assigned| *assigned != action);
+            }
+        }
+
+        bindings
+    }
+}
@@ -0,0 +1,548 @@
+use std::{f32::consts::FRAC_PI_2, fmt::Debug};
+
+use bevy::prelude::*;
+
+use crate::{pattern::Turn, sim::Border, TILE_SIZE};
+
+/// Cell layout the ant walks on. Cells are addressed by integer coordinates and the ant
+/// faces one of a fixed set of headings; everything grid-specific goes through here so
+/// the stepping and rendering systems stay the same for every lattice.
+pub trait Lattice: Send + Sync + 'static {
+    type Heading: Copy + Debug + PartialEq + Eq + Send + Sync + 'static;
+
+    const INITIAL_HEADING: Self::Heading;
+
+    /// Every heading, saved files name them by their `Debug` form
+    const HEADINGS: &'static [Self::Heading];
+
+    /// Heading an ant placed on `cell` can start with
+    fn start_heading(_cell: IVec2) -> Self::Heading {
+        Self::INITIAL_HEADING
+    }
+
+    /// Cell reached by moving one step from `cell` towards `heading`
+    fn neighbor(cell: IVec2, heading: Self::Heading) -> IVec2;
+
+    /// Angle between neighbouring headings in degrees
+    const TURN_DEGREES: i32;
+
+    /// Applies `turn`, rounding angles that aren't a multiple of `TURN_DEGREES`
+    fn turn(heading: Self::Heading, turn: Turn) -> Self::Heading;
+
+    /// Whether a turn by `degrees` lands on a heading the ant can step towards
+    fn check_turn(degrees: i32) -> anyhow::Result<()> {
+        if degrees % Self::TURN_DEGREES != 0 {
+            anyhow::bail!(
+                "turns on this grid must be multiples of {}°",
+                Self::TURN_DEGREES
+            );
+        }
+        Ok(())
+    }
+
+    /// Whether the ant can move `distance` cells on along one heading, as `*` asks of it
+    fn check_distance(_distance: u8) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn reverse(heading: Self::Heading) -> Self::Heading;
+
+    /// Heading of an ant that turned towards `heading` but stays on its cell, as at a
+    /// border that stops it
+    fn stay(heading: Self::Heading) -> Self::Heading {
+        heading
+    }
+
+    /// Mirror image of `cell` and `heading` across the world x axis
+    fn reflect(cell: IVec2, heading: Self::Heading) -> (IVec2, Self::Heading);
+
+    /// Image of `cell` and `heading` under a half turn around the world origin
+    fn half_turn(cell: IVec2, heading: Self::Heading) -> (IVec2, Self::Heading) {
+        (-cell, Self::reverse(heading))
+    }
+
+    /// Center of `cell` in world units
+    fn cell_to_world(cell: IVec2) -> Vec2;
+
+    /// Cell whose area contains `position`
+    fn world_to_cell(position: Vec2) -> IVec2;
+
+    /// Distance in steps-of-rings from the origin: Chebyshev distance on the square
+    /// lattice, where the rings are squares, and the hex distance on the hex lattice
+    fn radius(cell: IVec2) -> u32;
+
+    /// Ant sprite rotation for `heading`, the sprite itself points up
+    fn heading_to_rotation(heading: Self::Heading) -> Quat;
+
+    /// Mesh drawn for a single painted cell
+    fn tile_mesh() -> Mesh;
+
+    /// Rotation of the tile mesh drawn at `cell`
+    fn tile_rotation(_cell: IVec2) -> Quat {
+        Quat::IDENTITY
+    }
+
+    /// Closed outline of `cell` in world units
+    fn cell_outline(cell: IVec2) -> Vec<Vec2>;
+
+    /// Closed outline of `border` in world units
+    fn border_outline(border: &Border) -> Vec<Vec2> {
+        let corners = [
+            border.min,
+            IVec2::new(border.max.x, border.min.y),
+            border.max,
+            IVec2::new(border.min.x, border.max.y),
+            border.min,
+        ];
+        corners.into_iter().map(Self::cell_to_world).collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    South,
+    West,
+    East,
+}
+
+/// The classic square grid with four headings
+pub struct SquareLattice;
+
+impl Lattice for SquareLattice {
+    type Heading = Direction;
+
+    const INITIAL_HEADING: Direction = Direction::North;
+
+    const HEADINGS: &'static [Direction] = &[
+        Direction::North,
+        Direction::South,
+        Direction::West,
+        Direction::East,
+    ];
+
+    fn neighbor(cell: IVec2, heading: Direction) -> IVec2 {
+        cell + match heading {
+            Direction::North => IVec2::Y,
+            Direction::South => IVec2::NEG_Y,
+            Direction::West => IVec2::NEG_X,
+            Direction::East => IVec2::X,
+        }
+    }
+
+    const TURN_DEGREES: i32 = 90;
+
+    fn turn(heading: Direction, turn: Turn) -> Direction {
+        match turn {
+            Turn::Left => match heading {
+                Direction::North => Direction::West,
+                Direction::South => Direction::East,
+                Direction::West => Direction::South,
+                Direction::East => Direction::North,
+            },
+            Turn::Right => match heading {
+                Direction::North => Direction::East,
+                Direction::South => Direction::West,
+                Direction::West => Direction::North,
+                Direction::East => Direction::South,
+            },
+            Turn::Degrees(_) | Turn::Identity => {
+                (0..turn.clockwise_steps(Self::TURN_DEGREES).rem_euclid(4))
+                    .fold(heading, |heading, _| Self::turn(heading, Turn::Right))
+            }
+        }
+    }
+
+    fn reverse(heading: Direction) -> Direction {
+        match heading {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+            Direction::East => Direction::West,
+        }
+    }
+
+    fn reflect(cell: IVec2, heading: Direction) -> (IVec2, Direction) {
+        let heading = match heading {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            heading => heading,
+        };
+        (IVec2::new(cell.x, -cell.y), heading)
+    }
+
+    fn cell_to_world(cell: IVec2) -> Vec2 {
+        cell.as_vec2() * TILE_SIZE
+    }
+
+    fn world_to_cell(position: Vec2) -> IVec2 {
+        (position / TILE_SIZE).round().as_ivec2()
+    }
+
+    fn radius(cell: IVec2) -> u32 {
+        cell.x.unsigned_abs().max(cell.y.unsigned_abs())
+    }
+
+    fn heading_to_rotation(heading: Direction) -> Quat {
+        Quat::from_rotation_z(match heading {
+            Direction::North => 0.,
+            Direction::West => FRAC_PI_2,
+            Direction::South => 2. * FRAC_PI_2,
+            Direction::East => -FRAC_PI_2,
+        })
+    }
+
+    fn tile_mesh() -> Mesh {
+        Rectangle::new(TILE_SIZE, TILE_SIZE).into()
+    }
+
+    fn cell_outline(cell: IVec2) -> Vec<Vec2> {
+        let center = Self::cell_to_world(cell);
+        let half = TILE_SIZE / 2.;
+        vec![
+            center + Vec2::new(-half, -half),
+            center + Vec2::new(half, -half),
+            center + Vec2::new(half, half),
+            center + Vec2::new(-half, half),
+            center + Vec2::new(-half, -half),
+        ]
+    }
+
+    fn border_outline(border: &Border) -> Vec<Vec2> {
+        let min = Self::cell_to_world(border.min) - TILE_SIZE / 2.;
+        let max = Self::cell_to_world(border.max) + TILE_SIZE / 2.;
+        vec![
+            min,
+            Vec2::new(max.x, min.y),
+            max,
+            Vec2::new(min.x, max.y),
+            min,
+        ]
+    }
+}
+
+/// Headings of a pointy-top hex cell, counter-clockwise from east
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HexDirection {
+    East,
+    NorthEast,
+    NorthWest,
+    West,
+    SouthWest,
+    SouthEast,
+}
+
+impl HexDirection {
+    const ALL: [HexDirection; 6] = [
+        HexDirection::East,
+        HexDirection::NorthEast,
+        HexDirection::NorthWest,
+        HexDirection::West,
+        HexDirection::SouthWest,
+        HexDirection::SouthEast,
+    ];
+
+    fn rotated(self, steps: usize) -> Self {
+        Self::ALL[(self as usize + steps) % Self::ALL.len()]
+    }
+}
+
+/// Pointy-top hexagons in axial coordinates `(q, r)`, with `r` growing northwards.
+/// `L`/`R` turn the ant 60° counter-clockwise/clockwise.
+pub struct HexLattice;
+
+impl HexLattice {
+    /// Circumradius chosen so horizontal neighbours are `TILE_SIZE` apart
+    pub const RADIUS: f32 = TILE_SIZE / SQRT_3;
+}
+
+const SQRT_3: f32 = 1.732_050_8;
+
+impl Lattice for HexLattice {
+    type Heading = HexDirection;
+
+    const INITIAL_HEADING: HexDirection = HexDirection::East;
+
+    const HEADINGS: &'static [HexDirection] = &HexDirection::ALL;
+
+    fn neighbor(cell: IVec2, heading: HexDirection) -> IVec2 {
+        cell + match heading {
+            HexDirection::East => IVec2::new(1, 0),
+            HexDirection::NorthEast => IVec2::new(0, 1),
+            HexDirection::NorthWest => IVec2::new(-1, 1),
+            HexDirection::West => IVec2::new(-1, 0),
+            HexDirection::SouthWest => IVec2::new(0, -1),
+            HexDirection::SouthEast => IVec2::new(1, -1),
+        }
+    }
+
+    const TURN_DEGREES: i32 = 60;
+
+    fn turn(heading: HexDirection, turn: Turn) -> HexDirection {
+        match turn {
+            Turn::Left => heading.rotated(1),
+            Turn::Right => heading.rotated(5),
+            Turn::Degrees(_) | Turn::Identity => {
+                heading.rotated((-turn.clockwise_steps(Self::TURN_DEGREES)).rem_euclid(6) as usize)
+            }
+        }
+    }
+
+    fn reverse(heading: HexDirection) -> HexDirection {
+        heading.rotated(3)
+    }
+
+    fn reflect(cell: IVec2, heading: HexDirection) -> (IVec2, HexDirection) {
+        // Flipping y keeps the row's world x, which moves the cell along q by r
+        let heading = HexDirection::ALL[(6 - heading as usize) % 6];
+        (IVec2::new(cell.x + cell.y, -cell.y), heading)
+    }
+
+    fn cell_to_world(cell: IVec2) -> Vec2 {
+        let cell = cell.as_vec2();
+        Vec2::new(
+            Self::RADIUS * SQRT_3 * (cell.x + cell.y / 2.),
+            Self::RADIUS * 1.5 * cell.y,
+        )
+    }
+
+    fn world_to_cell(position: Vec2) -> IVec2 {
+        let r = position.y / (Self::RADIUS * 1.5);
+        let q = position.x / (Self::RADIUS * SQRT_3) - r / 2.;
+
+        // Round in cube coordinates and fix up the component with the largest error
+        let s = -q - r;
+        let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+        let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+        if dq > dr && dq > ds {
+            rq = -rr - rs;
+        } else if dr > ds {
+            rr = -rq - rs;
+        }
+        IVec2::new(rq as i32, rr as i32)
+    }
+
+    fn radius(cell: IVec2) -> u32 {
+        cell.x
+            .unsigned_abs()
+            .max(cell.y.unsigned_abs())
+            .max((cell.x + cell.y).unsigned_abs())
+    }
+
+    fn heading_to_rotation(heading: HexDirection) -> Quat {
+        let angle = (heading as usize as f32) * 60_f32.to_radians();
+        Quat::from_rotation_z(angle - FRAC_PI_2)
+    }
+
+    fn tile_mesh() -> Mesh {
+        RegularPolygon::new(Self::RADIUS, 6).into()
+    }
+
+    fn cell_outline(cell: IVec2) -> Vec<Vec2> {
+        polygon(Self::cell_to_world(cell), Self::RADIUS, 6, FRAC_PI_2)
+    }
+}
+
+/// Headings of the triangular lattice, counter-clockwise from 30°. Each one is
+/// perpendicular to an edge: up-pointing cells are left through `NorthEast`, `NorthWest`
+/// or `South`, down-pointing ones through `North`, `SouthWest` or `SouthEast`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TriDirection {
+    NorthEast,
+    North,
+    NorthWest,
+    SouthWest,
+    South,
+    SouthEast,
+}
+
+impl TriDirection {
+    const ALL: [TriDirection; 6] = [
+        TriDirection::NorthEast,
+        TriDirection::North,
+        TriDirection::NorthWest,
+        TriDirection::SouthWest,
+        TriDirection::South,
+        TriDirection::SouthEast,
+    ];
+
+    fn rotated(self, steps: usize) -> Self {
+        Self::ALL[(self as usize + steps) % Self::ALL.len()]
+    }
+}
+
+/// Equilateral triangles in rows, cell `(x, y)` points up when `x + y` is even and down
+/// otherwise. Horizontal neighbours share a slanted edge, vertical ones the flat edge.
+///
+/// The heading is the direction the ant entered its cell through, so it always crosses
+/// the edge opposite one of the other two. `L`/`R` turn it 60° counter-clockwise/clockwise,
+/// which points it straight at the left/right one of those edges; the cell's third edge
+/// is the one it came in through and can't be taken. Six `L`s walk once around a vertex.
+pub struct TriangularLattice;
+
+impl TriangularLattice {
+    /// Edge length, chosen so a cell covers about the area of a square tile
+    pub const SIDE: f32 = TILE_SIZE * 1.5;
+    const ROW_HEIGHT: f32 = Self::SIDE * SQRT_3 / 2.;
+
+    fn points_up(cell: IVec2) -> bool {
+        (cell.x + cell.y).rem_euclid(2) == 0
+    }
+}
+
+impl Lattice for TriangularLattice {
+    type Heading = TriDirection;
+
+    // Entering the up-pointing origin cell from below
+    const INITIAL_HEADING: TriDirection = TriDirection::North;
+
+    const HEADINGS: &'static [TriDirection] = &TriDirection::ALL;
+
+    /// Entering through the flat edge, from below or above
+    fn start_heading(cell: IVec2) -> TriDirection {
+        if Self::points_up(cell) {
+            TriDirection::North
+        } else {
+            TriDirection::South
+        }
+    }
+
+    fn neighbor(cell: IVec2, heading: TriDirection) -> IVec2 {
+        cell + match heading {
+            TriDirection::NorthEast | TriDirection::SouthEast => IVec2::X,
+            TriDirection::NorthWest | TriDirection::SouthWest => IVec2::NEG_X,
+            TriDirection::North => IVec2::Y,
+            TriDirection::South => IVec2::NEG_Y,
+        }
+    }
+
+    const TURN_DEGREES: i32 = 60;
+
+    fn turn(heading: TriDirection, turn: Turn) -> TriDirection {
+        match turn {
+            Turn::Left => heading.rotated(1),
+            Turn::Right => heading.rotated(5),
+            // Straight on points at a vertex, an unchecked inert state turns right instead
+            Turn::Identity => heading.rotated(5),
+            Turn::Degrees(degrees) => {
+                // Only odd steps point at an edge, round to the nearest of those
+                let steps = turn.clockwise_steps(Self::TURN_DEGREES);
+                let steps = match steps % 2 {
+                    0 if degrees >= steps * Self::TURN_DEGREES => steps + 1,
+                    0 => steps - 1,
+                    _ => steps,
+                };
+                heading.rotated((-steps).rem_euclid(6) as usize)
+            }
+        }
+    }
+
+    /// Turning by an even number of 60° steps would point the ant at a vertex
+    fn check_turn(degrees: i32) -> anyhow::Result<()> {
+        if degrees % 120 != 60 && degrees % 120 != -60 {
+            anyhow::bail!("turns on the triangular grid must be odd multiples of 60°");
+        }
+        Ok(())
+    }
+
+    /// Every hop flips the cell between pointing up and down, so a heading the ant leaves
+    /// one cell through only touches a vertex of the next
+    fn check_distance(distance: u8) -> anyhow::Result<()> {
+        if distance > 1 {
+            anyhow::bail!("the triangular grid moves one cell at a time");
+        }
+        Ok(())
+    }
+
+    fn reverse(heading: TriDirection) -> TriDirection {
+        heading.rotated(3)
+    }
+
+    /// The heading has to be one the cell is entered through, or the next turn could
+    /// point at a vertex, so the ant turns back as if it had stepped out and in again
+    fn stay(heading: TriDirection) -> TriDirection {
+        Self::reverse(heading)
+    }
+
+    fn reflect(cell: IVec2, heading: TriDirection) -> (IVec2, TriDirection) {
+        // The x axis runs along the bottom of row 0, so rows swap with their image below
+        let heading = TriDirection::ALL[(11 - heading as usize) % 6];
+        (IVec2::new(cell.x, -cell.y - 1), heading)
+    }
+
+    fn half_turn(cell: IVec2, heading: TriDirection) -> (IVec2, TriDirection) {
+        // The origin is the middle of the origin cell's bottom edge
+        (IVec2::new(-cell.x, -cell.y - 1), Self::reverse(heading))
+    }
+
+    fn cell_to_world(cell: IVec2) -> Vec2 {
+        // Centroid, a third of the way up from the flat edge
+        let offset = if Self::points_up(cell) { 1. } else { 2. } / 3.;
+        Vec2::new(
+            cell.x as f32 * Self::SIDE / 2.,
+            (cell.y as f32 + offset) * Self::ROW_HEIGHT,
+        )
+    }
+
+    fn world_to_cell(position: Vec2) -> IVec2 {
+        let y = (position.y / Self::ROW_HEIGHT).floor();
+        // Height within the row and x in half edges
+        let t = position.y / Self::ROW_HEIGHT - y;
+        let u = position.x / (Self::SIDE / 2.);
+        let y = y as i32;
+
+        let base = u.floor() as i32;
+        (base - 1..=base + 1)
+            .map(|x| IVec2::new(x, y))
+            .find(|&cell| {
+                let half_width = if Self::points_up(cell) { 1. - t } else { t };
+                (u - cell.x as f32).abs() <= half_width
+            })
+            .unwrap_or(IVec2::new(u.round() as i32, y))
+    }
+
+    fn radius(cell: IVec2) -> u32 {
+        // A row is about two columns tall
+        (cell.x.unsigned_abs() / 2).max(cell.y.unsigned_abs())
+    }
+
+    fn heading_to_rotation(heading: TriDirection) -> Quat {
+        let angle = (30. + heading as usize as f32 * 60.).to_radians();
+        Quat::from_rotation_z(angle - FRAC_PI_2)
+    }
+
+    fn tile_mesh() -> Mesh {
+        RegularPolygon::new(Self::SIDE / SQRT_3, 3).into()
+    }
+
+    fn tile_rotation(cell: IVec2) -> Quat {
+        if Self::points_up(cell) {
+            Quat::IDENTITY
+        } else {
+            Quat::from_rotation_z(2. * FRAC_PI_2)
+        }
+    }
+
+    fn cell_outline(cell: IVec2) -> Vec<Vec2> {
+        let first_corner = if Self::points_up(cell) { 1. } else { 3. } * FRAC_PI_2;
+        polygon(
+            Self::cell_to_world(cell),
+            Self::SIDE / SQRT_3,
+            3,
+            first_corner,
+        )
+    }
+}
+
+/// Closed outline of a regular polygon with its first corner `first_corner` radians
+/// counter-clockwise from the x axis
+fn polygon(center: Vec2, circumradius: f32, sides: u32, first_corner: f32) -> Vec<Vec2> {
+    let mut corners: Vec<Vec2> = (0..sides)
+        .map(|i| {
+            let angle = first_corner + i as f32 * std::f32::consts::TAU / sides as f32;
+            center + circumradius * Vec2::from_angle(angle)
+        })
+        .collect();
+    corners.push(corners[0]);
+    corners
+}
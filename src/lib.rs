@@ -0,0 +1,115 @@
+//! Langton's ant and its multi-state generalisations.
+//!
+//! [`sim`] holds the renderer-independent automaton, the remaining modules are the
+//! Bevy plugins the `langtons-ant` binary is built from.
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+pub mod ant_keys;
+pub mod ant_labels;
+pub mod board;
+pub mod camera;
+pub mod compare;
+pub mod fade;
+pub mod gamepad;
+pub mod grid_lines;
+pub mod growth;
+pub mod heatmap;
+pub mod highlight;
+pub mod hud;
+pub mod hue_cycle;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod icon;
+pub mod inspector;
+pub mod keybindings;
+pub mod lattice;
+pub mod menu;
+pub mod minimap;
+pub mod palette;
+pub mod pattern;
+pub mod pattern_input;
+pub mod presets;
+pub mod progress;
+pub mod rle;
+pub mod scheduler;
+pub mod share;
+pub mod sim;
+pub mod sound;
+pub mod summary;
+pub mod symmetry;
+pub mod theme;
+pub mod tile_texture;
+pub mod title;
+pub mod touch;
+pub mod trail;
+#[cfg(feature = "ui")]
+pub mod ui;
+pub mod view;
+pub mod wheel_rate;
+
+/// Size of a square cell in world units
+pub const TILE_SIZE: f32 = 20.;
+
+/// Depth of the tiles and chunk textures, below everything else on the board. Gizmos,
+/// such as the trail, grid lines and ant outlines, and the UI are drawn over every
+/// sprite and mesh whatever their depth.
+pub const TILE_Z: f32 = -1.;
+
+/// Depth of the ant sprites, above the tiles
+pub const ANT_Z: f32 = 0.;
+
+/// Fastest tick rate accepted, in Hz
+pub const MAX_RATE: f64 = 100_000.;
+
+/// Slowest tick rate the runtime controls go down to, in Hz. `--rate` takes anything
+/// above zero.
+pub const MIN_RATE: f64 = 0.1;
+
+/// Factor one step of the runtime rate controls, a D-pad press or a wheel notch, changes
+/// the rate by, so slow and fast rates change alike
+pub const RATE_STEP: f64 = 1.25;
+
+/// Tick rate for display, with one decimal below 10 Hz so slow motion rates tell apart
+pub fn format_rate(rate: f64) -> String {
+    if rate < 10. {
+        format!("{rate:.1} Hz")
+    } else {
+        format!("{rate:.0} Hz")
+    }
+}
+
+/// Systems driven by keyboard shortcuts, so other input surfaces can suppress them
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Shortcuts;
+
+/// The one source of randomness, seeded from `--seed` so a run can be reproduced.
+///
+/// Draws happen in a fixed order: the random colors of the starting pattern, state by
+/// state, those of the compared pattern, then those of every pattern applied at runtime,
+/// in the order they are applied.
+/// Only the random palette draws colors, and only for patterns of up to 8 states.
+/// Anything new that needs randomness should take it from here and document its place.
+#[derive(Resource)]
+pub struct SimRng(pub StdRng);
+
+impl SimRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl Default for SimRng {
+    fn default() -> Self {
+        Self(StdRng::from_entropy())
+    }
+}
+
+#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AppState {
+    Paused,
+    #[default]
+    Running,
+    /// The start menu is up and nothing steps, see `menu::MenuPlugin`
+    Menu,
+}
@@ -1,262 +1,1091 @@
-use anyhow::{bail, Result};
+use std::{io::Read, path::PathBuf, time::Duration};
+
+use anyhow::{bail, Context, Result};
 use bevy::{
-    log::LogPlugin,
+    log::{Level, LogPlugin},
     prelude::*,
-    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
-    winit::WinitWindows,
+    utils::Instant,
+    window::{PrimaryWindow, WindowFocused, WindowMode, WindowResolution},
 };
-use bevy_embedded_assets::EmbeddedAssetPlugin;
+use bevy_embedded_assets::{EmbeddedAssetPlugin, PluginMode};
 use bevy_pancam::*;
-use clap::Parser;
-use itertools::Itertools;
-use rand::prelude::*;
-use winit::window::Icon;
+use clap::{builder::PossibleValuesParser, Parser};
 
-const ANT_SPEED: f32 = 20.;
-const TILE_SIZE: f32 = 20.;
+use langtons_ant::{
+    ant_keys::AntKeysPlugin,
+    ant_labels::AntLabelsPlugin,
+    board::{
+        initial_ants, AntiAnt, Board, BoardPlugin, CollisionRule, MaxSteps, MirrorAnts,
+        RadiusLimits, StartingAnts, StepsPerTick, StopOnOrigin, TileCap, TogglePause, Warmup,
+    },
+    camera::{CameraKeysPlugin, InitialZoomPlugin},
+    compare::{ComparePattern, ComparePlugin},
+    fade::FadeInPlugin,
+    gamepad::GamepadPlugin,
+    grid_lines::GridLinesPlugin,
+    growth::GrowthGraphPlugin,
+    heatmap::{HeatGradient, HeatmapPlugin},
+    highlight::AntHighlightPlugin,
+    hud::HudPlugin,
+    hue_cycle::HueCyclePlugin,
+    inspector::InspectorPlugin,
+    keybindings::{Action, KeyBindings},
+    lattice::{HexLattice, Lattice, SquareLattice, TriangularLattice},
+    menu::{Menu, MenuPlugin},
+    minimap::MinimapPlugin,
+    palette::{check_cvd_safe, Palette},
+    pattern::{Pattern, PatternLimits, TurnSymbols, DEFAULT_MAX_STATES},
+    pattern_input::{pattern_input_closed, PatternInputPlugin},
+    presets::{preset_rule, PresetsPlugin, PRESETS},
+    progress::ProgressLogPlugin,
+    rle::{SavedAnt, Snapshot},
+    scheduler::{Scheduler, SchedulerPlugin},
+    share::ShareRunPlugin,
+    sim::{
+        simulate_all, simulate_all_counting, AntState, Border, Collision, EdgeMode, GridState,
+        VisitCounts,
+    },
+    sound::SoundPlugin,
+    summary::{RunSummary, RunSummaryPlugin},
+    symmetry::{Symmetry, SymmetryPlugin},
+    theme::Theme,
+    tile_texture::ChunkTexturePlugin,
+    title::TitlePlugin,
+    touch::TouchPlugin,
+    trail::TrailPlugin,
+    wheel_rate::WheelRatePlugin,
+    AppState, Shortcuts, SimRng, MAX_RATE,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use langtons_ant::{icon::WindowIconPlugin, view::RememberViewPlugin};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
+#[command(group = clap::ArgGroup::new("headless").args(["count_only", "print_hash", "print_symmetry", "export_rle"]).multiple(true))]
 struct AntApp {
-    /// Set custom render rate
-    #[arg(short, long, default_value_t = 60)]
-    rate: u8,
-    /// Pattern to use
-    #[arg(short, long, default_value = "RL")]
-    pattern: String,
+    /// Simulation ticks per second, each running `--steps-per-tick` steps. Anything above
+    /// 0 up to 100000, fractions such as 0.5 included
+    #[arg(short, long, default_value_t = 60., value_parser = parse_rate)]
+    rate: f64,
+    /// Pattern to use, a turn followed by `:color` pins that state's color, `-` reads it
+    /// from stdin. Without a pattern from here, `--preset`, `--stdin` or `--import-rle`
+    /// the window opens on a start menu with `RL` filled in
+    #[arg(short, long)]
+    pattern: Option<String>,
+    /// Read the pattern from stdin, like `--pattern -`
+    #[arg(long, conflicts_with_all = ["pattern", "preset", "import_rle"])]
+    stdin: bool,
+    /// Most states a pattern may have, typed at runtime too
+    #[arg(
+        long,
+        value_name = "STATES",
+        default_value_t = DEFAULT_MAX_STATES,
+        value_parser = parse_max_states
+    )]
+    max_states: usize,
+    /// Named pattern to use instead of `--pattern`, see `--list-presets`
+    #[arg(
+        long,
+        conflicts_with = "pattern",
+        value_parser = PossibleValuesParser::new(PRESETS.iter().map(|(name, _, _)| *name))
+    )]
+    preset: Option<String>,
+    /// Print the named patterns `--preset` accepts and exit
+    #[arg(long)]
+    list_presets: bool,
+    /// Check the pattern, `--pattern-b`, `--colors`, `--symbols` and `--import-rle`
+    /// without running anything, print each pattern's states with their turns and colors
+    /// and exit. Fails with the reason if any of them is invalid
+    #[arg(long, conflicts_with = "steps")]
+    validate: bool,
+    /// File defining extra turn symbols for patterns, such as `A=+90, B=-90, C=+45`
+    #[arg(long, value_name = "FILE")]
+    symbols: Option<PathBuf>,
+    /// Seed for everything random, such as the state colors, so a run can be reproduced.
+    /// Without one a random seed is drawn, logged at startup and printed in the summary
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Second pattern, run in lockstep and drawn in the right half of the window
+    #[arg(long, value_name = "PATTERN")]
+    pattern_b: Option<String>,
+    /// Where state colors come from when neither the pattern nor `--colors` sets them.
+    /// Defaults to the palette of `--theme`
+    #[arg(long, value_enum)]
+    palette: Option<PaletteArg>,
+    /// Use the color-blind-safe palette and check that every state color, pinned ones
+    /// included, stays apart with protanopia and deuteranopia
+    #[arg(long, conflicts_with = "palette")]
+    cvd_safe: bool,
+    /// Print the palettes `--palette` accepts and exit
+    #[arg(long)]
+    list_palettes: bool,
+    /// Colors of the states: names or #rrggbb separated by spaces or commas, or `rainbow`
+    #[arg(short, long)]
+    colors: Option<String>,
+    /// Steps to simulate on every tick
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    steps_per_tick: u32,
+    /// Steps to simulate before the window opens, or before a run started from the start
+    /// menu
+    #[arg(long, value_name = "STEPS", default_value_t = 0)]
+    warmup: u64,
+    /// Simulate `--steps` steps without a window, then print the number of painted cells
+    /// and the size of their bounding box as `CELLS WxH`. Progress goes to stderr unless
+    /// `--quiet` is given
+    #[arg(long, requires = "steps", conflicts_with = "pattern_b")]
+    count_only: bool,
+    /// Like `--count-only`, printing a hash of the painted cells and their states instead,
+    /// the same on every machine for the same arguments. Both lines are printed if both
+    /// flags are given
+    #[arg(long, requires = "steps", conflicts_with = "pattern_b")]
+    print_hash: bool,
+    /// Like `--count-only`, printing how symmetric the painted cells are as
+    /// `VERTICAL HORIZONTAL ROTATION`, the fraction of them that match their mirror image
+    /// across each axis through their centroid and their half turn about it
+    #[arg(long, requires = "steps", conflicts_with = "pattern_b")]
+    print_symmetry: bool,
+    /// Like `--count-only`, writing the painted cells, the pattern and the ants to FILE
+    /// as run-length encoded text that `--import-rle` reads back
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "steps",
+        conflicts_with = "pattern_b"
+    )]
+    export_rle: Option<PathBuf>,
+    /// Start from a snapshot written by `--export-rle` or another tool's `.rle` file, with
+    /// its pattern and ants. The usual ant starts if the file has none
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["pattern", "preset", "mirror", "anti_ant"]
+    )]
+    import_rle: Option<PathBuf>,
+    /// Steps `--count-only`, `--print-hash`, `--print-symmetry` and `--export-rle` simulate
+    #[arg(long, requires = "headless")]
+    steps: Option<u64>,
+    /// Forget the least recently visited cells once more than N are painted, so long runs
+    /// keep a bounded size. The ants then find background there, which changes the run
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..),
+        conflicts_with = "headless"
+    )]
+    max_tiles: Option<u64>,
+    /// Store the grid in 64x64 chunks and only draw tiles inside the camera view
+    #[arg(long)]
+    chunked: bool,
+    /// Confine the ant to a WxH world centered on the origin
+    #[arg(long, value_name = "WxH", value_parser = parse_size)]
+    border: Option<UVec2>,
+    /// What the ant does at the border
+    #[arg(long, value_enum, default_value_t = EdgeMode::Stop, requires = "border")]
+    edge: EdgeMode,
+    /// Lattice the ant walks on
+    #[arg(long, value_enum, default_value_t = Grid::Square)]
+    grid: Grid,
+    /// Add the ant's mirror images across both axes and the origin
+    #[arg(long)]
+    mirror: bool,
+    /// Add an anti-ant that walks the pattern backwards, eroding what the ant builds
+    #[arg(long)]
+    anti_ant: bool,
+    /// What ants do when they walk into each other or onto the same cell
+    #[arg(long, value_enum, default_value_t = Collision::Pass)]
+    collision: Collision,
+    /// Pause once an ant gets further than this many cells from the origin
+    /// (Chebyshev distance, hex distance on the hex grid)
+    #[arg(long, value_name = "CELLS")]
+    pause_radius: Option<u32>,
+    /// Quit once an ant gets further than this many cells from the origin
+    #[arg(long, value_name = "CELLS")]
+    exit_radius: Option<u32>,
+    /// Pause once this many steps were taken since the start or the last reset, resuming
+    /// carries on past it. Ctrl+C copies it along with the rest of the run
+    #[arg(long, value_name = "STEPS", conflicts_with = "steps")]
+    max_steps: Option<u64>,
+    /// Slowly cycle the hue of every state color
+    #[arg(long)]
+    rainbow: bool,
+    /// Fade newly painted tiles in from the background color
+    #[arg(long)]
+    animate: bool,
+    /// How long the fade-in of `--animate` takes
+    #[arg(long, value_name = "MS", default_value_t = 250, requires = "animate")]
+    fade_ms: u64,
+    /// Outline every cell, `G` toggles it
+    #[arg(long)]
+    grid_lines: bool,
+    /// Don't outline the cell under each ant, `O` toggles it
+    #[arg(long)]
+    no_ant_highlight: bool,
+    /// Write each ant's number above it, `L` toggles them
+    #[arg(long)]
+    ant_labels: bool,
+    /// Show the whole painted area at reduced scale in the top left corner, `N` toggles it
+    #[arg(long)]
+    minimap: bool,
+    /// Draw fading dots along the ant's recent path
+    #[arg(long)]
+    trail: bool,
+    /// Plot the painted cells against the step count in the corner, `V` toggles it
+    #[arg(long)]
+    growth_graph: bool,
+    /// Count how often each cell is visited and tint the cells by it instead of their
+    /// state, `E` toggles back to the state colors. `--export-rle` saves the counts
+    #[arg(
+        long,
+        value_enum,
+        value_name = "GRADIENT",
+        num_args = 0..=1,
+        default_missing_value = "magma"
+    )]
+    heatmap: Option<HeatGradient>,
+    /// Score how symmetric the board is every this many steps, shown in the control panel
+    /// and the `--log-every` lines
+    #[arg(long, value_name = "STEPS", value_parser = clap::value_parser!(u64).range(1..))]
+    symmetry_every: Option<u64>,
+    /// Pause whenever an ant steps back onto the origin
+    #[arg(long)]
+    stop_on_origin: bool,
+    /// Click while the ants step and chime when a stop condition pauses the run
+    #[arg(long)]
+    sound: bool,
+    /// Loudness of `--sound`, from 0 to 1
+    #[arg(long, default_value_t = 0.5, value_parser = parse_volume, requires = "sound")]
+    volume: f32,
+    /// Start in borderless fullscreen
+    #[arg(long, conflicts_with = "resolution")]
+    fullscreen: bool,
+    /// Window size in logical pixels
+    #[arg(long, value_name = "WxH", value_parser = parse_resolution)]
+    resolution: Option<UVec2>,
+    /// Pause while the window is unfocused
+    #[arg(long)]
+    pause_on_blur: bool,
+    /// Keep the window title to the rule instead of also showing the step count and state
+    #[arg(long)]
+    static_title: bool,
+    /// Closest zoom in screen pixels per world unit, the default keeps a few cells on screen
+    #[arg(long, default_value_t = 16., value_parser = parse_zoom)]
+    max_zoom: f32,
+    /// Furthest zoom in screen pixels per world unit, unlimited by default
+    #[arg(long, value_parser = parse_zoom)]
+    min_zoom: Option<f32>,
+    /// Cells that fit across the window at startup, within the zoom limits
+    #[arg(long, value_name = "CELLS", default_value_t = 60., value_parser = parse_zoom)]
+    initial_zoom: f32,
+    /// Mouse buttons that drag the view
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = [PanButton::Left, PanButton::Right, PanButton::Middle]
+    )]
+    pan_buttons: Vec<PanButton>,
+    /// Restore the camera position and zoom from the last run, saved to `view.toml` on exit
+    #[arg(long)]
+    remember_view: bool,
+    /// Keymap binding actions to keys, `keybindings.toml` is used if present
+    #[arg(long, value_name = "FILE")]
+    keybindings: Option<PathBuf>,
+    /// Log the step count, painted cells and steps per second every this many steps,
+    /// implies `--verbose` unless `--quiet` is given
+    #[arg(long, value_name = "STEPS", value_parser = clap::value_parser!(u64).range(1..))]
+    log_every: Option<u64>,
+    /// Only log errors
+    #[arg(short, long, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Also log informational messages
+    #[arg(short, long)]
+    verbose: bool,
+    /// How painted cells are drawn, `texture` scales to millions of cells but needs the square grid
+    #[arg(long, value_enum, default_value_t = Render::Mesh)]
+    render: Render,
+    /// What runs the simulation ticks, `accumulator` steps inside the frame update for
+    /// platforms where Bevy's fixed loop stutters
+    #[arg(long, value_enum, default_value_t = Scheduler::Fixed)]
+    scheduler: Scheduler,
+    /// Background, palette and tile filtering that go together. `--palette` overrides
+    /// the theme's palette
+    #[arg(long, value_enum, default_value_t = Theme::Classic)]
+    theme: Theme,
 }
 
-fn main() -> Result<()> {
-    let ant_app = AntApp::parse();
-    let pattern = Pattern::parse(ant_app.pattern)?;
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Grid {
+    Square,
+    Hex,
+    Triangular,
+}
 
-    App::new()
-        .add_plugins((
-            DefaultPlugins
-                .set(LogPlugin {
-                    level: bevy::log::Level::WARN,
-                    ..Default::default()
-                })
-                .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        title: "Langton's ant".to_owned(),
-                        ..Default::default()
-                    }),
-                    ..Default::default()
-                }),
-            PanCamPlugin,
-            EmbeddedAssetPlugin::default(),
-        ))
-        .init_state::<AppState>()
-        .insert_resource(Time::<Fixed>::from_hz(ant_app.rate.into()))
-        .insert_resource(pattern)
-        .insert_resource(ClearColor(Color::WHITE))
-        .add_systems(Startup, (set_window_icon, setup))
-        .add_systems(Update, pause)
-        .add_systems(
-            FixedUpdate,
-            run_rotation.run_if(in_state(AppState::Running)),
-        )
-        .run();
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum PaletteArg {
+    /// Random reds, greens and yellows, the only palette `--seed` changes
+    Random,
+    /// Okabe-Ito colors, distinguishable with common color vision deficiencies
+    #[value(alias = "cb-safe")]
+    Colorblind,
+    /// Evenly spread hues, used for patterns of more than 8 states by default
+    Distinct,
+    /// Dark blue through green to yellow
+    Viridis,
+    /// Black through purple and red to pale yellow
+    Magma,
+    /// Light gray down to black
+    Grayscale,
+}
 
-    Ok(())
+impl From<PaletteArg> for Palette {
+    fn from(palette: PaletteArg) -> Self {
+        match palette {
+            PaletteArg::Random => Palette::Random,
+            PaletteArg::Colorblind => Palette::ColorBlindSafe,
+            PaletteArg::Distinct => Palette::Distinct,
+            PaletteArg::Viridis => Palette::Viridis,
+            PaletteArg::Magma => Palette::Magma,
+            PaletteArg::Grayscale => Palette::Grayscale,
+        }
+    }
 }
 
-#[derive(Clone, Copy)]
-enum Turn {
-    Right,
-    Left,
+/// Prints `pattern` and its state count for `--validate`, then a line per state with
+/// its turn and color
+fn print_states(pattern: &Pattern) {
+    println!("{pattern}: {} states", pattern.turns.len());
+    let states = pattern
+        .turns
+        .iter()
+        .zip(&pattern.distances)
+        .zip(&pattern.colors);
+    for (state, ((turn, distance), color)) in states.enumerate() {
+        let turn = if *distance > 1 {
+            format!("{turn}*{distance}")
+        } else {
+            turn.to_string()
+        };
+        println!("{state:>4}  {turn:<8}{}", color.to_srgba().to_hex());
+    }
 }
 
-#[derive(Resource)]
-struct Pattern {
-    colors: Vec<Color>,
-    turns: Vec<Turn>,
+/// Prints every `--palette` name with its description
+fn list_palettes() {
+    use clap::ValueEnum;
+
+    for palette in PaletteArg::value_variants() {
+        let Some(value) = palette.to_possible_value() else {
+            continue;
+        };
+        let help = value
+            .get_help()
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        println!("{:<12}{help}", value.get_name());
+    }
 }
 
-impl Pattern {
-    fn parse_pattern(&mut self, pattern: String) {
-        let mut rng = rand::thread_rng();
-
-        for p in pattern.to_lowercase().chars() {
-            let color = Color::srgb(rng.gen_range(0.1..0.8), rng.gen_range(0.1..0.8), 0.);
-            match p {
-                'r' => {
-                    self.colors.push(color);
-                    self.turns.push(Turn::Right);
-                }
-                'l' => {
-                    self.colors.push(color);
-                    self.turns.push(Turn::Left);
-                }
-                _ => (),
-            }
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum PanButton {
+    Left,
+    Right,
+    Middle,
+}
+
+impl From<PanButton> for MouseButton {
+    fn from(button: PanButton) -> Self {
+        match button {
+            PanButton::Left => MouseButton::Left,
+            PanButton::Right => MouseButton::Right,
+            PanButton::Middle => MouseButton::Middle,
         }
     }
+}
 
-    fn parse(pattern: String) -> Result<Self> {
-        let mut s = Pattern {
-            colors: Vec::new(),
-            turns: Vec::new(),
-        };
+/// Limits and buttons for the camera's `PanCam`
+#[derive(Resource)]
+struct CameraControls {
+    min_scale: f32,
+    max_scale: Option<f32>,
+    grab_buttons: Vec<MouseButton>,
+}
 
-        s.parse_pattern(pattern);
-        if s.colors.len() < 2 {
-            bail!("incorrect pattern: should be at least 2 correct values (L, R)");
-        }
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Render {
+    /// One mesh entity per painted cell
+    Mesh,
+    /// One texture per 64x64 chunk, one texel per cell
+    Texture,
+}
+
+/// Pattern run without `--pattern`, and filled in on the start menu
+const DEFAULT_PATTERN: &str = "RL";
+
+/// Keymap read when `--keybindings` isn't given
+#[cfg(not(target_arch = "wasm32"))]
+const DEFAULT_KEYBINDINGS: &str = "keybindings.toml";
+
+/// Where `--remember-view` keeps the camera framing
+#[cfg(not(target_arch = "wasm32"))]
+const DEFAULT_VIEW_FILE: &str = "view.toml";
 
-        Ok(s)
+/// Progress lines `--count-only` prints over a run
+const PROGRESS_LINES: u64 = 20;
+
+/// Largest window side, the common texture size limit of GPUs
+const MAX_RESOLUTION: u32 = 8192;
+
+fn parse_size(value: &str) -> Result<UVec2, String> {
+    let (width, height) = value
+        .split_once(['x', 'X'])
+        .ok_or_else(|| format!("expected WxH, got `{value}`"))?;
+    let size = UVec2::new(
+        width
+            .parse()
+            .map_err(|err| format!("invalid width: {err}"))?,
+        height
+            .parse()
+            .map_err(|err| format!("invalid height: {err}"))?,
+    );
+    if size.min_element() == 0 {
+        return Err("size must be at least 1x1".to_owned());
     }
+    Ok(size)
+}
 
-    fn first(&self) -> (Color, Turn) {
-        return (*self.colors.get(1).unwrap(), *self.turns.first().unwrap());
+fn parse_zoom(value: &str) -> Result<f32, String> {
+    let zoom: f32 = value
+        .parse()
+        .map_err(|err| format!("invalid zoom: {err}"))?;
+    if !zoom.is_finite() || zoom <= 0. {
+        return Err("zoom must be a positive number".to_owned());
     }
+    Ok(zoom)
+}
 
-    fn next(&self, current: Color) -> (Color, Turn) {
-        for ((color, next_color), turn) in self
-            .colors
-            .iter()
-            .circular_tuple_windows::<(&Color, &Color)>()
-            .zip(self.turns.iter())
-        {
-            if *color == current {
-                return (*next_color, *turn);
-            }
-        }
-        panic!("can't find macthing color")
+/// A zero rate would give the fixed timestep an infinite period
+fn parse_rate(value: &str) -> Result<f64, String> {
+    let rate: f64 = value
+        .parse()
+        .map_err(|err| format!("invalid rate: {err}"))?;
+    if !(rate > 0. && rate <= MAX_RATE) {
+        return Err(format!("rate must be above 0 and at most {MAX_RATE}"));
+    }
+    Ok(rate)
+}
+
+/// A single state could only ever turn one way
+fn parse_max_states(value: &str) -> Result<usize, String> {
+    let states: usize = value
+        .parse()
+        .map_err(|err| format!("invalid state count: {err}"))?;
+    if states < 2 {
+        return Err("at least 2 states are needed".to_owned());
     }
+    Ok(states)
 }
 
-enum Direction {
-    North,
-    South,
-    West,
-    East,
+fn parse_volume(value: &str) -> Result<f32, String> {
+    let volume: f32 = value
+        .parse()
+        .map_err(|err| format!("invalid volume: {err}"))?;
+    if !(0. ..=1.).contains(&volume) {
+        return Err("volume must be between 0 and 1".to_owned());
+    }
+    Ok(volume)
+}
+
+fn parse_resolution(value: &str) -> Result<UVec2, String> {
+    let size = parse_size(value)?;
+    if size.max_element() > MAX_RESOLUTION {
+        return Err(format!(
+            "resolution must be at most {MAX_RESOLUTION}x{MAX_RESOLUTION}"
+        ));
+    }
+    Ok(size)
 }
 
-#[derive(Component)]
-struct Ant(Direction);
+/// Command line equivalent of the page's query string: `?pattern=LLRR&chunked` becomes
+/// `--pattern LLRR --chunked`
+#[cfg(target_arch = "wasm32")]
+fn query_args() -> Vec<String> {
+    let decode = |text: &str| {
+        js_sys::decode_uri_component(&text.replace('+', " "))
+            .map(String::from)
+            .unwrap_or_else(|_| text.to_owned())
+    };
+    let search = web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .unwrap_or_default();
 
-#[derive(Component)]
-struct Tile;
+    let mut args = vec!["langtons-ant".to_owned()];
+    for pair in search.trim_start_matches('?').split('&') {
+        if pair.is_empty() {
+            continue;
+        }
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        args.push(format!("--{}", decode(key).replace('_', "-")));
+        if !value.is_empty() && value != "true" {
+            args.push(decode(value));
+        }
+    }
+    args
+}
 
-#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
-enum AppState {
-    Paused,
-    #[default]
-    Running,
+/// Fails on flags that read or write files, the web build has no file system to use
+#[cfg(target_arch = "wasm32")]
+fn reject_file_flags(ant_app: &AntApp) -> Result<()> {
+    let flags = [
+        ("--symbols", ant_app.symbols.is_some()),
+        ("--import-rle", ant_app.import_rle.is_some()),
+        ("--export-rle", ant_app.export_rle.is_some()),
+        ("--keybindings", ant_app.keybindings.is_some()),
+        ("--remember-view", ant_app.remember_view),
+    ];
+    if let Some((flag, _)) = flags.into_iter().find(|&(_, given)| given) {
+        bail!("{flag} is not supported on the web, which has no file system");
+    }
+    Ok(())
 }
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands
-        .spawn(Camera2dBundle::default())
-        .insert(PanCam::default());
+/// Arguments the app was started with, without the program name, for sharing the run
+fn launch_args() -> Vec<String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let args = std::env::args_os().map(|arg| arg.to_string_lossy().into_owned());
+    #[cfg(target_arch = "wasm32")]
+    let args = query_args().into_iter();
+    args.skip(1).collect()
+}
 
-    commands.spawn((
-        SpriteBundle {
-            texture: asset_server.load("ant.png"),
-            ..default()
+/// Keymap from `path`, or from `DEFAULT_KEYBINDINGS` if that exists
+#[cfg(not(target_arch = "wasm32"))]
+fn load_keybindings(path: Option<&std::path::Path>) -> Result<Option<KeyBindings>> {
+    let text = match path {
+        Some(path) => std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?,
+        None => match std::fs::read_to_string(DEFAULT_KEYBINDINGS) {
+            Ok(text) => text,
+            Err(_) => return Ok(None),
         },
-        Ant(Direction::North),
+    };
+    Ok(Some(KeyBindings::parse(&text)))
+}
+
+/// Ants of an imported snapshot on lattice `L`
+fn snapshot_ants<L: Lattice>(snapshot: &Snapshot) -> Result<Vec<AntState<L>>> {
+    snapshot
+        .ants
+        .iter()
+        .map(SavedAnt::to_state)
+        .collect::<Result<_>>()
+        .context("invalid ant in the snapshot")
+}
+
+/// Plugins that depend on the lattice the ant walks on, starting from the ants of
+/// `snapshot` if it has any
+fn add_lattice_plugins<L: Lattice>(
+    app: &mut App,
+    grid_lines: bool,
+    ant_highlight: bool,
+    minimap: bool,
+    snapshot: Option<&Snapshot>,
+    seed: u64,
+) -> Result<()> {
+    if let Some(snapshot) = snapshot.filter(|snapshot| !snapshot.ants.is_empty()) {
+        app.insert_resource(StartingAnts(snapshot_ants::<L>(snapshot)?));
+    }
+    app.add_plugins((
+        BoardPlugin::<L>::default(),
+        GridLinesPlugin::<L>::new(grid_lines),
+        AntHighlightPlugin::<L>::new(ant_highlight),
+        MinimapPlugin::<L>::new(minimap),
+        InspectorPlugin::<L>::default(),
+        AntKeysPlugin::<L>::default(),
+        RunSummaryPlugin::<L>::new(seed),
     ));
+    #[cfg(feature = "ui")]
+    app.add_plugins(langtons_ant::ui::UiPlugin::<L>::default());
+    Ok(())
 }
 
-fn run_rotation(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    pattern: Res<Pattern>,
-    mut ant_query: Query<(&mut Ant, &mut Transform)>,
-    mut tile_query: Query<(&Transform, &mut Handle<ColorMaterial>), Without<Ant>>,
-) {
-    let (mut ant, mut ant_transform) = ant_query.single_mut();
-
-    let mut flip_color = false;
-    let mut next_turn = Turn::Left;
-
-    for (tile_transform, tile_color) in tile_query.iter_mut() {
-        if (tile_transform.translation.x, tile_transform.translation.y)
-            == (ant_transform.translation.x, ant_transform.translation.y)
-        {
-            flip_color = true;
-            let current_color = materials.get_mut(tile_color.id()).unwrap();
-            (current_color.color, next_turn) = pattern.next(current_color.color);
+/// Runs `steps` steps on `grid` for `--count-only`, `--print-hash`, `--print-symmetry` and
+/// `--export-rle`, starting from the ants of `snapshot` if it has any and counting the
+/// visits into `visits` if given. Unless `quiet`, a
+/// progress line with the time left goes to stderr after every `PROGRESS_LINES`th of the
+/// run, and the run's summary once it is done.
+#[allow(clippy::too_many_arguments)]
+fn run_headless<L: Lattice>(
+    mut grid: GridState,
+    snapshot: Option<&Snapshot>,
+    mirror: bool,
+    anti_ant: bool,
+    pattern: &Pattern,
+    collision: Collision,
+    steps: u64,
+    seed: u64,
+    quiet: bool,
+    mut visits: Option<&mut VisitCounts>,
+) -> Result<(GridState, Vec<SavedAnt>)> {
+    let mut ants = match snapshot.filter(|snapshot| !snapshot.ants.is_empty()) {
+        Some(snapshot) => snapshot_ants::<L>(snapshot)?,
+        None => initial_ants::<L>(mirror, anti_ant),
+    };
+    let start = Instant::now();
+    let batch = steps.div_ceil(PROGRESS_LINES).max(1);
+    let mut done = 0;
+    while done < steps {
+        let batch = batch.min(steps - done);
+        match &mut visits {
+            Some(visits) => {
+                simulate_all_counting(&mut grid, &mut ants, pattern, collision, batch, visits)
+            }
+            None => simulate_all(&mut grid, &mut ants, pattern, collision, batch),
+        }
+        done += batch;
+        if !quiet {
+            let elapsed = start.elapsed().as_secs_f64();
+            let left = elapsed / done as f64 * (steps - done) as f64;
+            eprintln!(
+                "step {done}/{steps} ({}%), {:.0} steps/s, {left:.1}s left",
+                done * 100 / steps,
+                done as f64 / elapsed.max(f64::EPSILON),
+            );
         }
     }
-
-    if !flip_color {
-        let (color, turn) = pattern.first();
-        next_turn = turn;
-
-        commands.spawn((
-            MaterialMesh2dBundle {
-                mesh: Mesh2dHandle(meshes.add(Rectangle::new(TILE_SIZE, TILE_SIZE))),
-                material: materials.add(color),
-                transform: Transform::from_xyz(
-                    ant_transform.translation.x,
-                    ant_transform.translation.y,
-                    -1.,
-                ),
-                ..default()
-            },
-            Tile,
-        ));
+    if !quiet {
+        let summary = RunSummary::new(pattern, seed, steps, &grid, &ants, collision);
+        eprintln!("{summary}");
     }
+    Ok((grid, ants.iter().map(SavedAnt::from_state).collect()))
+}
 
-    match next_turn {
-        Turn::Left => {
-            ant_transform.rotate_z(f32::to_radians(90.));
-            match ant.0 {
-                Direction::North => ant.0 = Direction::West,
-                Direction::South => ant.0 = Direction::East,
-                Direction::West => ant.0 = Direction::South,
-                Direction::East => ant.0 = Direction::North,
-            }
+fn main() -> Result<()> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let ant_app = AntApp::parse();
+    #[cfg(target_arch = "wasm32")]
+    let ant_app = AntApp::try_parse_from(query_args())?;
+    #[cfg(target_arch = "wasm32")]
+    reject_file_flags(&ant_app)?;
+    if ant_app.list_palettes {
+        list_palettes();
+        return Ok(());
+    }
+    if ant_app.list_presets {
+        for (name, rule, description) in PRESETS {
+            println!("{name:<12}{rule:<14}{description}");
+        }
+        return Ok(());
+    }
+    // Drawn rather than left to the entropy source, so the run can be repeated
+    let seed = ant_app.seed.unwrap_or_else(rand::random);
+    let mut rng = SimRng::seeded(seed);
+    let palette = if ant_app.cvd_safe {
+        Palette::ColorBlindSafe
+    } else {
+        ant_app
+            .palette
+            .map_or_else(|| ant_app.theme.palette(), Palette::from)
+    };
+    let symbols = match &ant_app.symbols {
+        Some(path) => TurnSymbols::parse(
+            &std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?,
+        )
+        .with_context(|| format!("invalid turn symbols in {}", path.display()))?,
+        None => TurnSymbols::default(),
+    };
+    let snapshot = ant_app
+        .import_rle
+        .as_ref()
+        .map(|path| {
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            Snapshot::parse(&text).with_context(|| format!("invalid snapshot {}", path.display()))
+        })
+        .transpose()?;
+    let start_menu = snapshot.is_none()
+        && ant_app.preset.is_none()
+        && !ant_app.stdin
+        && ant_app.pattern.is_none();
+    let pattern_text = match (&snapshot, &ant_app.preset) {
+        (Some(snapshot), _) => snapshot.rule.clone(),
+        // The parser only lets names from the table through
+        (None, Some(name)) => preset_rule(name).unwrap().to_owned(),
+        (None, None) if ant_app.stdin || ant_app.pattern.as_deref() == Some("-") => {
+            let mut text = String::new();
+            std::io::stdin()
+                .read_to_string(&mut text)
+                .context("failed to read the pattern from stdin")?;
+            text.trim().to_owned()
         }
-        Turn::Right => {
-            ant_transform.rotate_z(f32::to_radians(-90.));
-            match ant.0 {
-                Direction::North => ant.0 = Direction::East,
-                Direction::South => ant.0 = Direction::West,
-                Direction::West => ant.0 = Direction::North,
-                Direction::East => ant.0 = Direction::South,
+        (None, None) => ant_app
+            .pattern
+            .unwrap_or_else(|| DEFAULT_PATTERN.to_owned()),
+    };
+    let mut pattern = Pattern::parse_with_symbols(pattern_text, palette, &symbols, &mut rng.0)?;
+    if let Some(colors) = &ant_app.colors {
+        pattern.set_colors(colors)?;
+    }
+    let compare_pattern = ant_app
+        .pattern_b
+        .map(|pattern_b| Pattern::parse_with_symbols(pattern_b, palette, &symbols, &mut rng.0))
+        .transpose()?;
+    let mut board = if ant_app.chunked {
+        Board::chunked()
+    } else {
+        Board::default()
+    };
+    let pattern_limits = match ant_app.grid {
+        Grid::Square => PatternLimits::new::<SquareLattice>,
+        Grid::Hex => PatternLimits::new::<HexLattice>,
+        Grid::Triangular => PatternLimits::new::<TriangularLattice>,
+    };
+    let limits = pattern_limits(ant_app.max_states, &board.grid);
+    for pattern in std::iter::once(&pattern).chain(&compare_pattern) {
+        // Logging is only set up with the window, headless runs and `--validate` never
+        // get it
+        if let Some(warning) = pattern.validate(&limits)? {
+            if !ant_app.quiet {
+                eprintln!("warning: {warning}");
             }
         }
     }
+    if ant_app.cvd_safe {
+        for pattern in std::iter::once(&pattern).chain(&compare_pattern) {
+            check_cvd_safe(&pattern.colors)
+                .with_context(|| format!("pattern {pattern} is not color-blind safe"))?;
+        }
+    }
+    if let Some(size) = ant_app.border {
+        board.grid = board.grid.with_border(Border::new(size, ant_app.edge));
+    }
+    for &(cell, state) in snapshot.iter().flat_map(|snapshot| &snapshot.cells) {
+        if state >= pattern.turns.len() {
+            bail!("the snapshot paints state {state}, its pattern {pattern} has fewer states");
+        }
+        board.grid.set(cell, state);
+    }
+    if ant_app.heatmap.is_some() {
+        board
+            .visits
+            .extend(snapshot.iter().flat_map(|snapshot| &snapshot.visits));
+    }
+    if ant_app.validate {
+        for pattern in std::iter::once(&pattern).chain(&compare_pattern) {
+            print_states(pattern);
+        }
+        return Ok(());
+    }
+    if let Some(steps) = ant_app.steps {
+        let run_headless = match ant_app.grid {
+            Grid::Square => run_headless::<SquareLattice>,
+            Grid::Hex => run_headless::<HexLattice>,
+            Grid::Triangular => run_headless::<TriangularLattice>,
+        };
+        let (grid, ants) = run_headless(
+            board.grid,
+            snapshot.as_ref(),
+            ant_app.mirror,
+            ant_app.anti_ant,
+            &pattern,
+            ant_app.collision,
+            steps,
+            seed,
+            ant_app.quiet,
+            ant_app.heatmap.is_some().then_some(&mut board.visits),
+        )?;
+        if ant_app.count_only {
+            let size = grid
+                .bounds()
+                .map_or(IVec2::ZERO, |bounds| bounds.size() + 1);
+            println!("{} {}x{}", grid.len(), size.x, size.y);
+        }
+        if ant_app.print_hash {
+            println!("{:016x}", grid.content_hash());
+        }
+        if ant_app.print_symmetry {
+            let symmetry = Symmetry::of(&grid);
+            println!(
+                "{:.3} {:.3} {:.3}",
+                symmetry.vertical, symmetry.horizontal, symmetry.rotation
+            );
+        }
+        if let Some(path) = &ant_app.export_rle {
+            let snapshot = Snapshot {
+                rule: pattern.to_string(),
+                cells: grid.cells().collect(),
+                ants,
+                visits: board.visits.into_iter().collect(),
+            };
+            std::fs::write(path, snapshot.to_rle()?)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+        return Ok(());
+    }
+    if ant_app
+        .min_zoom
+        .is_some_and(|min_zoom| min_zoom > ant_app.max_zoom)
+    {
+        bail!("--min-zoom must not be larger than --max-zoom");
+    }
+    if ant_app.render == Render::Texture {
+        if !matches!(ant_app.grid, Grid::Square) {
+            bail!("texture rendering only supports the square grid");
+        }
+        // Chunk sprites are culled by the renderer itself
+        board.visible_chunks = None;
+    }
+
+    let log_level = if ant_app.quiet {
+        Level::ERROR
+    } else if ant_app.verbose || ant_app.log_every.is_some() {
+        Level::INFO
+    } else {
+        Level::WARN
+    };
+
+    let mut app = App::new();
+    app.add_plugins((
+        // Replaces the default asset source, so it has to come before `AssetPlugin`
+        EmbeddedAssetPlugin {
+            mode: PluginMode::ReplaceDefault,
+        },
+        DefaultPlugins
+            .set(LogPlugin {
+                level: log_level,
+                ..Default::default()
+            })
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: "Langton's ant".to_owned(),
+                    fit_canvas_to_parent: true,
+                    mode: if ant_app.fullscreen {
+                        WindowMode::BorderlessFullscreen
+                    } else {
+                        WindowMode::Windowed
+                    },
+                    resolution: ant_app
+                        .resolution
+                        .map(|size| WindowResolution::new(size.x as f32, size.y as f32))
+                        .unwrap_or_default(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+        PanCamPlugin,
+        PatternInputPlugin,
+        PresetsPlugin,
+        HudPlugin,
+        CameraKeysPlugin,
+        InitialZoomPlugin {
+            cells_across: ant_app.initial_zoom,
+        },
+        TitlePlugin {
+            live: !ant_app.static_title,
+        },
+        TrailPlugin {
+            enabled: ant_app.trail,
+        },
+        AntLabelsPlugin {
+            shown: ant_app.ant_labels,
+        },
+        GrowthGraphPlugin {
+            enabled: ant_app.growth_graph,
+        },
+        HueCyclePlugin {
+            enabled: ant_app.rainbow,
+        },
+        TouchPlugin,
+        GamepadPlugin,
+    ))
+    .add_plugins(WheelRatePlugin);
+    info!("pattern parsed: {pattern} ({} states)", pattern.turns.len());
+    info!("seed {seed}, `--seed {seed}` repeats this run");
 
-    match ant.0 {
-        Direction::North => ant_transform.translation.y += ANT_SPEED,
-        Direction::South => ant_transform.translation.y -= ANT_SPEED,
-        Direction::West => ant_transform.translation.x -= ANT_SPEED,
-        Direction::East => ant_transform.translation.x += ANT_SPEED,
+    // Parsed once logging is up, so problems in the file are reported
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(bindings) = load_keybindings(ant_app.keybindings.as_deref())? {
+        app.insert_resource(bindings);
     }
+
+    app.insert_state(if start_menu {
+        AppState::Menu
+    } else {
+        AppState::Running
+    })
+    .insert_resource(board)
+    .insert_resource(RadiusLimits {
+        pause: ant_app.pause_radius,
+        exit: ant_app.exit_radius,
+    })
+    .insert_resource(CameraControls {
+        // The projection scale is world units per pixel, the inverse of the zoom
+        min_scale: 1. / ant_app.max_zoom,
+        max_scale: ant_app.min_zoom.map(|min_zoom| 1. / min_zoom),
+        grab_buttons: ant_app
+            .pan_buttons
+            .iter()
+            .map(|&button| button.into())
+            .collect(),
+    })
+    .insert_resource(StopOnOrigin(ant_app.stop_on_origin))
+    .insert_resource(MaxSteps(ant_app.max_steps))
+    .insert_resource(Warmup(ant_app.warmup))
+    .insert_resource(Menu {
+        colors: ant_app.colors,
+        ..default()
+    })
+    .insert_resource(MirrorAnts(ant_app.mirror))
+    .insert_resource(AntiAnt(ant_app.anti_ant))
+    .insert_resource(CollisionRule(ant_app.collision))
+    .insert_resource(TileCap {
+        max: ant_app.max_tiles.map(|max| max as usize),
+        evicted: 0,
+    })
+    .insert_resource(StepsPerTick(ant_app.steps_per_tick))
+    .insert_resource(Time::<Fixed>::from_hz(ant_app.rate))
+    .insert_resource(pattern)
+    .insert_resource(rng)
+    .insert_resource(palette)
+    .insert_resource(symbols)
+    .insert_resource(limits)
+    .insert_resource(ClearColor(ant_app.theme.background()))
+    .insert_resource(ant_app.theme.filter())
+    .add_systems(Startup, setup)
+    .add_systems(
+        Update,
+        (pause.run_if(pattern_input_closed).in_set(Shortcuts),),
+    );
+
+    let (grid_lines, ant_highlight) = (ant_app.grid_lines, !ant_app.no_ant_highlight);
+    let add_lattice_plugins = match ant_app.grid {
+        Grid::Square => add_lattice_plugins::<SquareLattice>,
+        Grid::Hex => add_lattice_plugins::<HexLattice>,
+        Grid::Triangular => add_lattice_plugins::<TriangularLattice>,
+    };
+    add_lattice_plugins(
+        &mut app,
+        grid_lines,
+        ant_highlight,
+        ant_app.minimap,
+        snapshot.as_ref(),
+        seed,
+    )?;
+
+    if let Some(pattern_b) = compare_pattern {
+        app.insert_resource(ComparePattern(pattern_b));
+        match ant_app.grid {
+            Grid::Square => app.add_plugins(ComparePlugin::<SquareLattice>::default()),
+            Grid::Hex => app.add_plugins(ComparePlugin::<HexLattice>::default()),
+            Grid::Triangular => app.add_plugins(ComparePlugin::<TriangularLattice>::default()),
+        };
+    }
+
+    app.add_plugins((
+        SchedulerPlugin {
+            scheduler: ant_app.scheduler,
+        },
+        ShareRunPlugin {
+            args: launch_args(),
+            seed,
+        },
+        MenuPlugin,
+    ));
+
+    if ant_app.render == Render::Texture {
+        app.add_plugins(ChunkTexturePlugin);
+    }
+
+    if ant_app.pause_on_blur {
+        app.add_systems(Update, pause_on_blur);
+    }
+
+    if ant_app.animate {
+        app.add_plugins(FadeInPlugin {
+            duration: Duration::from_millis(ant_app.fade_ms),
+        });
+    }
+
+    if ant_app.sound {
+        app.add_plugins(SoundPlugin {
+            volume: ant_app.volume,
+        });
+    }
+
+    if let Some(every) = ant_app.log_every {
+        app.add_plugins(ProgressLogPlugin { every });
+    }
+
+    if let Some(every) = ant_app.symmetry_every {
+        app.add_plugins(SymmetryPlugin { every });
+    }
+
+    if let Some(gradient) = ant_app.heatmap {
+        app.add_plugins(HeatmapPlugin {
+            gradient,
+            shown: true,
+        });
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if ant_app.remember_view {
+        app.add_plugins(RememberViewPlugin {
+            path: DEFAULT_VIEW_FILE.into(),
+        });
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    app.add_plugins(WindowIconPlugin);
+
+    app.run();
+
+    Ok(())
+}
+
+fn setup(mut commands: Commands, controls: Res<CameraControls>) {
+    commands.spawn(Camera2dBundle::default()).insert(PanCam {
+        grab_buttons: controls.grab_buttons.clone(),
+        min_scale: controls.min_scale,
+        max_scale: controls.max_scale,
+        ..default()
+    });
 }
 
 fn pause(
-    game_state: Res<State<AppState>>,
-    mut next_game_state: ResMut<NextState<AppState>>,
+    mut toggle_events: EventWriter<TogglePause>,
     keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
 ) {
-    if keys.just_pressed(KeyCode::Space) {
-        match *game_state.get() {
-            AppState::Paused => next_game_state.set(AppState::Running),
-            AppState::Running => next_game_state.set(AppState::Paused),
-        }
+    if bindings.just_pressed(Action::Pause, &keys) {
+        toggle_events.send(TogglePause);
     }
 }
 
-fn set_window_icon(windows: NonSend<WinitWindows>) {
-    let (icon_rgba, icon_width, icon_height) = {
-        let image = image::open("assets/ant.png")
-            .expect("Failed to open icon path")
-            .into_rgba8();
-        let (width, height) = image.dimensions();
-        let rgba = image.into_raw();
-        (rgba, width, height)
-    };
-    let icon = Icon::from_rgba(icon_rgba, icon_width, icon_height).unwrap();
+/// Pauses when the window loses focus and resumes on regain, unless the run was already
+/// paused by hand
+fn pause_on_blur(
+    mut focus_events: EventReader<WindowFocused>,
+    mut auto_paused: Local<bool>,
+    game_state: Res<State<AppState>>,
+    mut next_game_state: ResMut<NextState<AppState>>,
+    window_query: Query<(), With<PrimaryWindow>>,
+) {
+    for event in focus_events.read() {
+        if window_query.get(event.window).is_err() {
+            continue;
+        }
 
-    for window in windows.windows.values() {
-        window.set_window_icon(Some(icon.clone()));
+        match (event.focused, game_state.get()) {
+            (false, AppState::Running) => {
+                next_game_state.set(AppState::Paused);
+                *auto_paused = true;
+            }
+            (true, AppState::Paused) if *auto_paused => {
+                next_game_state.set(AppState::Running);
+                *auto_paused = false;
+            }
+            (true, _) => *auto_paused = false,
+            (false, AppState::Paused | AppState::Menu) => (),
+        }
     }
 }
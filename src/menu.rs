@@ -0,0 +1,301 @@
+use bevy::{
+    ecs::system::SystemParam,
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+};
+
+use crate::{
+    board::{PendingWarmup, ResetBoard},
+    keybindings::{Action, KeyBindings},
+    palette::Palette,
+    pattern::{Pattern, PatternLimits, TurnSymbols},
+    pattern_input::{pattern_input, pattern_input_closed},
+    presets::PRESETS,
+    theme::foreground,
+    AppState, Shortcuts, SimRng,
+};
+
+const BUTTON_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+const HOVERED_COLOR: Color = Color::srgb(0.8, 0.8, 0.8);
+const PRESSED_COLOR: Color = Color::srgb(0.65, 0.65, 0.65);
+
+/// Start screen of `AppState::Menu`: a button per preset, a field for typing a pattern
+/// and a start button. Starting parses the pattern, resets the board with it and runs.
+/// `Action::OpenMenu` goes back to the menu from a run, resetting the board, and the
+/// shortcuts stay off while the menu is up.
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Menu>()
+            .init_resource::<PendingWarmup>()
+            .init_resource::<KeyBindings>()
+            .init_resource::<SimRng>()
+            .init_resource::<Palette>()
+            .init_resource::<TurnSymbols>()
+            .init_resource::<PatternLimits>()
+            .init_resource::<ClearColor>()
+            .configure_sets(Update, Shortcuts.run_if(not(in_state(AppState::Menu))))
+            .add_systems(OnEnter(AppState::Menu), spawn_menu)
+            .add_systems(OnExit(AppState::Menu), despawn_menu)
+            .add_systems(
+                Update,
+                (
+                    // Before the pattern input, whose own Escape would reopen the menu
+                    open_menu
+                        .run_if(pattern_input_closed)
+                        .in_set(Shortcuts)
+                        .before(pattern_input),
+                    (press_buttons, type_pattern, update_menu)
+                        .chain()
+                        .run_if(in_state(AppState::Menu)),
+                ),
+            );
+    }
+}
+
+/// Pattern typed or picked on the menu, and why the last one failed to start
+#[derive(Resource, Default)]
+pub struct Menu {
+    pub text: String,
+    pub error: Option<String>,
+    /// Colors every started pattern gets, in the form of `Pattern::set_colors`
+    pub colors: Option<String>,
+}
+
+#[derive(Component)]
+struct MenuRoot;
+
+#[derive(Component)]
+struct MenuText;
+
+/// What a menu button does when clicked
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuButton {
+    /// Puts the rule of `PRESETS[index]` in the pattern field
+    Preset(usize),
+    Start,
+}
+
+fn open_menu(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut reset_events: EventWriter<ResetBoard>,
+) {
+    if bindings.just_pressed(Action::OpenMenu, &keys) {
+        next_state.set(AppState::Menu);
+        reset_events.send(ResetBoard);
+    }
+}
+
+fn spawn_menu(
+    mut commands: Commands,
+    mut menu: ResMut<Menu>,
+    pattern: Res<Pattern>,
+    background: Res<ClearColor>,
+) {
+    menu.text = pattern.to_string();
+    menu.error = None;
+
+    let text_style = |font_size: f32| TextStyle {
+        font_size,
+        color: foreground(background.0),
+        ..default()
+    };
+    // The buttons stay light whatever the theme
+    let button_text_style = |font_size: f32| TextStyle {
+        font_size,
+        color: Color::BLACK,
+        ..default()
+    };
+    let button = |width: f32| ButtonBundle {
+        style: Style {
+            width: Val::Px(width),
+            padding: UiRect::all(Val::Px(8.)),
+            margin: UiRect::all(Val::Px(4.)),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        background_color: BUTTON_COLOR.into(),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: background.0.with_alpha(0.9).into(),
+                // Above the other overlays
+                z_index: ZIndex::Global(1),
+                ..default()
+            },
+            MenuRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Langton's ant", text_style(40.)));
+            parent.spawn(
+                TextBundle::from_section("Pick a preset or type a pattern", text_style(20.))
+                    .with_style(Style {
+                        margin: UiRect::bottom(Val::Px(16.)),
+                        ..default()
+                    }),
+            );
+            for (index, (name, rule, description)) in PRESETS.iter().enumerate() {
+                parent
+                    .spawn((button(420.), MenuButton::Preset(index)))
+                    .with_children(|button| {
+                        button.spawn(TextBundle::from_section(
+                            format!("{name}: {rule}, {description}"),
+                            button_text_style(18.),
+                        ));
+                    });
+            }
+            parent.spawn((
+                TextBundle::from_sections([
+                    TextSection::new("", text_style(24.)),
+                    TextSection::new(
+                        "",
+                        TextStyle {
+                            font_size: 18.,
+                            color: Color::srgb(0.8, 0.1, 0.1),
+                            ..default()
+                        },
+                    ),
+                ])
+                .with_style(Style {
+                    margin: UiRect::vertical(Val::Px(16.)),
+                    ..default()
+                }),
+                MenuText,
+            ));
+            parent
+                .spawn((button(160.), MenuButton::Start))
+                .with_children(|button| {
+                    button.spawn(TextBundle::from_section("Start", button_text_style(24.)));
+                });
+        });
+}
+
+fn despawn_menu(mut commands: Commands, root_query: Query<Entity, With<MenuRoot>>) {
+    for root in root_query.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn press_buttons(
+    mut menu: ResMut<Menu>,
+    mut start: StartRun,
+    mut button_query: Query<
+        (&Interaction, &MenuButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+) {
+    for (interaction, button, mut color) in button_query.iter_mut() {
+        *color = match interaction {
+            Interaction::Pressed => PRESSED_COLOR,
+            Interaction::Hovered => HOVERED_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        }
+        .into();
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            MenuButton::Preset(index) => {
+                menu.text = PRESETS[*index].1.to_owned();
+                menu.error = None;
+            }
+            MenuButton::Start => start.run(&mut menu),
+        }
+    }
+}
+
+/// Edits the pattern field, Enter starts like the start button
+fn type_pattern(
+    mut menu: ResMut<Menu>,
+    mut start: StartRun,
+    mut keyboard_events: EventReader<KeyboardInput>,
+) {
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Character(c) => menu.text.push_str(c),
+            Key::Space => menu.text.push(' '),
+            Key::Backspace => {
+                menu.text.pop();
+            }
+            Key::Enter => start.run(&mut menu),
+            _ => (),
+        }
+    }
+}
+
+/// What starting a run from the menu needs
+#[derive(SystemParam)]
+struct StartRun<'w, 's> {
+    commands: Commands<'w, 's>,
+    palette: Res<'w, Palette>,
+    symbols: Res<'w, TurnSymbols>,
+    limits: Res<'w, PatternLimits>,
+    rng: ResMut<'w, SimRng>,
+    pending_warmup: ResMut<'w, PendingWarmup>,
+    next_state: ResMut<'w, NextState<AppState>>,
+    reset_events: EventWriter<'w, ResetBoard>,
+}
+
+impl StartRun<'_, '_> {
+    /// Runs the pattern typed in `menu` in its colors after the warmup, or tells why it
+    /// can't
+    fn run(&mut self, menu: &mut Menu) {
+        match Pattern::parse_with_symbols(
+            menu.text.clone(),
+            *self.palette,
+            &self.symbols,
+            &mut self.rng.0,
+        )
+        .and_then(|mut pattern| {
+            if let Some(colors) = &menu.colors {
+                pattern.set_colors(colors)?;
+            }
+            pattern.validated(&self.limits)
+        }) {
+            Ok(pattern) => {
+                info!("starting {pattern} ({} states)", pattern.turns.len());
+                self.commands.insert_resource(pattern);
+                self.pending_warmup.0 = true;
+                self.reset_events.send(ResetBoard);
+                self.next_state.set(AppState::Running);
+                menu.error = None;
+            }
+            Err(err) => menu.error = Some(err.to_string()),
+        }
+    }
+}
+
+fn update_menu(menu: Res<Menu>, mut text_query: Query<&mut Text, With<MenuText>>) {
+    if !menu.is_changed() {
+        return;
+    }
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = format!("Pattern: {}_", menu.text);
+        text.sections[1].value = match &menu.error {
+            Some(err) => format!("\n{err}"),
+            None => String::new(),
+        };
+    }
+}
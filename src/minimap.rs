@@ -0,0 +1,251 @@
+use std::{marker::PhantomData, time::Duration};
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+};
+use bevy_pancam::PanCam;
+
+use crate::{
+    board::{Ant, Board, WorldOrigin},
+    keybindings::{Action, KeyBindings},
+    lattice::Lattice,
+    pattern::Pattern,
+    pattern_input::pattern_input_closed,
+    Shortcuts, TILE_SIZE,
+};
+
+/// Width and height of the minimap, in screen pixels and texels alike
+pub const MINIMAP_SIZE: u32 = 160;
+
+/// Time between two repaints of the minimap
+const REFRESH_PERIOD: Duration = Duration::from_millis(500);
+
+/// Gap between the minimap and the top left corner of the window, in pixels
+const MINIMAP_MARGIN: f32 = 10.;
+
+const BACKGROUND: [u8; 4] = [255, 255, 255, 220];
+const FRAME: [u8; 4] = [0, 0, 0, 255];
+const ANT_MARKER: [u8; 4] = [255, 0, 0, 255];
+const VIEW_OUTLINE: [u8; 4] = [0, 90, 255, 255];
+
+/// Shows the bounding box of every painted cell at reduced scale in the top left
+/// corner, with a red dot on each ant and the main view outlined in blue, so zooming in
+/// doesn't lose track of the rest of the field. The thumbnail is repainted from the grid
+/// twice a second rather than every frame. `Action::ToggleMinimap` shows and hides it.
+pub struct MinimapPlugin<L: Lattice> {
+    /// Whether the minimap is shown from the start
+    pub shown: bool,
+    lattice: PhantomData<L>,
+}
+
+impl<L: Lattice> MinimapPlugin<L> {
+    pub fn new(shown: bool) -> Self {
+        Self {
+            shown,
+            lattice: PhantomData,
+        }
+    }
+}
+
+impl<L: Lattice> Plugin for MinimapPlugin<L> {
+    fn build(&self, app: &mut App) {
+        // Due straight away, so the first frame already shows it
+        let mut timer = Timer::new(REFRESH_PERIOD, TimerMode::Repeating);
+        timer.set_elapsed(REFRESH_PERIOD);
+        app.insert_resource(Minimap {
+            shown: self.shown,
+            timer,
+            image: Handle::default(),
+        })
+        .init_resource::<KeyBindings>()
+        .add_systems(Startup, setup_minimap)
+        .add_systems(
+            Update,
+            (
+                toggle_minimap
+                    .run_if(pattern_input_closed)
+                    .in_set(Shortcuts),
+                paint_minimap::<L>,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct Minimap {
+    pub shown: bool,
+    timer: Timer,
+    /// Thumbnail drawn by the minimap node
+    pub image: Handle<Image>,
+}
+
+#[derive(Component)]
+struct MinimapNode;
+
+fn setup_minimap(
+    mut commands: Commands,
+    mut minimap: ResMut<Minimap>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: MINIMAP_SIZE,
+            height: MINIMAP_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &BACKGROUND,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = ImageSampler::nearest();
+    minimap.image = images.add(image);
+
+    commands.spawn((
+        ImageBundle {
+            image: UiImage::new(minimap.image.clone()),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(MINIMAP_MARGIN),
+                left: Val::Px(MINIMAP_MARGIN),
+                width: Val::Px(MINIMAP_SIZE as f32),
+                height: Val::Px(MINIMAP_SIZE as f32),
+                ..default()
+            },
+            visibility: if minimap.shown {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            },
+            ..default()
+        },
+        MinimapNode,
+    ));
+}
+
+fn toggle_minimap(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut minimap: ResMut<Minimap>,
+) {
+    if bindings.just_pressed(Action::ToggleMinimap, &keys) {
+        minimap.shown = !minimap.shown;
+        // Stale while it was hidden, repaint on the next frame
+        minimap.timer.set_elapsed(REFRESH_PERIOD);
+    }
+}
+
+/// Repaints the thumbnail every `REFRESH_PERIOD` while shown
+#[allow(clippy::too_many_arguments)]
+fn paint_minimap<L: Lattice>(
+    time: Res<Time>,
+    mut minimap: ResMut<Minimap>,
+    mut images: ResMut<Assets<Image>>,
+    board: Res<Board>,
+    pattern: Res<Pattern>,
+    origin: Res<WorldOrigin>,
+    ant_query: Query<&Ant<L>>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<PanCam>>,
+    mut node_query: Query<&mut Visibility, With<MinimapNode>>,
+) {
+    let visibility = if minimap.shown {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    for mut node_visibility in node_query.iter_mut() {
+        node_visibility.set_if_neq(visibility);
+    }
+    if !minimap.shown {
+        return;
+    }
+    if !minimap.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Some(image) = images.get_mut(&minimap.image) else {
+        return;
+    };
+
+    // World area shown: every painted cell and ant, squared up around its center
+    let corners = board.grid.bounds().into_iter().flat_map(|bounds| {
+        [
+            bounds.min,
+            bounds.max,
+            IVec2::new(bounds.min.x, bounds.max.y),
+            IVec2::new(bounds.max.x, bounds.min.y),
+        ]
+    });
+    let ants = ant_query.iter().map(|ant| ant.0.cell);
+    let Some(area) = corners
+        .chain(ants)
+        .map(|cell| origin.cell_to_world::<L>(cell))
+        .map(|center| Rect::from_center_size(center, Vec2::splat(TILE_SIZE)))
+        .reduce(|area, cell| area.union(cell))
+    else {
+        image
+            .data
+            .chunks_exact_mut(4)
+            .for_each(|texel| texel.copy_from_slice(&BACKGROUND));
+        return;
+    };
+    let side = area.width().max(area.height());
+    let area = Rect::from_center_size(area.center(), Vec2::splat(side));
+    let size = MINIMAP_SIZE as i32;
+    // Texel under a world position, rows run top down
+    let to_texel = |position: Vec2| {
+        let fraction = (position - area.min) / side;
+        IVec2::new(
+            (fraction.x * size as f32).floor() as i32,
+            size - 1 - (fraction.y * size as f32).floor() as i32,
+        )
+    };
+    let mut put = |texel: IVec2, color: &[u8; 4]| {
+        if (0..size).contains(&texel.x) && (0..size).contains(&texel.y) {
+            let at = (texel.y * size + texel.x) as usize * 4;
+            image.data[at..at + 4].copy_from_slice(color);
+        }
+    };
+
+    for y in 0..size {
+        for x in 0..size {
+            let on_frame = x == 0 || y == 0 || x == size - 1 || y == size - 1;
+            put(
+                IVec2::new(x, y),
+                if on_frame { &FRAME } else { &BACKGROUND },
+            );
+        }
+    }
+    for (cell, state) in board.grid.cells() {
+        let color = pattern.colors[state].to_srgba().to_u8_array();
+        put(to_texel(origin.cell_to_world::<L>(cell)), &color);
+    }
+    if let Ok((transform, projection)) = camera_query.get_single() {
+        let view = to_texel(transform.translation.truncate() + projection.area.min);
+        let view_max = to_texel(transform.translation.truncate() + projection.area.max);
+        // Rows run top down, so the view's bottom left lands on its bottom row
+        let (left, right, top, bottom) = (view.x, view_max.x, view_max.y, view.y);
+        // Only the parts of the outline that fall on the minimap
+        for x in left.max(0)..=right.min(size - 1) {
+            put(IVec2::new(x, top), &VIEW_OUTLINE);
+            put(IVec2::new(x, bottom), &VIEW_OUTLINE);
+        }
+        for y in top.max(0)..=bottom.min(size - 1) {
+            put(IVec2::new(left, y), &VIEW_OUTLINE);
+            put(IVec2::new(right, y), &VIEW_OUTLINE);
+        }
+    }
+    for ant in ant_query.iter() {
+        let texel = to_texel(origin.cell_to_world::<L>(ant.0.cell));
+        for y in -1..=1 {
+            for x in -1..=1 {
+                put(texel + IVec2::new(x, y), &ANT_MARKER);
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use anyhow::{bail, Result};
+use bevy::prelude::*;
+use rand::prelude::*;
+
+/// Okabe-Ito colors, which stay distinct under the common color vision deficiencies.
+/// Black comes last so small patterns don't hide the ant.
+const OKABE_ITO: [Srgba; 8] = [
+    Srgba::rgb(0.902, 0.624, 0.),
+    Srgba::rgb(0.337, 0.706, 0.914),
+    Srgba::rgb(0., 0.620, 0.451),
+    Srgba::rgb(0.941, 0.894, 0.259),
+    Srgba::rgb(0., 0.447, 0.698),
+    Srgba::rgb(0.835, 0.369, 0.),
+    Srgba::rgb(0.800, 0.475, 0.655),
+    Srgba::rgb(0., 0., 0.),
+];
+
+/// Stops of matplotlib's viridis map, dark blue through green to yellow
+pub(crate) const VIRIDIS: [Srgba; 5] = [
+    Srgba::rgb(0.267, 0.005, 0.329),
+    Srgba::rgb(0.231, 0.322, 0.545),
+    Srgba::rgb(0.129, 0.569, 0.549),
+    Srgba::rgb(0.369, 0.788, 0.384),
+    Srgba::rgb(0.992, 0.906, 0.145),
+];
+
+/// Stops of matplotlib's magma map, black through purple and red to pale yellow
+pub(crate) const MAGMA: [Srgba; 5] = [
+    Srgba::rgb(0., 0., 0.016),
+    Srgba::rgb(0.3176, 0.071, 0.486),
+    Srgba::rgb(0.718, 0.216, 0.475),
+    Srgba::rgb(0.988, 0.537, 0.380),
+    Srgba::rgb(0.988, 0.992, 0.749),
+];
+
+/// Smallest Oklab distance `check_cvd_safe` accepts between two simulated state colors,
+/// the Okabe-Ito set just clears it
+pub const MIN_CVD_DISTANCE: f32 = 0.07;
+
+/// Most states that still get random colors, past this they start to look alike
+const MAX_RANDOM_STATES: usize = 8;
+
+/// Where state colors come from when a pattern doesn't pin them. Only `Random` draws
+/// from the rng, every other palette is fixed, so `--seed` doesn't change its colors.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Palette {
+    /// Random reds, greens and yellows, `Distinct` past `MAX_RANDOM_STATES` states
+    #[default]
+    Random,
+    /// Okabe-Ito colors, repeated lighter and then darker past eight states and `Distinct`
+    /// past 24
+    ColorBlindSafe,
+    /// Hues a golden angle apart, stepping to another saturation and brightness
+    /// every eight states, so even 20+ states stay apart
+    Distinct,
+    /// Viridis sampled evenly over the states, first state darkest
+    Viridis,
+    /// Magma sampled evenly over the states, first state darkest
+    Magma,
+    /// Light gray down to black
+    Grayscale,
+}
+
+impl Palette {
+    /// Color of state `index` out of `states`, only `Random` draws from `rng`
+    pub fn color(self, index: usize, states: usize, rng: &mut impl Rng) -> Color {
+        match self {
+            Palette::Random if states > MAX_RANDOM_STATES => distinct(index),
+            Palette::Random => Color::srgb(rng.gen_range(0.1..0.8), rng.gen_range(0.1..0.8), 0.),
+            Palette::ColorBlindSafe => okabe_ito(index),
+            Palette::Distinct => distinct(index),
+            Palette::Viridis => gradient(&VIRIDIS, index, states),
+            Palette::Magma => gradient(&MAGMA, index, states),
+            Palette::Grayscale => grayscale(index, states),
+        }
+    }
+}
+
+/// Okabe-Ito color of state `index`, lighter and then darker on every pass
+pub fn okabe_ito(index: usize) -> Color {
+    if index >= 3 * OKABE_ITO.len() {
+        return distinct(index);
+    }
+    let color = OKABE_ITO[index % OKABE_ITO.len()];
+    let color = match index / OKABE_ITO.len() % 3 {
+        0 => color,
+        1 => color.lighter(0.25),
+        _ => color.darker(0.25),
+    };
+    color.into()
+}
+
+/// Color of state `index` with hues a golden angle apart
+pub fn distinct(index: usize) -> Color {
+    const LEVELS: [(f32, f32); 4] = [(0.7, 1.), (0.7, 0.8), (0.35, 1.), (0.45, 0.8)];
+    let hue = (index as f32 * 222.49) % 360.;
+    let (saturation, value) = LEVELS[index / 8 % LEVELS.len()];
+    Hsva::hsv(hue, saturation, value).into()
+}
+
+/// `stops` spread evenly over the states and blended in Oklab in between
+pub fn gradient(stops: &[Srgba], index: usize, states: usize) -> Color {
+    let t = index as f32 / states.saturating_sub(1).max(1) as f32;
+    let position = t * (stops.len() - 1) as f32;
+    let stop = (position as usize).min(stops.len() - 2);
+    let from = Oklaba::from(stops[stop]);
+    let to = Oklaba::from(stops[stop + 1]);
+    from.mix(&to, position - stop as f32).into()
+}
+
+/// Even steps from light gray down to black
+pub fn grayscale(index: usize, states: usize) -> Color {
+    let t = index as f32 / states.saturating_sub(1).max(1) as f32;
+    Color::srgb(0.85 * (1. - t), 0.85 * (1. - t), 0.85 * (1. - t))
+}
+
+/// Red-green color vision deficiencies, the common ones
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cvd {
+    Protanopia,
+    Deuteranopia,
+}
+
+impl Cvd {
+    pub const ALL: [Cvd; 2] = [Cvd::Protanopia, Cvd::Deuteranopia];
+
+    /// How `color` looks with this deficiency, using the full severity matrices of
+    /// Machado et al. (2009) on linear RGB
+    pub fn simulate(self, color: Color) -> Color {
+        let matrix = match self {
+            Cvd::Protanopia => [
+                [0.152_286, 1.052_583, -0.204_868],
+                [0.114_503, 0.786_281, 0.099_216],
+                [-0.003_882, -0.048_116, 1.051_998],
+            ],
+            Cvd::Deuteranopia => [
+                [0.367_322, 0.860_646, -0.227_968],
+                [0.280_085, 0.672_501, 0.047_413],
+                [-0.011_820, 0.042_940, 0.968_881],
+            ],
+        };
+        let linear = LinearRgba::from(color);
+        let [red, green, blue] = matrix.map(|row| {
+            (row[0] * linear.red + row[1] * linear.green + row[2] * linear.blue).clamp(0., 1.)
+        });
+        LinearRgba::rgb(red, green, blue).into()
+    }
+}
+
+/// Fails if two of `colors` come closer than `MIN_CVD_DISTANCE` under any `Cvd`
+pub fn check_cvd_safe(colors: &[Color]) -> Result<()> {
+    for cvd in Cvd::ALL {
+        let simulated: Vec<Vec3> = colors
+            .iter()
+            .map(|color| {
+                let oklab = Oklaba::from(cvd.simulate(*color));
+                Vec3::new(oklab.lightness, oklab.a, oklab.b)
+            })
+            .collect();
+        for (index, color) in simulated.iter().enumerate() {
+            for (other, other_color) in simulated[..index].iter().enumerate() {
+                let distance = color.distance(*other_color);
+                if distance < MIN_CVD_DISTANCE {
+                    bail!("states {other} and {index} look alike with {cvd:?} (distance {distance:.3})");
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,510 @@
+use anyhow::{anyhow, bail, Context, Result};
+use bevy::{
+    color::palettes::{basic, css},
+    prelude::*,
+    utils::HashMap,
+};
+use rand::prelude::*;
+
+use crate::{
+    lattice::{Lattice, SquareLattice},
+    palette::Palette,
+    sim::GridState,
+};
+
+/// Default for `PatternLimits::max_states`, well past any pattern worth looking at
+pub const DEFAULT_MAX_STATES: usize = 256;
+
+/// States past which a pattern is allowed but warned about, its colors no longer tell
+/// apart well
+pub const MANY_STATES: usize = 64;
+
+/// Colors of the `rainbow` preset, red to violet
+const RAINBOW: [Srgba; 7] = [
+    basic::RED,
+    css::ORANGE,
+    basic::YELLOW,
+    basic::LIME,
+    basic::BLUE,
+    css::INDIGO,
+    css::VIOLET,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Turn {
+    /// The lattice's own clockwise turn, 90° on squares and 60° on hexes and triangles
+    Right,
+    Left,
+    /// Clockwise by this many degrees, counter-clockwise when negative. Every lattice is
+    /// discrete, so the angle has to land on one of its headings, see `Lattice::check_turn`.
+    Degrees(i32),
+    /// Inert state: the ant walks straight on and leaves the cell as it is, so the cell
+    /// keeps this state for good
+    Identity,
+}
+
+impl Turn {
+    /// The same turn seen in a mirror
+    pub fn mirrored(self) -> Self {
+        match self {
+            Turn::Right => Turn::Left,
+            Turn::Left => Turn::Right,
+            Turn::Degrees(degrees) => Turn::Degrees(-degrees),
+            Turn::Identity => Turn::Identity,
+        }
+    }
+
+    /// Clockwise turn in multiples of `step` degrees, rounded to the nearest one
+    pub fn clockwise_steps(self, step: i32) -> i32 {
+        match self {
+            Turn::Right => 1,
+            Turn::Left => -1,
+            Turn::Degrees(degrees) => (degrees as f32 / step as f32).round() as i32,
+            Turn::Identity => 0,
+        }
+    }
+}
+
+impl std::fmt::Display for Turn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Turn::Right => write!(f, "R"),
+            Turn::Left => write!(f, "L"),
+            Turn::Degrees(degrees) if degrees < 0 => write!(f, "L{}", -degrees),
+            Turn::Degrees(degrees) => write!(f, "R{degrees}"),
+            Turn::Identity => write!(f, "i"),
+        }
+    }
+}
+
+/// Extra single-character turns, each standing for a fixed angle in degrees, clockwise
+/// positive like `Turn::Degrees`
+#[derive(Resource, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TurnSymbols(pub HashMap<char, i32>);
+
+impl TurnSymbols {
+    /// Reads definitions such as `A=+90, B=-90, C=+45`, separated by commas or newlines.
+    /// `L`, `R`, `i` and the characters with a meaning of their own in a pattern can't be
+    /// redefined.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut symbols = HashMap::default();
+        for definition in text.split([',', '\n']).map(str::trim) {
+            if definition.is_empty() {
+                continue;
+            }
+            let (symbol, angle) = definition
+                .split_once('=')
+                .with_context(|| format!("expected SYMBOL=ANGLE, got `{definition}`"))?;
+            let mut chars = symbol.trim().chars();
+            let (Some(symbol), None) = (chars.next(), chars.next()) else {
+                bail!("turn symbol `{}` must be a single character", symbol.trim());
+            };
+            if "rlRLiI#:*".contains(symbol) || symbol.is_whitespace() || symbol.is_ascii_digit() {
+                bail!("`{symbol}` can't be used as a turn symbol");
+            }
+            let angle = angle.trim();
+            let degrees = angle
+                .strip_prefix('+')
+                .unwrap_or(angle)
+                .parse()
+                .with_context(|| format!("invalid angle `{angle}` for turn symbol `{symbol}`"))?;
+            if symbols.insert(symbol, degrees).is_some() {
+                bail!("turn symbol `{symbol}` is defined twice");
+            }
+        }
+        Ok(Self(symbols))
+    }
+}
+
+/// What every pattern is checked against before it runs, whichever way it comes in: the
+/// command line, P, the start menu, Tab or the control panel. See `Pattern::validate`.
+/// `Default` is for the square lattice and an unbounded flat grid.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct PatternLimits {
+    /// Most states a pattern may have, from `--max-states`. A pattern of thousands of
+    /// states is most likely a mistake and would need a material per state.
+    pub max_states: usize,
+    /// Most states the grid can store, see `GridState::max_states`
+    pub grid_states: Option<usize>,
+    /// `Pattern::check_turns` for the lattice the ants walk on
+    pub check_turns: fn(&Pattern) -> Result<()>,
+}
+
+impl PatternLimits {
+    /// Limits of a run on lattice `L` storing its cells in `grid`
+    pub fn new<L: Lattice>(max_states: usize, grid: &GridState) -> Self {
+        Self {
+            max_states,
+            grid_states: grid.max_states(),
+            check_turns: Pattern::check_turns::<L>,
+        }
+    }
+}
+
+impl Default for PatternLimits {
+    fn default() -> Self {
+        Self::new::<SquareLattice>(DEFAULT_MAX_STATES, &GridState::default())
+    }
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct Pattern {
+    pub colors: Vec<Color>,
+    pub turns: Vec<Turn>,
+    /// Cells the ant moves on after leaving a cell in each state, 1 unless the pattern
+    /// gives another with `*`. Only the cell left is repainted, the ones jumped over
+    /// aren't touched.
+    pub distances: Vec<u8>,
+}
+
+impl Pattern {
+    /// Whitespace is ignored and `#` starts a comment running to the end of the string.
+    /// A turn followed by an angle in degrees, as in `R45` or `L120`, turns by that much
+    /// instead of the lattice's own turn, and `i` is an inert state, see `Turn::Identity`.
+    /// A turn followed by `*` and a distance, as in `R*2L`, moves the ant that many cells
+    /// instead of one. A turn followed by `:` and a color, as in `R:#ff0000 L:blue`, pins
+    /// that state's color, the other states get random ones. The distance goes before
+    /// the color, as in `R45*2:red`.
+    ///
+    /// Characters in `symbols` are turns too. Other characters are skipped, unless there
+    /// are symbols, then letters and digits that aren't defined are an error.
+    fn parse_pattern(
+        &mut self,
+        pattern: &str,
+        palette: Palette,
+        symbols: &TurnSymbols,
+        rng: &mut impl Rng,
+    ) -> Result<()> {
+        let mut chars = pattern.chars().peekable();
+        let mut pinned = Vec::new();
+
+        while let Some(c) = chars.next() {
+            let clockwise = match c.to_ascii_lowercase() {
+                '#' => break,
+                'r' => Some(true),
+                'l' => Some(false),
+                'i' => None,
+                _ if symbols.0.contains_key(&c) => {
+                    self.turns.push(Turn::Degrees(symbols.0[&c]));
+                    self.distances.push(parse_distance(&mut chars)?);
+                    pinned.push(parse_pinned_color(&mut chars)?);
+                    continue;
+                }
+                '*' => bail!("a distance `*` must follow a turn"),
+                _ if c.is_ascii_digit() && symbols.0.is_empty() => {
+                    bail!("`{c}` must follow a turn, as an angle")
+                }
+                _ if !symbols.0.is_empty() && c.is_alphanumeric() => {
+                    bail!("turn symbol `{c}` is not defined")
+                }
+                _ => continue,
+            };
+
+            let mut angle = String::new();
+            while let Some(c) = chars.next_if(char::is_ascii_digit) {
+                angle.push(c);
+            }
+            let turn = match (angle.parse::<i32>(), clockwise) {
+                (_, None) if !angle.is_empty() => bail!("the inert state `i` takes no angle"),
+                (_, None) => Turn::Identity,
+                (Ok(degrees), Some(true)) => Turn::Degrees(degrees),
+                (Ok(degrees), Some(false)) => Turn::Degrees(-degrees),
+                (Err(_), _) if !angle.is_empty() => bail!("turn angle {angle} is too large"),
+                (Err(_), Some(true)) => Turn::Right,
+                (Err(_), Some(false)) => Turn::Left,
+            };
+
+            self.distances.push(parse_distance(&mut chars)?);
+            pinned.push(parse_pinned_color(&mut chars)?);
+            self.turns.push(turn);
+        }
+
+        // The palette color is drawn either way, so pinning one state keeps the others
+        let states = self.turns.len();
+        self.colors = pinned
+            .into_iter()
+            .enumerate()
+            .map(|(index, pinned)| {
+                let color = palette.color(index, states, rng);
+                pinned.unwrap_or(color)
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// Random colors are drawn from `rng`, two values per state
+    pub fn parse(pattern: String, rng: &mut impl Rng) -> Result<Self> {
+        Self::parse_with_palette(pattern, Palette::Random, rng)
+    }
+
+    /// Like `parse`, with the colors of states that don't pin one taken from `palette`
+    pub fn parse_with_palette(
+        pattern: String,
+        palette: Palette,
+        rng: &mut impl Rng,
+    ) -> Result<Self> {
+        Self::parse_with_symbols(pattern, palette, &TurnSymbols::default(), rng)
+    }
+
+    /// Like `parse_with_palette`, also accepting the turns in `symbols`
+    pub fn parse_with_symbols(
+        pattern: String,
+        palette: Palette,
+        symbols: &TurnSymbols,
+        rng: &mut impl Rng,
+    ) -> Result<Self> {
+        let mut s = Pattern {
+            colors: Vec::new(),
+            turns: Vec::new(),
+            distances: Vec::new(),
+        };
+
+        s.parse_pattern(&pattern, palette, symbols, rng)?;
+        let pattern = Self::new(s.turns, s.colors)?.with_distances(s.distances)?;
+        let first = (pattern.turns[0], pattern.distances[0]);
+        if pattern
+            .turns
+            .iter()
+            .zip(&pattern.distances)
+            .all(|(turn, distance)| (*turn, *distance) == first)
+        {
+            bail!(
+                "every state of {pattern} turns the same way, so the ant only circles and never \
+                 builds anything"
+            );
+        }
+        Ok(pattern)
+    }
+
+    /// Pattern with one color per turn, moving one cell on every step. It needs at least
+    /// two states, and no two states may share a color, or the drawing couldn't tell them
+    /// apart.
+    pub fn new(turns: Vec<Turn>, colors: Vec<Color>) -> Result<Self> {
+        if turns.len() < 2 {
+            bail!("incorrect pattern: should be at least 2 correct values (L, R)");
+        }
+        if colors.len() != turns.len() {
+            bail!(
+                "expected one color per state ({}), got {}",
+                turns.len(),
+                colors.len()
+            );
+        }
+        Self::check_colors(&colors)?;
+
+        let distances = vec![1; turns.len()];
+        Ok(Self {
+            colors,
+            turns,
+            distances,
+        })
+    }
+
+    /// Fails if two states share a color, whether parsed or picked in the control panel
+    pub fn check_colors(colors: &[Color]) -> Result<()> {
+        for (index, color) in colors.iter().enumerate() {
+            if let Some(other) = colors[..index].iter().position(|other| other == color) {
+                bail!(
+                    "states {other} and {index} share the color {}",
+                    color.to_srgba().to_hex()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// The same pattern moving `distances[state]` cells on leaving a cell in `state`
+    pub fn with_distances(mut self, distances: Vec<u8>) -> Result<Self> {
+        if distances.len() != self.turns.len() {
+            bail!(
+                "expected one distance per state ({}), got {}",
+                self.turns.len(),
+                distances.len()
+            );
+        }
+        if let Some(state) = distances.iter().position(|distance| *distance == 0) {
+            bail!("state {state} moves 0 cells, distances start at 1");
+        }
+        self.distances = distances;
+        Ok(self)
+    }
+
+    /// Replaces the random colors with `spec`: one name or hex color per state separated by
+    /// whitespace or commas, or `rainbow` for patterns of up to 7 states
+    pub fn set_colors(&mut self, spec: &str) -> Result<()> {
+        let colors: Vec<Color> = if spec.trim().eq_ignore_ascii_case("rainbow") {
+            if self.turns.len() > RAINBOW.len() {
+                bail!(
+                    "the rainbow preset has {} colors, the pattern has {} states",
+                    RAINBOW.len(),
+                    self.turns.len()
+                );
+            }
+            RAINBOW[..self.turns.len()]
+                .iter()
+                .map(|color| Color::from(*color))
+                .collect()
+        } else {
+            spec.split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|color| !color.is_empty())
+                .map(parse_color)
+                .collect::<Result<_>>()?
+        };
+
+        *self = Self::new(self.turns.clone(), colors)?.with_distances(self.distances.clone())?;
+        Ok(())
+    }
+
+    /// State and turn for a cell currently in `state`, an inert state stays as it is. An
+    /// unvisited cell steps as state 0.
+    pub fn next(&self, state: usize) -> (usize, Turn) {
+        match self.turns[state] {
+            Turn::Identity => (state, Turn::Identity),
+            turn => ((state + 1) % self.turns.len(), turn),
+        }
+    }
+
+    /// Undoes `next`: state and turn for a cell currently in `state` stepped backwards,
+    /// with the turn that led out of the earlier state. An inert state stays as it is.
+    /// The caller mirrors the turn, so that it leads back the way `next` came.
+    pub fn previous(&self, state: usize) -> (usize, Turn) {
+        if self.turns[state] == Turn::Identity {
+            return (state, Turn::Identity);
+        }
+        let previous = (state + self.turns.len() - 1) % self.turns.len();
+        (previous, self.turns[previous])
+    }
+
+    /// Fails if the pattern has more than `max_states` states. Past `MANY_STATES` it
+    /// returns a warning for the caller to show, the command line checks its patterns
+    /// before logging is set up.
+    pub fn check_states(&self, max_states: usize) -> Result<Option<String>> {
+        let states = self.turns.len();
+        if states > max_states {
+            bail!(
+                "the pattern has {states} states, more than the limit of {max_states}: so many \
+                 states can't be told apart and each one needs its own material. \
+                 `--max-states` raises the limit"
+            );
+        }
+        Ok((states > MANY_STATES).then(|| {
+            format!("the pattern has {states} states, their colors will be hard to tell apart")
+        }))
+    }
+
+    /// Runs every check of `limits`, so a pattern that can't run is turned away the same
+    /// way on every path. Returns the warning of `check_states`, if any.
+    pub fn validate(&self, limits: &PatternLimits) -> Result<Option<String>> {
+        (limits.check_turns)(self)?;
+        let states = self.turns.len();
+        if let Some(grid_states) = limits.grid_states.filter(|max| states > *max) {
+            bail!("the pattern has {states} states, the chunked grid stores at most {grid_states}");
+        }
+        self.check_states(limits.max_states)
+    }
+
+    /// `validate` for patterns applied at runtime, logging the warning
+    pub fn validated(self, limits: &PatternLimits) -> Result<Self> {
+        if let Some(warning) = self.validate(limits)? {
+            warn!("{warning}");
+        }
+        Ok(self)
+    }
+
+    /// Fails on the first turn angle that doesn't land on a heading of lattice `L`, or
+    /// distance it can't move. Unchecked patterns still run, their angles snap to the
+    /// nearest valid heading.
+    pub fn check_turns<L: Lattice>(&self) -> Result<()> {
+        for (turn, &distance) in self.turns.iter().zip(&self.distances) {
+            L::check_distance(distance)
+                .with_context(|| format!("invalid distance {turn}*{distance}"))?;
+            let degrees = match turn {
+                Turn::Degrees(degrees) => *degrees,
+                // Walking straight on has to be possible
+                Turn::Identity => 0,
+                Turn::Right | Turn::Left => continue,
+            };
+            L::check_turn(degrees).with_context(|| format!("invalid turn {turn}"))?;
+        }
+        Ok(())
+    }
+}
+
+fn named_color(name: &str) -> Option<Srgba> {
+    let color = match name {
+        "aqua" | "cyan" => basic::AQUA,
+        "black" => basic::BLACK,
+        "blue" => basic::BLUE,
+        "fuchsia" | "magenta" => basic::FUCHSIA,
+        "gray" | "grey" => basic::GRAY,
+        "green" => basic::GREEN,
+        "indigo" => css::INDIGO,
+        "lime" => basic::LIME,
+        "maroon" => basic::MAROON,
+        "navy" => basic::NAVY,
+        "olive" => basic::OLIVE,
+        "orange" => css::ORANGE,
+        "purple" => basic::PURPLE,
+        "red" => basic::RED,
+        "silver" => basic::SILVER,
+        "teal" => basic::TEAL,
+        "violet" => css::VIOLET,
+        "white" => basic::WHITE,
+        "yellow" => basic::YELLOW,
+        _ => return None,
+    };
+    Some(color)
+}
+
+/// Distance after a `*` following a turn, 1 if there is none
+fn parse_distance(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<u8> {
+    if chars.next_if_eq(&'*').is_none() {
+        return Ok(1);
+    }
+    let mut distance = String::new();
+    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+        distance.push(c);
+    }
+    match distance.parse::<u8>() {
+        Ok(0) => bail!("a turn can't move 0 cells, distances start at 1"),
+        Ok(distance) => Ok(distance),
+        Err(_) if distance.is_empty() => bail!("`*` must be followed by a distance"),
+        Err(_) => bail!("distance {distance} is too large, at most 255 cells"),
+    }
+}
+
+/// Color after a `:` following a turn, if there is one
+fn parse_pinned_color(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<Option<Color>> {
+    if chars.next_if_eq(&':').is_none() {
+        return Ok(None);
+    }
+    let mut spec = String::new();
+    while let Some(c) = chars.next_if(|c| *c == '#' || c.is_ascii_alphanumeric()) {
+        spec.push(c);
+    }
+    parse_color(&spec).map(Some)
+}
+
+/// `#rrggbb` style hex or one of the basic CSS color names
+fn parse_color(spec: &str) -> Result<Color> {
+    if spec.starts_with('#') {
+        return Srgba::hex(spec)
+            .map(Color::from)
+            .map_err(|err| anyhow!("invalid color `{spec}`: {err}"));
+    }
+    named_color(&spec.to_lowercase())
+        .map(Color::from)
+        .ok_or_else(|| anyhow!("unknown color `{spec}`, expected a color name or #rrggbb"))
+}
+
+impl std::fmt::Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (turn, distance) in self.turns.iter().zip(&self.distances) {
+            write!(f, "{turn}")?;
+            if *distance > 1 {
+                write!(f, "*{distance}")?;
+            }
+        }
+        Ok(())
+    }
+}
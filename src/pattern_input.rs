@@ -0,0 +1,185 @@
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+};
+
+use crate::{
+    board::ResetBoard,
+    keybindings::{Action, KeyBindings},
+    palette::Palette,
+    pattern::{Pattern, PatternLimits, TurnSymbols},
+    Shortcuts, SimRng,
+};
+
+pub struct PatternInputPlugin;
+
+impl Plugin for PatternInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PatternInput>()
+            .init_resource::<KeyBindings>()
+            .init_resource::<SimRng>()
+            .init_resource::<Palette>()
+            .init_resource::<TurnSymbols>()
+            .init_resource::<PatternLimits>()
+            .add_systems(Startup, setup_overlay)
+            .add_systems(
+                Update,
+                (pattern_input.in_set(Shortcuts), update_overlay).chain(),
+            );
+    }
+}
+
+/// State of the pattern text-entry overlay
+#[derive(Resource, Default)]
+pub struct PatternInput {
+    open: bool,
+    text: String,
+    error: Option<String>,
+}
+
+/// Run condition for the regular key bindings, which are suppressed while typing
+pub fn pattern_input_closed(input: Res<PatternInput>) -> bool {
+    !input.open
+}
+
+#[derive(Component)]
+struct PatternInputOverlay;
+
+#[derive(Component)]
+struct PatternInputText;
+
+fn setup_overlay(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(10.),
+                    left: Val::Px(10.),
+                    padding: UiRect::all(Val::Px(8.)),
+                    ..default()
+                },
+                background_color: Color::srgba(0., 0., 0., 0.8).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            PatternInputOverlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_sections([
+                    TextSection::new(
+                        "",
+                        TextStyle {
+                            font_size: 24.,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    ),
+                    TextSection::new(
+                        "",
+                        TextStyle {
+                            font_size: 20.,
+                            color: Color::srgb(1., 0.2, 0.2),
+                            ..default()
+                        },
+                    ),
+                ]),
+                PatternInputText,
+            ));
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn pattern_input(
+    mut commands: Commands,
+    mut input: ResMut<PatternInput>,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut reset_events: EventWriter<ResetBoard>,
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    palette: Res<Palette>,
+    symbols: Res<TurnSymbols>,
+    limits: Res<PatternLimits>,
+    mut rng: ResMut<SimRng>,
+) {
+    if !input.open {
+        // Drop everything typed while closed, including the key that opens the overlay
+        keyboard_events.clear();
+        if bindings.just_pressed(Action::PatternInput, &keys) {
+            input.open = true;
+            input.text.clear();
+            input.error = None;
+        }
+        return;
+    }
+
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+
+        match &event.logical_key {
+            Key::Character(c) => {
+                input.text.push_str(c);
+            }
+            Key::Space => input.text.push(' '),
+            Key::Backspace => {
+                input.text.pop();
+            }
+            Key::Escape => {
+                input.open = false;
+                return;
+            }
+            Key::Enter => {
+                match Pattern::parse_with_symbols(
+                    input.text.clone(),
+                    *palette,
+                    &symbols,
+                    &mut rng.0,
+                )
+                .and_then(|pattern| pattern.validated(&limits))
+                {
+                    Ok(pattern) => {
+                        info!("pattern parsed: {pattern} ({} states)", pattern.turns.len());
+                        commands.insert_resource(pattern);
+                        reset_events.send(ResetBoard);
+                        input.open = false;
+                        return;
+                    }
+                    Err(err) => input.error = Some(err.to_string()),
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+fn update_overlay(
+    input: Res<PatternInput>,
+    mut overlay_query: Query<&mut Visibility, With<PatternInputOverlay>>,
+    mut text_query: Query<&mut Text, With<PatternInputText>>,
+) {
+    if !input.is_changed() {
+        return;
+    }
+
+    for mut visibility in overlay_query.iter_mut() {
+        *visibility = if input.open {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = format!("Pattern: {}_", input.text);
+        text.sections[1].value = match &input.error {
+            Some(err) => format!("\n{err}"),
+            None => String::new(),
+        };
+    }
+}
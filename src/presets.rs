@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::ResetBoard,
+    keybindings::{Action, KeyBindings},
+    palette::Palette,
+    pattern::{Pattern, PatternLimits},
+    pattern_input::pattern_input_closed,
+    Shortcuts, SimRng,
+};
+
+/// Notable rules by name, with what they grow into
+pub const PRESETS: &[(&str, &str, &str)] = &[
+    ("classic", "RL", "highway"),
+    ("chaotic", "RLR", "chaotic growth"),
+    ("symmetric", "LLRR", "symmetric blob"),
+    ("square", "LRRRRRLLR", "filled square"),
+    ("convoluted", "LLRRRLRLRLLR", "convoluted highway"),
+    ("triangle", "RRLLLRLLLRRR", "filled triangle"),
+];
+
+/// Tab switches to the next preset
+pub struct PresetsPlugin;
+
+impl Plugin for PresetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyBindings>()
+            .init_resource::<SimRng>()
+            .init_resource::<Palette>()
+            .init_resource::<PatternLimits>()
+            .add_systems(
+                Update,
+                next_preset.run_if(pattern_input_closed).in_set(Shortcuts),
+            );
+    }
+}
+
+/// Index of the preset `pattern` follows and its description, if any
+pub fn preset_of(pattern: &Pattern) -> Option<(usize, &'static str)> {
+    let rule = pattern.to_string();
+    PRESETS
+        .iter()
+        .position(|(_, preset, _)| *preset == rule)
+        .map(|index| (index, PRESETS[index].2))
+}
+
+/// Rule of the preset called `name`
+pub fn preset_rule(name: &str) -> Option<&'static str> {
+    PRESETS
+        .iter()
+        .find(|(preset, _, _)| *preset == name)
+        .map(|(_, rule, _)| *rule)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn next_preset(
+    mut commands: Commands,
+    mut reset_events: EventWriter<ResetBoard>,
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    pattern: Res<Pattern>,
+    palette: Res<Palette>,
+    limits: Res<PatternLimits>,
+    mut rng: ResMut<SimRng>,
+) {
+    if !bindings.just_pressed(Action::NextPreset, &keys) {
+        return;
+    }
+
+    let next = preset_of(&pattern).map_or(0, |(index, _)| (index + 1) % PRESETS.len());
+    let (_, rule, _) = PRESETS[next];
+    match Pattern::parse_with_palette(rule.to_owned(), *palette, &mut rng.0)
+        .and_then(|pattern| pattern.validated(&limits))
+    {
+        Ok(pattern) => {
+            commands.insert_resource(pattern);
+            reset_events.send(ResetBoard);
+        }
+        Err(err) => error!("can't run preset {rule}: {err}"),
+    }
+}
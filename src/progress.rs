@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::{Board, StepCounter, TileCap},
+    symmetry::SymmetryScore,
+    AppState,
+};
+
+/// Logs a progress line with `info!` every `every` steps: the step count, painted cells,
+/// their bounding box and the steps per second since the previous line, with the latest
+/// symmetry score if `SymmetryPlugin` is added
+pub struct ProgressLogPlugin {
+    pub every: u64,
+}
+
+impl Plugin for ProgressLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ProgressLog {
+            every: self.every,
+            next: self.every,
+            last_steps: 0,
+            running_time: 0.,
+        })
+        .add_systems(Update, log_progress);
+    }
+}
+
+#[derive(Resource)]
+struct ProgressLog {
+    every: u64,
+    /// Step count at which the next line is due
+    next: u64,
+    /// Step count of the previous line
+    last_steps: u64,
+    /// Wall time spent running since the previous line, paused time isn't counted
+    running_time: f64,
+}
+
+fn log_progress(
+    mut log: ResMut<ProgressLog>,
+    time: Res<Time<Real>>,
+    state: Res<State<AppState>>,
+    step_counter: Res<StepCounter>,
+    board: Res<Board>,
+    cap: Res<TileCap>,
+    symmetry: Option<Res<SymmetryScore>>,
+) {
+    let steps = step_counter.0;
+    // The board was reset, count from zero again
+    if steps < log.last_steps {
+        log.next = log.every;
+        log.last_steps = 0;
+        log.running_time = 0.;
+    }
+    if *state.get() == AppState::Running {
+        log.running_time += time.delta_seconds_f64();
+    }
+    if steps < log.next {
+        return;
+    }
+
+    let steps_per_second = if log.running_time > 0. {
+        (steps - log.last_steps) as f64 / log.running_time
+    } else {
+        0.
+    };
+    let bounds = match board.grid.bounds() {
+        Some(bounds) => format!(
+            "{}x{} from {} to {}",
+            bounds.width() + 1,
+            bounds.height() + 1,
+            bounds.min,
+            bounds.max
+        ),
+        None => "empty".to_owned(),
+    };
+    let evicted = match cap.max {
+        Some(_) => format!(", {} forgotten", cap.evicted),
+        None => String::new(),
+    };
+    let symmetry = match symmetry.and_then(|symmetry| symmetry.latest) {
+        Some((_, score)) => format!(
+            ", symmetry {:.3} vertical {:.3} horizontal {:.3} rotation",
+            score.vertical, score.horizontal, score.rotation
+        ),
+        None => String::new(),
+    };
+    info!(
+        "step {steps}: {} cells painted{evicted}, bounding box {bounds}, \
+         {steps_per_second:.0} steps/s{symmetry}",
+        board.grid.len()
+    );
+
+    // Steps per tick can jump past several multiples at once, log only the latest
+    log.next = (steps / log.every + 1) * log.every;
+    log.last_steps = steps;
+    log.running_time = 0.;
+}
@@ -0,0 +1,404 @@
+//! Grid snapshots in the run-length encoded `.rle` text format of Game of Life tools.
+//!
+//! ```text
+//! #C langtons-ant snapshot
+//! #P -2 1
+//! #A 0 0 North
+//! x = 5, y = 3, rule = RL
+//! .2A$B.2AB$4A!
+//! ```
+//!
+//! The header gives the bounding box and the rule. Rows run from the top of the box
+//! down and end with `$`, a count before a tag repeats it, and `!` ends the grid.
+//! Unpainted cells are `.` (or `b`), state `s` is the `s + 1`th multi-state tag: `A`
+//! to `X` for states 0 to 23, then two letters, `pA` to `pX` for 24 to 47 and so on up
+//! to `yO` for state 254. A plain two-state `o` reads as state 0.
+//!
+//! `#P` puts the top-left corner on a cell and defaults to the origin. Each `#A` line
+//! places an ant, in `AntId` order, by its cell and heading, followed by `mirrored`
+//! and `inverse` when those are set. `#V` lines hold the visit counts of the heatmap,
+//! one per painted cell in the order the cells are written, and are left out when the
+//! visits weren't counted. Other `#` lines are comments.
+
+use std::fmt::Write;
+
+use anyhow::{anyhow, bail, Context, Result};
+use bevy::{math::IVec2, utils::HashMap};
+
+use crate::{lattice::Lattice, sim::AntState};
+
+/// Most states the tags can tell apart
+pub const MAX_RLE_STATES: usize = 255;
+
+/// Widest line written, as other tools expect
+const LINE_WIDTH: usize = 70;
+
+/// Painted cells, the rule that painted them and where the ants stand
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub rule: String,
+    /// Painted cells and their state, in no particular order
+    pub cells: Vec<(IVec2, usize)>,
+    pub ants: Vec<SavedAnt>,
+    /// Times each painted cell was visited, empty if the visits weren't counted
+    pub visits: Vec<(IVec2, u32)>,
+}
+
+/// Ant read from or written to a snapshot, its heading named as in `Lattice::HEADINGS`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SavedAnt {
+    pub cell: IVec2,
+    pub heading: String,
+    pub mirrored: bool,
+    pub inverse: bool,
+}
+
+impl SavedAnt {
+    pub fn from_state<L: Lattice>(ant: &AntState<L>) -> Self {
+        Self {
+            cell: ant.cell,
+            heading: format!("{:?}", ant.heading),
+            mirrored: ant.mirrored,
+            inverse: ant.inverse,
+        }
+    }
+
+    /// The ant on lattice `L`, if it has a heading called that
+    pub fn to_state<L: Lattice>(&self) -> Result<AntState<L>> {
+        let heading = L::HEADINGS
+            .iter()
+            .find(|heading| format!("{heading:?}") == self.heading)
+            .ok_or_else(|| anyhow!("`{}` is not a heading of this grid", self.heading))?;
+        Ok(AntState {
+            cell: self.cell,
+            heading: *heading,
+            mirrored: self.mirrored,
+            inverse: self.inverse,
+        })
+    }
+}
+
+impl Snapshot {
+    /// Writes the snapshot out, failing if a state is past `MAX_RLE_STATES`
+    pub fn to_rle(&self) -> Result<String> {
+        let mut text = String::from("#C langtons-ant snapshot\n");
+        let Some((min, max)) = bounds(&self.cells) else {
+            for ant in &self.ants {
+                write_ant(&mut text, ant);
+            }
+            writeln!(text, "x = 0, y = 0, rule = {}", self.rule).unwrap();
+            text.push_str("!\n");
+            return Ok(text);
+        };
+
+        // Cell states row by row from the top, `None` for unpainted cells
+        let size = max - min + IVec2::ONE;
+        let mut rows = vec![vec![None; size.x as usize]; size.y as usize];
+        for &(cell, state) in &self.cells {
+            if state >= MAX_RLE_STATES {
+                bail!("state {state} is past the {MAX_RLE_STATES} states RLE can hold");
+            }
+            rows[(max.y - cell.y) as usize][(cell.x - min.x) as usize] = Some(state);
+        }
+
+        writeln!(text, "#P {} {}", min.x, max.y).unwrap();
+        for ant in &self.ants {
+            write_ant(&mut text, ant);
+        }
+        if !self.visits.is_empty() {
+            let visits: HashMap<IVec2, u32> = self.visits.iter().copied().collect();
+            let counts = rows.iter().enumerate().flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, state)| state.is_some())
+                    .map(move |(x, _)| IVec2::new(min.x + x as i32, max.y - y as i32))
+            });
+            write_visits(
+                &mut text,
+                counts.map(|cell| visits.get(&cell).copied().unwrap_or_default()),
+            );
+        }
+        writeln!(text, "x = {}, y = {}, rule = {}", size.x, size.y, self.rule).unwrap();
+
+        let mut runs = Runs::default();
+        for (index, row) in rows.iter().enumerate() {
+            if index > 0 {
+                runs.push("$".to_owned());
+            }
+            // Trailing unpainted cells are implied by the end of the row
+            let len = row
+                .iter()
+                .rposition(Option::is_some)
+                .map_or(0, |last| last + 1);
+            for &cell in &row[..len] {
+                runs.push(match cell {
+                    Some(state) => state_tag(state),
+                    None => ".".to_owned(),
+                });
+            }
+        }
+        runs.push("!".to_owned());
+        text.push_str(&runs.finish());
+        Ok(text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut top_left = IVec2::ZERO;
+        let mut ants = Vec::new();
+        let mut counts = Vec::new();
+        let mut header = None;
+        let mut body = String::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            let context = || format!("line {}", number + 1);
+            if header.is_some() {
+                body.push_str(line);
+            } else if let Some(position) = line.strip_prefix("#P") {
+                top_left = parse_cell(&mut position.split_whitespace()).with_context(context)?;
+            } else if let Some(ant) = line.strip_prefix("#A") {
+                ants.push(parse_ant(ant).with_context(context)?);
+            } else if let Some(visits) = line.strip_prefix("#V") {
+                for count in visits.split_whitespace() {
+                    counts.push(
+                        count
+                            .parse::<u32>()
+                            .with_context(|| format!("invalid visit count `{count}`"))
+                            .with_context(context)?,
+                    );
+                }
+            } else if line.starts_with('#') || line.is_empty() {
+                continue;
+            } else {
+                header = Some(parse_header(line).with_context(context)?);
+            }
+        }
+        let Some((size, rule)) = header else {
+            bail!("missing the `x = .., y = .., rule = ..` header");
+        };
+
+        let mut cells = Vec::new();
+        let mut count = String::new();
+        let (mut x, mut y) = (0, 0);
+        let mut chars = body.chars();
+        while let Some(c) = chars.next() {
+            let tag_state = match c {
+                '0'..='9' => {
+                    count.push(c);
+                    continue;
+                }
+                '!' => break,
+                '.' | 'b' => None,
+                'o' => Some(0),
+                'A'..='X' => Some(c as usize - 'A' as usize),
+                'p'..='y' => {
+                    let suffix = chars
+                        .next()
+                        .filter(|suffix| suffix.is_ascii_uppercase() && *suffix <= 'X')
+                        .ok_or_else(|| anyhow!("`{c}` must be followed by `A` to `X`"))?;
+                    let state = 24 * (c as usize - 'o' as usize) + suffix as usize - 'A' as usize;
+                    if state >= MAX_RLE_STATES {
+                        bail!("`{c}{suffix}` is past the {MAX_RLE_STATES} states RLE can hold");
+                    }
+                    Some(state)
+                }
+                '$' => {
+                    y += parse_count(&count)?;
+                    x = 0;
+                    count.clear();
+                    continue;
+                }
+                _ => bail!("unexpected `{c}` in the cells"),
+            };
+
+            let run = parse_count(&count)?;
+            count.clear();
+            if y >= size.y || x + run > size.x {
+                bail!("cells past the {}x{} bounding box", size.x, size.y);
+            }
+            if let Some(state) = tag_state {
+                cells.extend((x..x + run).map(|x| (top_left + IVec2::new(x, -y), state)));
+            }
+            x += run;
+        }
+
+        // Cells are read in the order their counts were written
+        let visits = if counts.is_empty() {
+            Vec::new()
+        } else if counts.len() == cells.len() {
+            cells.iter().map(|(cell, _)| *cell).zip(counts).collect()
+        } else {
+            bail!(
+                "{} visit counts for {} painted cells",
+                counts.len(),
+                cells.len()
+            );
+        };
+
+        Ok(Self {
+            rule,
+            cells,
+            ants,
+            visits,
+        })
+    }
+}
+
+/// Smallest and largest corner of `cells`
+fn bounds(cells: &[(IVec2, usize)]) -> Option<(IVec2, IVec2)> {
+    let mut corners = cells.iter().map(|(cell, _)| (*cell, *cell));
+    let first = corners.next()?;
+    Some(corners.fold(first, |(min, max), (cell, _)| {
+        (min.min(cell), max.max(cell))
+    }))
+}
+
+fn write_ant(text: &mut String, ant: &SavedAnt) {
+    write!(text, "#A {} {} {}", ant.cell.x, ant.cell.y, ant.heading).unwrap();
+    if ant.mirrored {
+        text.push_str(" mirrored");
+    }
+    if ant.inverse {
+        text.push_str(" inverse");
+    }
+    text.push('\n');
+}
+
+/// Writes `counts` on `#V` lines no wider than `LINE_WIDTH`
+fn write_visits(text: &mut String, counts: impl Iterator<Item = u32>) {
+    let mut line = String::from("#V");
+    for count in counts {
+        let count = count.to_string();
+        if line.len() + 1 + count.len() > LINE_WIDTH {
+            text.push_str(&line);
+            text.push('\n');
+            line = String::from("#V");
+        }
+        line.push(' ');
+        line.push_str(&count);
+    }
+    text.push_str(&line);
+    text.push('\n');
+}
+
+/// Multi-state tag of `state`, which must be below `MAX_RLE_STATES`
+fn state_tag(state: usize) -> String {
+    let letter = |index: usize| char::from(b'A' + (index % 24) as u8);
+    match state {
+        0..=23 => letter(state).to_string(),
+        _ => format!("{}{}", char::from(b'o' + (state / 24) as u8), letter(state)),
+    }
+}
+
+/// Collects tags into counted runs and wraps them into lines
+#[derive(Default)]
+struct Runs {
+    text: String,
+    line: usize,
+    last: Option<(String, usize)>,
+}
+
+impl Runs {
+    fn push(&mut self, tag: String) {
+        match &mut self.last {
+            Some((last, count)) if *last == tag => *count += 1,
+            _ => {
+                self.flush();
+                self.last = Some((tag, 1));
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        let Some((tag, count)) = self.last.take() else {
+            return;
+        };
+        let run = if count > 1 {
+            format!("{count}{tag}")
+        } else {
+            tag
+        };
+        if self.line + run.len() > LINE_WIDTH {
+            self.text.push('\n');
+            self.line = 0;
+        }
+        self.line += run.len();
+        self.text.push_str(&run);
+    }
+
+    fn finish(mut self) -> String {
+        self.flush();
+        self.text.push('\n');
+        self.text
+    }
+}
+
+fn parse_count(count: &str) -> Result<i32> {
+    if count.is_empty() {
+        return Ok(1);
+    }
+    count
+        .parse()
+        .ok()
+        .filter(|count| *count > 0)
+        .ok_or_else(|| anyhow!("invalid count `{count}`"))
+}
+
+fn parse_cell<'a>(words: &mut impl Iterator<Item = &'a str>) -> Result<IVec2> {
+    let mut coordinate = || -> Result<i32> {
+        let word = words
+            .next()
+            .ok_or_else(|| anyhow!("missing a coordinate"))?;
+        word.parse()
+            .with_context(|| format!("invalid coordinate `{word}`"))
+    };
+    Ok(IVec2::new(coordinate()?, coordinate()?))
+}
+
+fn parse_ant(line: &str) -> Result<SavedAnt> {
+    let mut words = line.split_whitespace();
+    let cell = parse_cell(&mut words)?;
+    let heading = words.next().ok_or_else(|| anyhow!("missing the heading"))?;
+    let mut ant = SavedAnt {
+        cell,
+        heading: heading.to_owned(),
+        mirrored: false,
+        inverse: false,
+    };
+    for flag in words {
+        match flag {
+            "mirrored" => ant.mirrored = true,
+            "inverse" => ant.inverse = true,
+            _ => bail!("unknown ant flag `{flag}`"),
+        }
+    }
+    Ok(ant)
+}
+
+/// Bounding box size and rule from `x = .., y = .., rule = ..`
+fn parse_header(line: &str) -> Result<(IVec2, String)> {
+    let (mut width, mut height, mut rule) = (None, None, None);
+    for field in line.split(',') {
+        let (name, value) = field
+            .split_once('=')
+            .ok_or_else(|| anyhow!("expected `name = value`, found `{}`", field.trim()))?;
+        let value = value.trim();
+        let size = || {
+            value
+                .parse::<i32>()
+                .ok()
+                .filter(|size| *size >= 0)
+                .ok_or_else(|| anyhow!("invalid size `{value}`"))
+        };
+        match name.trim() {
+            "x" => width = Some(size()?),
+            "y" => height = Some(size()?),
+            "rule" => rule = Some(value.to_owned()),
+            name => bail!("unknown header field `{name}`"),
+        }
+    }
+    match (width, height, rule) {
+        (Some(width), Some(height), Some(rule)) => Ok((IVec2::new(width, height), rule)),
+        (_, _, None) => bail!("the header has no rule, the pattern to run"),
+        _ => bail!("the header needs both `x` and `y`"),
+    }
+}
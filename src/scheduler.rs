@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use bevy::{
+    app::{FixedMain, MainScheduleOrder, RunFixedMainLoop},
+    ecs::schedule::ScheduleLabel,
+    prelude::*,
+};
+
+use crate::{board::SyncTiles, Shortcuts};
+
+/// What runs the simulation ticks of `FixedUpdate` and the other fixed schedules
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Scheduler {
+    /// Bevy's own fixed main loop, between `PreUpdate` and `Update`
+    #[default]
+    Fixed,
+    /// An accumulator pumped by a system inside `Update`, for platforms where the fixed
+    /// main loop stutters with uneven frame times, such as browsers
+    Accumulator,
+}
+
+/// Runs the fixed schedules the way `scheduler` says. Both ways run one tick per
+/// `Time<Fixed>` timestep of virtual time, so they step the same over the same time and
+/// the rate controls work alike.
+pub struct SchedulerPlugin {
+    pub scheduler: Scheduler,
+}
+
+impl Plugin for SchedulerPlugin {
+    fn build(&self, app: &mut App) {
+        if self.scheduler == Scheduler::Fixed {
+            return;
+        }
+        // Without this, Bevy's loop would run every tick a second time
+        app.world_mut()
+            .resource_mut::<MainScheduleOrder>()
+            .labels
+            .retain(|label| *label != RunFixedMainLoop.intern());
+        app.init_resource::<TickAccumulator>()
+            .configure_sets(Update, SimulationTicks.before(Shortcuts).before(SyncTiles))
+            .add_systems(Update, pump_fixed_main.in_set(SimulationTicks));
+    }
+}
+
+/// The ticks run by `Scheduler::Accumulator`, ahead of the input and tile systems that
+/// would otherwise see them a frame late
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SimulationTicks;
+
+/// Virtual time not yet spent on ticks
+#[derive(Resource, Default)]
+pub struct TickAccumulator(pub Duration);
+
+/// Adds the frame's virtual time to the accumulator and runs `FixedMain` once for every
+/// whole timestep in it, with `Time` reading as `Time<Fixed>` meanwhile
+fn pump_fixed_main(world: &mut World) {
+    let delta = world.resource::<Time<Virtual>>().delta();
+    world.resource_mut::<TickAccumulator>().0 += delta;
+
+    let _ = world.try_schedule_scope(FixedMain, |world, schedule| loop {
+        // Read every time, a tick may change the rate
+        let timestep = world.resource::<Time<Fixed>>().timestep();
+        let Some(left) = world.resource::<TickAccumulator>().0.checked_sub(timestep) else {
+            break;
+        };
+        world.resource_mut::<TickAccumulator>().0 = left;
+        world.resource_mut::<Time<Fixed>>().advance_by(timestep);
+        *world.resource_mut::<Time>() = world.resource::<Time<Fixed>>().as_generic();
+        schedule.run(world);
+    });
+
+    *world.resource_mut::<Time>() = world.resource::<Time<Virtual>>().as_generic();
+}
@@ -0,0 +1,244 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    board::StepCounter,
+    keybindings::{Action, KeyBindings},
+    pattern::Pattern,
+    pattern_input::pattern_input_closed,
+    theme::foreground,
+    Shortcuts,
+};
+
+/// Name the shared command line starts with
+const PROGRAM: &str = "langtons-ant";
+
+/// How long the confirmation stays on screen, in seconds
+const TOAST_SECS: f32 = 1.5;
+
+/// Options the shared command line sets itself: long name, short name and whether a
+/// value follows. The pattern and its colors are written out in full, since a pattern
+/// typed at runtime or read from stdin can't be passed on any other way.
+const REPLACED: [(&str, Option<char>, bool); 7] = [
+    ("--pattern", Some('p'), true),
+    ("--preset", None, true),
+    ("--stdin", None, false),
+    ("--colors", Some('c'), true),
+    ("--seed", None, true),
+    ("--rate", Some('r'), true),
+    ("--max-steps", None, true),
+];
+
+/// `Action::CopyShareLink`, Ctrl+C by default, copies a command line reproducing the run up to the current step to the
+/// clipboard: the arguments the app was started with, and the current pattern, colors,
+/// seed and rate, with `--max-steps` pausing it where it was copied. Without the
+/// `clipboard` feature the line is printed to stdout instead.
+pub struct ShareRunPlugin {
+    /// Arguments the app was started with, without the program name
+    pub args: Vec<String>,
+    /// `--seed`, or the seed drawn in its place
+    pub seed: u64,
+}
+
+impl Plugin for ShareRunPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LaunchArgs {
+            args: self.args.clone(),
+            seed: self.seed,
+        })
+        .init_resource::<KeyBindings>()
+        .init_resource::<ClearColor>()
+        .add_systems(Startup, setup_toast)
+        .add_systems(
+            Update,
+            (
+                share_run.run_if(pattern_input_closed).in_set(Shortcuts),
+                hide_toast,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct LaunchArgs {
+    args: Vec<String>,
+    seed: u64,
+}
+
+#[derive(Component)]
+struct Toast {
+    /// Seconds left on screen
+    left: f32,
+}
+
+/// Command line running `pattern` from `seed` at `rate` and pausing after `steps`
+/// steps, with the other options of `args` kept as they were
+pub fn share_command(
+    args: &[String],
+    pattern: &Pattern,
+    seed: u64,
+    rate: f64,
+    steps: u64,
+) -> String {
+    let mut kept = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let replaced = REPLACED.iter().find_map(|&(long, short, takes_value)| {
+            let short = short.map(|short| format!("-{short}"));
+            if *arg == long || short.as_ref() == Some(arg) {
+                Some(takes_value)
+            } else if arg.starts_with(&format!("{long}="))
+                || short.is_some_and(|short| arg.starts_with(&short))
+            {
+                // The value is attached, as in `--rate=120` or `-r120`
+                Some(false)
+            } else {
+                None
+            }
+        });
+        match replaced {
+            Some(true) => {
+                args.next();
+            }
+            Some(false) => {}
+            None => kept.push(arg.clone()),
+        }
+    }
+
+    let colors: Vec<String> = pattern
+        .colors
+        .iter()
+        .map(|color| color.to_srgba().to_hex())
+        .collect();
+    // Rounded, the timestep holds the rate to the nanosecond only
+    let rate = (rate * 1000.).round() / 1000.;
+    let words = [PROGRAM.to_owned()].into_iter().chain(kept).chain([
+        "--pattern".to_owned(),
+        pattern.to_string(),
+        "--colors".to_owned(),
+        colors.join(","),
+        "--seed".to_owned(),
+        seed.to_string(),
+        "--rate".to_owned(),
+        rate.to_string(),
+        "--max-steps".to_owned(),
+        steps.to_string(),
+    ]);
+    words
+        .map(|word| quoted(&word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `word` as a POSIX shell reads it back, single quoted unless it is plain
+fn quoted(word: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "-_./:=+,@%".contains(c);
+    if !word.is_empty() && word.chars().all(plain) {
+        word.to_owned()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+fn setup_toast(mut commands: Commands, background: Res<ClearColor>) {
+    commands.spawn((
+        TextBundle {
+            visibility: Visibility::Hidden,
+            ..TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 20.,
+                    color: foreground(background.0),
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.),
+                left: Val::Percent(50.),
+                ..default()
+            })
+        },
+        Toast { left: 0. },
+    ));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn share_run(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    launch: Res<LaunchArgs>,
+    pattern: Res<Pattern>,
+    step_counter: Res<StepCounter>,
+    fixed_time: Res<Time<Fixed>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut clipboard: Local<Clipboard>,
+    mut toast_query: Query<(&mut Text, &mut Visibility, &mut Toast)>,
+) {
+    if !bindings.just_pressed(Action::CopyShareLink, &keys) {
+        return;
+    }
+    if !window_query.iter().any(|window| window.focused) {
+        return;
+    }
+
+    let rate = 1. / fixed_time.timestep().as_secs_f64();
+    let command = share_command(&launch.args, &pattern, launch.seed, rate, step_counter.0);
+    let message = match clipboard.copy(&command) {
+        Ok(message) => message,
+        Err(err) => {
+            warn!("failed to copy the command line: {err}");
+            "Copy failed"
+        }
+    };
+    for (mut text, mut visibility, mut toast) in toast_query.iter_mut() {
+        text.sections[0].value = message.to_owned();
+        *visibility = Visibility::Visible;
+        toast.left = TOAST_SECS;
+    }
+}
+
+fn hide_toast(time: Res<Time>, mut toast_query: Query<(&mut Visibility, &mut Toast)>) {
+    for (mut visibility, mut toast) in toast_query.iter_mut() {
+        if toast.left <= 0. {
+            continue;
+        }
+        toast.left -= time.delta_seconds();
+        if toast.left <= 0. {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+/// The system clipboard, opened on first use and kept open, as X11 only serves the copied
+/// text while its owner is alive
+#[cfg(feature = "clipboard")]
+#[derive(Default)]
+struct Clipboard(Option<arboard::Clipboard>);
+
+#[cfg(feature = "clipboard")]
+impl Clipboard {
+    /// Copies `text`, returning the confirmation to show
+    fn copy(&mut self, text: &str) -> Result<&'static str, arboard::Error> {
+        let clipboard = match &mut self.0 {
+            Some(clipboard) => clipboard,
+            None => self.0.insert(arboard::Clipboard::new()?),
+        };
+        clipboard.set_text(text)?;
+        Ok("Copied")
+    }
+}
+
+/// Stands in for the system clipboard without the `clipboard` feature
+#[cfg(not(feature = "clipboard"))]
+#[derive(Default)]
+struct Clipboard;
+
+#[cfg(not(feature = "clipboard"))]
+impl Clipboard {
+    /// Prints `text`, returning the confirmation to show
+    fn copy(&mut self, text: &str) -> Result<&'static str, std::convert::Infallible> {
+        println!("{text}");
+        Ok("Printed to the terminal")
+    }
+}
@@ -0,0 +1,583 @@
+use bevy::{
+    math::{IRect, IVec2, UVec2},
+    utils::HashMap,
+};
+
+use crate::{
+    lattice::Lattice,
+    pattern::{Pattern, Turn},
+};
+
+/// What happens when the ant tries to step over the world border
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum EdgeMode {
+    /// The ant stays on its edge cell and keeps applying the rule there
+    Stop,
+    /// The ant stays on its edge cell and turns around
+    Bounce,
+}
+
+/// What happens when two ants run into each other
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Collision {
+    /// Ants walk through each other and may share cells
+    #[default]
+    Pass,
+    /// An ant about to enter a cell another ant stands on turns around instead. Ants move
+    /// one after the other in slice order, so of two ants heading for the same cell the
+    /// later one turns, while two ants walking into each other both turn.
+    Block,
+    /// Ants that swap cells or land on the same cell are removed
+    Annihilate,
+}
+
+/// Finite world of `width`×`height` cells centered on the origin, in lattice coordinates
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Border {
+    pub min: IVec2,
+    pub max: IVec2,
+    pub mode: EdgeMode,
+}
+
+impl Border {
+    pub fn new(size: UVec2, mode: EdgeMode) -> Self {
+        let size = size.as_ivec2();
+        let min = -size / 2;
+        Self {
+            min,
+            max: min + size - IVec2::ONE,
+            mode,
+        }
+    }
+
+    pub fn contains(&self, cell: IVec2) -> bool {
+        cell.cmpge(self.min).all() && cell.cmple(self.max).all()
+    }
+}
+
+/// Side of the square chunks used by the chunked grid backing, in cells
+pub const CHUNK_SIZE: i32 = 64;
+
+const CHUNK_AREA: usize = (CHUNK_SIZE * CHUNK_SIZE) as usize;
+
+/// Largest pattern the chunked backing can hold, one packed byte per cell
+pub const MAX_CHUNKED_STATES: usize = u8::MAX as usize;
+
+/// Packed states of a `CHUNK_SIZE`×`CHUNK_SIZE` block, `0` marks an unvisited cell
+#[derive(Clone, Debug)]
+struct Chunk {
+    states: Box<[u8; CHUNK_AREA]>,
+}
+
+impl Default for Chunk {
+    fn default() -> Self {
+        Self {
+            states: Box::new([0; CHUNK_AREA]),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Storage {
+    /// One map entry per painted cell
+    Flat(HashMap<IVec2, usize>),
+    /// Painted cells packed into fixed-size chunks.
+    ///
+    /// After 1M steps `RL` has painted 210k cells along its highway: the flat map takes
+    /// ~3.7 MiB and the 598 chunks ~2.3 MiB. Compact patterns gain more, `RLR` (29k cells)
+    /// drops from ~950 KiB to ~70 KiB. Neighbouring cells also share cache lines instead of
+    /// being scattered across the table.
+    Chunked(HashMap<IVec2, Chunk>),
+}
+
+/// Times each cell was stepped on, see `count_visits`
+pub type VisitCounts = HashMap<IVec2, u32>;
+
+/// Painted cells and their state index; unvisited cells are not stored and step like
+/// state 0
+#[derive(Clone, Debug)]
+pub struct GridState {
+    storage: Storage,
+    border: Option<Border>,
+    painted: usize,
+    bounds: Option<IRect>,
+}
+
+impl Default for GridState {
+    fn default() -> Self {
+        Self {
+            storage: Storage::Flat(HashMap::default()),
+            border: None,
+            painted: 0,
+            bounds: None,
+        }
+    }
+}
+
+/// Chunk holding `cell` and the cell's index inside it
+pub fn chunk_of(cell: IVec2) -> (IVec2, usize) {
+    let chunk = cell.div_euclid(IVec2::splat(CHUNK_SIZE));
+    let local = cell.rem_euclid(IVec2::splat(CHUNK_SIZE));
+    (chunk, (local.y * CHUNK_SIZE + local.x) as usize)
+}
+
+impl GridState {
+    /// Grid backed by packed chunks, which only supports patterns of up to `MAX_CHUNKED_STATES` states
+    pub fn chunked() -> Self {
+        Self {
+            storage: Storage::Chunked(HashMap::default()),
+            border: None,
+            painted: 0,
+            bounds: None,
+        }
+    }
+
+    /// Confines the ants to `border` instead of an unbounded plane
+    pub fn with_border(mut self, border: Border) -> Self {
+        self.border = Some(border);
+        self
+    }
+
+    pub fn border(&self) -> Option<Border> {
+        self.border
+    }
+
+    /// Most states a pattern may have to be stored here, `None` if there is no limit
+    pub fn max_states(&self) -> Option<usize> {
+        match self.storage {
+            Storage::Flat(_) => None,
+            Storage::Chunked(_) => Some(MAX_CHUNKED_STATES),
+        }
+    }
+
+    pub fn get(&self, cell: IVec2) -> Option<usize> {
+        match &self.storage {
+            Storage::Flat(cells) => cells.get(&cell).copied(),
+            Storage::Chunked(chunks) => {
+                let (chunk, index) = chunk_of(cell);
+                match chunks.get(&chunk)?.states[index] {
+                    0 => None,
+                    state => Some(state as usize - 1),
+                }
+            }
+        }
+    }
+
+    /// Paints `cell` with `state`, as a step would
+    pub fn set(&mut self, cell: IVec2, state: usize) {
+        let newly_painted = match &mut self.storage {
+            Storage::Flat(cells) => cells.insert(cell, state).is_none(),
+            Storage::Chunked(chunks) => {
+                let (chunk, index) = chunk_of(cell);
+                let packed = &mut chunks.entry(chunk).or_default().states[index];
+                let newly_painted = *packed == 0;
+                *packed = state as u8 + 1;
+                newly_painted
+            }
+        };
+
+        if newly_painted {
+            self.painted += 1;
+            self.bounds = Some(match self.bounds {
+                Some(bounds) => bounds.union_point(cell),
+                None => IRect::from_corners(cell, cell),
+            });
+        }
+    }
+
+    /// Number of painted cells
+    pub fn len(&self) -> usize {
+        self.painted
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.painted == 0
+    }
+
+    /// Smallest rectangle holding every painted cell, `max` included
+    pub fn bounds(&self) -> Option<IRect> {
+        self.bounds
+    }
+
+    /// Every painted cell with its state index, in no particular order
+    pub fn cells(&self) -> Box<dyn Iterator<Item = (IVec2, usize)> + '_> {
+        match &self.storage {
+            Storage::Flat(cells) => Box::new(cells.iter().map(|(cell, state)| (*cell, *state))),
+            Storage::Chunked(chunks) => Box::new(
+                chunks
+                    .iter()
+                    .flat_map(|(chunk, c)| Self::packed_cells(*chunk, c)),
+            ),
+        }
+    }
+
+    fn packed_cells(chunk: IVec2, c: &Chunk) -> impl Iterator<Item = (IVec2, usize)> + '_ {
+        let origin = chunk * CHUNK_SIZE;
+        c.states
+            .iter()
+            .enumerate()
+            .filter(|(_, state)| **state != 0)
+            .map(move |(index, state)| {
+                let local = IVec2::new(index as i32 % CHUNK_SIZE, index as i32 / CHUNK_SIZE);
+                (origin + local, *state as usize - 1)
+            })
+    }
+
+    /// Painted cells of one chunk with their states
+    pub fn chunk_cells(&self, chunk: IVec2) -> Vec<(IVec2, usize)> {
+        match &self.storage {
+            Storage::Flat(cells) => cells
+                .iter()
+                .filter(|(cell, _)| chunk_of(**cell).0 == chunk)
+                .map(|(cell, state)| (*cell, *state))
+                .collect(),
+            Storage::Chunked(chunks) => chunks
+                .get(&chunk)
+                .map(|c| Self::packed_cells(chunk, c).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// FNV-1a hash of the painted cells and their states in cell order, the same for equal
+    /// grids on every machine and with either storage
+    pub fn content_hash(&self) -> u64 {
+        const PRIME: u64 = 0x0100_0000_01b3;
+
+        let mut cells: Vec<(IVec2, usize)> = self.cells().collect();
+        cells.sort_unstable_by_key(|(cell, _)| (cell.y, cell.x));
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for (cell, state) in cells {
+            let bytes = [
+                cell.x.to_le_bytes(),
+                cell.y.to_le_bytes(),
+                (state as u32).to_le_bytes(),
+            ];
+            for byte in bytes.into_iter().flatten() {
+                hash = (hash ^ byte as u64).wrapping_mul(PRIME);
+            }
+        }
+        hash
+    }
+
+    /// Forgets `cells`, as if they had never been painted
+    pub fn remove_cells(&mut self, cells: &[IVec2]) {
+        for cell in cells {
+            let removed = match &mut self.storage {
+                Storage::Flat(cells) => cells.remove(cell).is_some(),
+                Storage::Chunked(chunks) => {
+                    let (chunk, index) = chunk_of(*cell);
+                    chunks
+                        .get_mut(&chunk)
+                        .is_some_and(|chunk| std::mem::take(&mut chunk.states[index]) != 0)
+                }
+            };
+            if removed {
+                self.painted -= 1;
+            }
+        }
+
+        // The bounds can only shrink, finding by how much takes a pass over every cell
+        self.bounds = self
+            .cells()
+            .map(|(cell, _)| IRect::from_corners(cell, cell))
+            .reduce(|bounds, cell| bounds.union(cell));
+    }
+
+    pub fn clear(&mut self) {
+        match &mut self.storage {
+            Storage::Flat(cells) => cells.clear(),
+            Storage::Chunked(chunks) => chunks.clear(),
+        }
+        self.painted = 0;
+        self.bounds = None;
+    }
+}
+
+pub struct AntState<L: Lattice> {
+    pub cell: IVec2,
+    pub heading: L::Heading,
+    /// Swaps every `L` and `R`, so the ant walks the mirror image of an unmirrored one
+    pub mirrored: bool,
+    /// Steps the pattern backwards with `L` and `R` swapped, unvisited cells count as
+    /// state 0, so it erodes what a forward ant builds
+    pub inverse: bool,
+}
+
+// Derives would require the lattice marker type itself to implement these traits
+impl<L: Lattice> Clone for AntState<L> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<L: Lattice> Copy for AntState<L> {}
+
+impl<L: Lattice> PartialEq for AntState<L> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cell == other.cell
+            && self.heading == other.heading
+            && self.mirrored == other.mirrored
+            && self.inverse == other.inverse
+    }
+}
+
+impl<L: Lattice> Eq for AntState<L> {}
+
+impl<L: Lattice> std::fmt::Debug for AntState<L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AntState")
+            .field("cell", &self.cell)
+            .field("heading", &self.heading)
+            .field("mirrored", &self.mirrored)
+            .field("inverse", &self.inverse)
+            .finish()
+    }
+}
+
+impl<L: Lattice> Default for AntState<L> {
+    fn default() -> Self {
+        Self {
+            cell: IVec2::ZERO,
+            heading: L::INITIAL_HEADING,
+            mirrored: false,
+            inverse: false,
+        }
+    }
+}
+
+impl<L: Lattice> AntState<L> {
+    /// Mirror image across the world x axis
+    pub fn reflected(self) -> Self {
+        let (cell, heading) = L::reflect(self.cell, self.heading);
+        Self {
+            cell,
+            heading,
+            mirrored: !self.mirrored,
+            ..self
+        }
+    }
+
+    /// Image under a half turn around the origin
+    pub fn half_turned(self) -> Self {
+        let (cell, heading) = L::half_turn(self.cell, self.heading);
+        Self {
+            cell,
+            heading,
+            ..self
+        }
+    }
+
+    /// This ant and its mirror images across the x axis, the y axis and the origin
+    pub fn mirror_images(self) -> [Self; 4] {
+        [
+            self,
+            self.reflected(),
+            self.reflected().half_turned(),
+            self.half_turned(),
+        ]
+    }
+}
+
+/// What a single step changed, so renderers can update only the touched cell
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StepDelta {
+    /// Cell the ant stood on and repainted
+    pub cell: IVec2,
+    /// State of the cell before the step, `None` if it was unvisited
+    pub old_state: Option<usize>,
+    /// State of the cell after the step, meaningless for `Turn::Identity`, which leaves
+    /// the cell untouched
+    pub new_state: usize,
+    pub turn: Turn,
+    /// Cells the ant moves on after turning, see `Pattern::distances`
+    pub distance: u8,
+}
+
+/// Works out what `ant` would do on `grid` without touching either
+fn resolve<L: Lattice>(grid: &GridState, ant: &AntState<L>, pattern: &Pattern) -> StepDelta {
+    let old_state = grid.get(ant.cell);
+    // An unvisited cell is in state 0, it only isn't stored
+    let state = old_state.unwrap_or(0);
+    let (new_state, turn, distance) = if ant.inverse {
+        // Backwards, the move out of the earlier state is undone
+        let (new_state, turn) = pattern.previous(state);
+        (new_state, turn.mirrored(), pattern.distances[new_state])
+    } else {
+        let (new_state, turn) = pattern.next(state);
+        (new_state, turn, pattern.distances[state])
+    };
+    let turn = if ant.mirrored { turn.mirrored() } else { turn };
+
+    StepDelta {
+        cell: ant.cell,
+        old_state,
+        new_state,
+        turn,
+        distance,
+    }
+}
+
+/// Writes the new state of the cell `delta` stepped on, unless it is inert
+fn paint(grid: &mut GridState, delta: &StepDelta) {
+    if delta.turn != Turn::Identity {
+        grid.set(delta.cell, delta.new_state);
+    }
+}
+
+/// Turns `ant` and moves it `distance` cells on, jumping over the cells in between. The
+/// border stops the move at the last cell inside it.
+fn advance<L: Lattice>(grid: &GridState, ant: &mut AntState<L>, turn: Turn, distance: u8) {
+    ant.heading = L::turn(ant.heading, turn);
+    for _ in 0..distance {
+        let next = L::neighbor(ant.cell, ant.heading);
+        match grid.border {
+            Some(border) if !border.contains(next) => {
+                ant.heading = match border.mode {
+                    EdgeMode::Stop => L::stay(ant.heading),
+                    EdgeMode::Bounce => L::reverse(ant.heading),
+                };
+                break;
+            }
+            _ => ant.cell = next,
+        }
+    }
+}
+
+/// Applies the pattern rule once: repaint the current cell, turn and move forward. An
+/// inert cell is neither repainted nor turned on.
+pub fn step<L: Lattice>(
+    grid: &mut GridState,
+    ant: &mut AntState<L>,
+    pattern: &Pattern,
+) -> StepDelta {
+    let delta = resolve(grid, ant, pattern);
+    paint(grid, &delta);
+    advance(grid, ant, delta.turn, delta.distance);
+
+    delta
+}
+
+/// Runs `steps` steps of a single ant
+pub fn simulate<L: Lattice>(
+    grid: &mut GridState,
+    ant: &mut AntState<L>,
+    pattern: &Pattern,
+    steps: u64,
+) {
+    for _ in 0..steps {
+        step(grid, ant, pattern);
+    }
+}
+
+/// Counts a visit to the cell of every delta
+pub fn count_visits(visits: &mut VisitCounts, deltas: &[StepDelta]) {
+    for delta in deltas {
+        let count = visits.entry(delta.cell).or_default();
+        *count = count.saturating_add(1);
+    }
+}
+
+/// `simulate_all` that also counts the visits of every step into `visits`
+pub fn simulate_all_counting<L: Lattice>(
+    grid: &mut GridState,
+    ants: &mut Vec<AntState<L>>,
+    pattern: &Pattern,
+    collision: Collision,
+    steps: u64,
+    visits: &mut VisitCounts,
+) {
+    for _ in 0..steps {
+        let (deltas, annihilated) = step_colliding(grid, ants, pattern, collision);
+        count_visits(visits, &deltas);
+        for index in annihilated.into_iter().rev() {
+            ants.remove(index);
+        }
+    }
+}
+
+/// Runs `steps` steps of every ant under `collision`, dropping annihilated ants
+pub fn simulate_all<L: Lattice>(
+    grid: &mut GridState,
+    ants: &mut Vec<AntState<L>>,
+    pattern: &Pattern,
+    collision: Collision,
+    steps: u64,
+) {
+    for _ in 0..steps {
+        let (_, annihilated) = step_colliding(grid, ants, pattern, collision);
+        for index in annihilated.into_iter().rev() {
+            ants.remove(index);
+        }
+    }
+}
+
+/// Steps every ant once. All moves are resolved against the grid as it was before
+/// the step, then writes are applied in slice order, so ants sharing a cell read the
+/// same state and the cell changes only once.
+pub fn step_all<L: Lattice>(
+    grid: &mut GridState,
+    ants: &mut [AntState<L>],
+    pattern: &Pattern,
+) -> Vec<StepDelta> {
+    if let [ant] = ants {
+        return vec![step(grid, ant, pattern)];
+    }
+
+    let deltas: Vec<StepDelta> = ants.iter().map(|ant| resolve(grid, ant, pattern)).collect();
+
+    for (ant, delta) in ants.iter_mut().zip(deltas.iter()) {
+        paint(grid, delta);
+        advance(grid, ant, delta.turn, delta.distance);
+    }
+
+    deltas
+}
+
+/// Steps every ant once like `step_all`, then applies `collision`. Returns the deltas of
+/// every ant, annihilated ones included, and the indices of the annihilated ants, which
+/// the caller removes.
+pub fn step_colliding<L: Lattice>(
+    grid: &mut GridState,
+    ants: &mut [AntState<L>],
+    pattern: &Pattern,
+    collision: Collision,
+) -> (Vec<StepDelta>, Vec<usize>) {
+    if collision == Collision::Pass || ants.len() < 2 {
+        return (step_all(grid, ants, pattern), Vec::new());
+    }
+
+    let deltas: Vec<StepDelta> = ants.iter().map(|ant| resolve(grid, ant, pattern)).collect();
+    let starts: Vec<IVec2> = ants.iter().map(|ant| ant.cell).collect();
+
+    for (index, delta) in deltas.iter().enumerate() {
+        paint(grid, delta);
+
+        let mut moved = ants[index];
+        advance(grid, &mut moved, delta.turn, delta.distance);
+        let occupied = ants
+            .iter()
+            .enumerate()
+            .any(|(other, ant)| other != index && ant.cell == moved.cell);
+        if collision == Collision::Block && occupied && moved.cell != starts[index] {
+            ants[index].heading = L::reverse(moved.heading);
+        } else {
+            ants[index] = moved;
+        }
+    }
+
+    let mut annihilated = Vec::new();
+    if collision == Collision::Annihilate {
+        for (index, ant) in ants.iter().enumerate() {
+            let collided = ants.iter().enumerate().any(|(other, other_ant)| {
+                other != index
+                    && (other_ant.cell == ant.cell
+                        || (other_ant.cell == starts[index] && ant.cell == starts[other]))
+            });
+            if collided {
+                annihilated.push(index);
+            }
+        }
+    }
+
+    (deltas, annihilated)
+}
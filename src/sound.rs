@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use bevy::{
+    audio::{Pitch, PitchBundle, Volume},
+    prelude::*,
+};
+
+use crate::{
+    board::{AntStepped, AutoPaused},
+    keybindings::{Action, KeyBindings},
+    pattern_input::pattern_input_closed,
+    Shortcuts,
+};
+
+/// Most clicks per second, at high step rates one per step would be a buzz
+const MAX_CLICKS_PER_SECOND: f32 = 30.;
+
+/// Clicks while the ants step and chimes when a stop condition pauses the run. Both are
+/// plain tones, so there are no sound files to ship. Needs `Assets<Pitch>`, which
+/// `AudioPlugin` provides.
+pub struct SoundPlugin {
+    /// Loudness from 0 to 1
+    pub volume: f32,
+}
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Sound {
+            volume: self.volume,
+            muted: false,
+            last_click: None,
+        })
+        .init_resource::<KeyBindings>()
+        .add_systems(Startup, setup)
+        .add_systems(
+            Update,
+            (
+                toggle_mute.run_if(pattern_input_closed).in_set(Shortcuts),
+                (play_clicks, play_chimes),
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct Sound {
+    pub volume: f32,
+    /// Silences both sounds, `Action::ToggleMute` flips it
+    pub muted: bool,
+    /// Elapsed seconds at the last click
+    last_click: Option<f32>,
+}
+
+#[derive(Resource)]
+struct Tones {
+    click: Handle<Pitch>,
+    chime: Handle<Pitch>,
+}
+
+fn setup(mut commands: Commands, mut pitches: ResMut<Assets<Pitch>>) {
+    commands.insert_resource(Tones {
+        click: pitches.add(Pitch::new(2000., Duration::from_millis(4))),
+        chime: pitches.add(Pitch::new(880., Duration::from_millis(300))),
+    });
+}
+
+fn toggle_mute(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut sound: ResMut<Sound>,
+) {
+    if bindings.just_pressed(Action::ToggleMute, &keys) {
+        sound.muted = !sound.muted;
+    }
+}
+
+fn play(commands: &mut Commands, tone: &Handle<Pitch>, volume: f32) {
+    commands.spawn(PitchBundle {
+        source: tone.clone(),
+        settings: PlaybackSettings::DESPAWN.with_volume(Volume::new(volume)),
+    });
+}
+
+fn play_clicks(
+    mut commands: Commands,
+    mut stepped: EventReader<AntStepped>,
+    mut sound: ResMut<Sound>,
+    tones: Res<Tones>,
+    time: Res<Time<Real>>,
+) {
+    // Several ticks can land in one frame, they still make a single click
+    if stepped.read().count() == 0 || sound.muted {
+        return;
+    }
+    let now = time.elapsed_seconds();
+    if sound
+        .last_click
+        .is_some_and(|last| now - last < 1. / MAX_CLICKS_PER_SECOND)
+    {
+        return;
+    }
+    sound.last_click = Some(now);
+    play(&mut commands, &tones.click, sound.volume);
+}
+
+fn play_chimes(
+    mut commands: Commands,
+    mut auto_paused: EventReader<AutoPaused>,
+    sound: Res<Sound>,
+    tones: Res<Tones>,
+) {
+    if auto_paused.read().count() > 0 && !sound.muted {
+        play(&mut commands, &tones.chime, sound.volume);
+    }
+}
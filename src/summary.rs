@@ -0,0 +1,170 @@
+use std::{fmt, marker::PhantomData};
+
+use bevy::prelude::*;
+
+use crate::{
+    board::{Ant, AntId, Board, CollisionRule, StepCounter},
+    lattice::Lattice,
+    pattern::Pattern,
+    sim::{simulate_all, AntState, Collision, GridState},
+};
+
+/// Longest highway period looked for, `RL` repeats its highway every 104 steps
+pub const MAX_HIGHWAY_PERIOD: usize = 1024;
+
+/// Steps run ahead on a copy of the board, enough to see the longest period repeat twice
+const LOOKAHEAD: usize = 3 * MAX_HIGHWAY_PERIOD;
+
+/// The first ant retraces its moves every `period` steps, `shift` cells further along
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Highway {
+    pub period: usize,
+    pub shift: IVec2,
+}
+
+/// Whether the first of `ants` is building a highway, found by running `LOOKAHEAD` more
+/// steps on a copy of `grid`. None if there are no ants or one of them is annihilated
+/// meanwhile.
+pub fn find_highway<L: Lattice>(
+    grid: &GridState,
+    ants: &[AntState<L>],
+    pattern: &Pattern,
+    collision: Collision,
+) -> Option<Highway> {
+    if ants.is_empty() {
+        return None;
+    }
+    let mut grid = grid.clone();
+    let mut ants = ants.to_vec();
+    let count = ants.len();
+    let mut path = Vec::with_capacity(LOOKAHEAD);
+    for _ in 0..LOOKAHEAD {
+        simulate_all(&mut grid, &mut ants, pattern, collision, 1);
+        if ants.len() != count {
+            return None;
+        }
+        path.push((ants[0].cell, ants[0].heading));
+    }
+
+    (1..=MAX_HIGHWAY_PERIOD).find_map(|period| {
+        let shift = path[period].0 - path[0].0;
+        let repeats = shift != IVec2::ZERO
+            && path[period..].iter().zip(&path).all(
+                |((cell, heading), (before, heading_before))| {
+                    *cell - *before == shift && heading == heading_before
+                },
+            );
+        repeats.then_some(Highway { period, shift })
+    })
+}
+
+/// What a run did, for the record printed when it ends
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunSummary {
+    pub pattern: String,
+    /// Seed of every random draw, `--seed` or the one drawn in its place
+    pub seed: u64,
+    pub steps: u64,
+    pub painted: usize,
+    pub bounds: Option<IRect>,
+    pub highway: Option<Highway>,
+}
+
+impl RunSummary {
+    pub fn new<L: Lattice>(
+        pattern: &Pattern,
+        seed: u64,
+        steps: u64,
+        grid: &GridState,
+        ants: &[AntState<L>],
+        collision: Collision,
+    ) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            seed,
+            steps,
+            painted: grid.len(),
+            bounds: grid.bounds(),
+            highway: find_highway(grid, ants, pattern, collision),
+        }
+    }
+}
+
+impl fmt::Display for RunSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "pattern:      {}", self.pattern)?;
+        writeln!(f, "seed:         {}", self.seed)?;
+        writeln!(f, "steps:        {}", self.steps)?;
+        writeln!(f, "painted:      {} cells", self.painted)?;
+        match self.bounds {
+            Some(bounds) => writeln!(
+                f,
+                "bounding box: {}x{} from {} to {}",
+                bounds.width() + 1,
+                bounds.height() + 1,
+                bounds.min,
+                bounds.max
+            )?,
+            None => writeln!(f, "bounding box: empty")?,
+        }
+        match self.highway {
+            Some(Highway { period, shift }) => {
+                write!(f, "highway:      period {period}, shifting by {shift}")
+            }
+            None => write!(f, "highway:      none"),
+        }
+    }
+}
+
+/// Prints a `RunSummary` to stdout when the app exits, whether the window was closed or
+/// a stop condition quit
+pub struct RunSummaryPlugin<L: Lattice> {
+    pub seed: u64,
+    lattice: PhantomData<L>,
+}
+
+impl<L: Lattice> RunSummaryPlugin<L> {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            lattice: PhantomData,
+        }
+    }
+}
+
+impl<L: Lattice> Plugin for RunSummaryPlugin<L> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SummarySeed(self.seed))
+            .add_systems(Last, print_summary::<L>.run_if(on_event::<AppExit>()));
+    }
+}
+
+#[derive(Resource)]
+struct SummarySeed(u64);
+
+fn print_summary<L: Lattice>(
+    seed: Res<SummarySeed>,
+    pattern: Res<Pattern>,
+    step_counter: Res<StepCounter>,
+    board: Res<Board>,
+    collision: Res<CollisionRule>,
+    ant_query: Query<(&Ant<L>, &AntId)>,
+    mut printed: Local<bool>,
+) {
+    // Several exit events can arrive in the same frame
+    if std::mem::replace(&mut *printed, true) {
+        return;
+    }
+    let mut ants: Vec<_> = ant_query.iter().collect();
+    ants.sort_by_key(|(_, id)| **id);
+    let ants: Vec<_> = ants.into_iter().map(|(ant, _)| ant.0).collect();
+    let summary = RunSummary::new(
+        &pattern,
+        seed.0,
+        step_counter.0,
+        &board.grid,
+        &ants,
+        collision.0,
+    );
+    println!("{summary}");
+}
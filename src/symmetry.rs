@@ -0,0 +1,109 @@
+use bevy::{math::I64Vec2, prelude::*};
+
+use crate::{
+    board::{Board, ResetBoard, StepCounter},
+    sim::GridState,
+};
+
+/// Fraction of painted cells whose image is painted with the same state, for the mirror
+/// images across the vertical and horizontal axes through the centroid of the painted
+/// cells and the half turn about it.
+///
+/// The images are taken in cell coordinates, which only match what is on screen on the
+/// square grid. An empty board counts as perfectly symmetric.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Symmetry {
+    /// Left-right mirror image
+    pub vertical: f64,
+    /// Top-bottom mirror image
+    pub horizontal: f64,
+    pub rotation: f64,
+}
+
+impl Symmetry {
+    /// Scores `grid` with one lookup per painted cell and symmetry
+    pub fn of(grid: &GridState) -> Self {
+        let painted = grid.len();
+        if painted == 0 {
+            return Self {
+                vertical: 1.,
+                horizontal: 1.,
+                rotation: 1.,
+            };
+        }
+
+        // Twice the centroid, rounded to the nearest cell or cell edge, so the images of
+        // whole cells are whole cells
+        let sum = grid
+            .cells()
+            .fold(I64Vec2::ZERO, |sum, (cell, _)| sum + cell.as_i64vec2());
+        let axes = (sum.as_dvec2() * 2. / painted as f64).round().as_ivec2();
+
+        let mut matches = [0usize; 3];
+        for (cell, state) in grid.cells() {
+            let images = [
+                IVec2::new(axes.x - cell.x, cell.y),
+                IVec2::new(cell.x, axes.y - cell.y),
+                axes - cell,
+            ];
+            for (image, matched) in images.into_iter().zip(&mut matches) {
+                if grid.get(image) == Some(state) {
+                    *matched += 1;
+                }
+            }
+        }
+        let [vertical, horizontal, rotation] =
+            matches.map(|matched| matched as f64 / painted as f64);
+        Self {
+            vertical,
+            horizontal,
+            rotation,
+        }
+    }
+}
+
+/// Scores the board's symmetry every `every` steps for the control panel. Scoring looks
+/// up every painted cell three times, so it is spread out by steps rather than done on
+/// every tick.
+pub struct SymmetryPlugin {
+    pub every: u64,
+}
+
+impl Plugin for SymmetryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SymmetryScore {
+            every: self.every,
+            latest: None,
+        })
+        .add_systems(FixedPostUpdate, score_symmetry)
+        .add_systems(Update, clear_symmetry);
+    }
+}
+
+#[derive(Resource)]
+pub struct SymmetryScore {
+    pub every: u64,
+    /// Step count and the score at that step, `None` until the first score
+    pub latest: Option<(u64, Symmetry)>,
+}
+
+fn clear_symmetry(mut reset_events: EventReader<ResetBoard>, mut score: ResMut<SymmetryScore>) {
+    if reset_events.read().count() > 0 {
+        score.latest = None;
+    }
+}
+
+fn score_symmetry(
+    mut score: ResMut<SymmetryScore>,
+    step_counter: Res<StepCounter>,
+    board: Res<Board>,
+) {
+    let steps = step_counter.0;
+    if score
+        .latest
+        .is_some_and(|(last, _)| steps < last + score.every)
+    {
+        return;
+    }
+    score.latest = Some((steps, Symmetry::of(&board.grid)));
+}
@@ -0,0 +1,77 @@
+use bevy::{prelude::*, render::texture::ImageSampler};
+
+use crate::palette::Palette;
+
+/// Named look of the board: a background, a palette and a texture filter that go
+/// together. The binary reads them into `ClearColor`, `Palette` and `TextureFilter`
+/// before building the app, so `--palette` still picks its own palette over the theme's.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Theme {
+    /// White background and random colors with sharp tiles
+    #[default]
+    Classic,
+    /// Near-black background and bright distinct hues, smoothed
+    Neon,
+    /// Off-white background and grays, smoothed
+    Paper,
+    /// Dark gray background and viridis with sharp tiles
+    Dark,
+}
+
+impl Theme {
+    /// Color the window is cleared to and tiles fade in from
+    pub fn background(self) -> Color {
+        match self {
+            Theme::Classic => Color::WHITE,
+            Theme::Neon => Color::srgb(0.02, 0.02, 0.05),
+            Theme::Paper => Color::srgb(0.96, 0.94, 0.88),
+            Theme::Dark => Color::srgb(0.1, 0.1, 0.12),
+        }
+    }
+
+    /// Palette used when the pattern, `--colors` and `--palette` leave it open
+    pub fn palette(self) -> Palette {
+        match self {
+            Theme::Classic => Palette::Random,
+            Theme::Neon => Palette::Distinct,
+            Theme::Paper => Palette::Grayscale,
+            Theme::Dark => Palette::Viridis,
+        }
+    }
+
+    pub fn filter(self) -> TextureFilter {
+        match self {
+            Theme::Classic | Theme::Dark => TextureFilter::Nearest,
+            Theme::Neon | Theme::Paper => TextureFilter::Linear,
+        }
+    }
+}
+
+/// Color of text and outlines drawn straight over `background`: black on light
+/// backgrounds and white on dark ones, so overlays stay readable with every theme
+pub fn foreground(background: Color) -> Color {
+    if Oklcha::from(background).lightness > 0.6 {
+        Color::BLACK
+    } else {
+        Color::WHITE
+    }
+}
+
+/// How chunk textures are sampled when a texel covers more than one screen pixel
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextureFilter {
+    /// Sharp texel edges, so a texel looks exactly like a tile
+    #[default]
+    Nearest,
+    /// Blended texels, softer when zoomed in
+    Linear,
+}
+
+impl TextureFilter {
+    pub fn sampler(self) -> ImageSampler {
+        match self {
+            TextureFilter::Nearest => ImageSampler::nearest(),
+            TextureFilter::Linear => ImageSampler::linear(),
+        }
+    }
+}
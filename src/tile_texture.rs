@@ -0,0 +1,162 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    board::{Board, ResetBoard, WorldOrigin},
+    heatmap::Heatmap,
+    pattern::Pattern,
+    sim::{chunk_of, CHUNK_SIZE},
+    theme::TextureFilter,
+    TILE_SIZE, TILE_Z,
+};
+
+/// Draws the square lattice as one texture per chunk, one texel per cell, instead of one
+/// mesh entity per tile.
+///
+/// The simulation only records which cells changed; texels are written once per frame and
+/// a million painted cells come down to a few hundred sprites. Only the square lattice maps
+/// cells onto texels.
+pub struct ChunkTexturePlugin;
+
+impl Plugin for ChunkTexturePlugin {
+    fn build(&self, app: &mut App) {
+        // After every Update system so a reset has already cleared the grid, and before
+        // transforms propagate so new chunk sprites are placed in the frame they appear
+        app.init_resource::<ChunkTextures>()
+            .init_resource::<TextureFilter>()
+            .add_systems(
+                PostUpdate,
+                (
+                    reset_textures,
+                    repaint_all.run_if(resource_changed::<Pattern>),
+                    paint_dirty_cells,
+                )
+                    .chain()
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Sprite drawn for each chunk and the cells whose texel is out of date
+#[derive(Resource, Default)]
+pub struct ChunkTextures {
+    pub dirty: HashSet<IVec2>,
+    chunks: HashMap<IVec2, (Entity, Handle<Image>)>,
+}
+
+fn spawn_chunk(
+    commands: &mut Commands,
+    images: &mut Assets<Image>,
+    origin: WorldOrigin,
+    filter: TextureFilter,
+    chunk: IVec2,
+) -> (Entity, Handle<Image>) {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: CHUNK_SIZE as u32,
+            height: CHUNK_SIZE as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0; 4],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = filter.sampler();
+    let texture = images.add(image);
+
+    // Cell centers sit on multiples of TILE_SIZE, the chunk spans its first to last cell
+    let center = (chunk * CHUNK_SIZE - origin.0).as_vec2() + (CHUNK_SIZE - 1) as f32 / 2.;
+    let entity = commands
+        .spawn(SpriteBundle {
+            texture: texture.clone(),
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(CHUNK_SIZE as f32 * TILE_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation((center * TILE_SIZE).extend(TILE_Z)),
+            ..default()
+        })
+        .id();
+    (entity, texture)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn paint_dirty_cells(
+    mut commands: Commands,
+    mut textures: ResMut<ChunkTextures>,
+    mut images: ResMut<Assets<Image>>,
+    board: Res<Board>,
+    pattern: Res<Pattern>,
+    origin: Res<WorldOrigin>,
+    heatmap: Option<Res<Heatmap>>,
+    filter: Res<TextureFilter>,
+) {
+    let heatmap = heatmap.filter(|heatmap| heatmap.shown);
+    let ChunkTextures { dirty, chunks } = &mut *textures;
+    #[cfg(feature = "trace")]
+    let _span = info_span!("paint_texels", cells = dirty.len()).entered();
+    for cell in dirty.drain() {
+        let (chunk, _) = chunk_of(cell);
+        let color = match board.grid.get(cell) {
+            Some(state) => match &heatmap {
+                Some(heatmap) => {
+                    heatmap.color(board.visits.get(&cell).copied().unwrap_or_default())
+                }
+                None => pattern.colors[state],
+            }
+            .to_srgba()
+            .to_u8_array(),
+            // A forgotten cell goes back to the background
+            None if chunks.contains_key(&chunk) => [0; 4],
+            None => continue,
+        };
+
+        let (_, texture) = chunks
+            .entry(chunk)
+            .or_insert_with(|| spawn_chunk(&mut commands, &mut images, *origin, *filter, chunk));
+        let Some(image) = images.get_mut(texture) else {
+            continue;
+        };
+
+        // Texture rows run top to bottom, lattice rows bottom to top
+        let local = cell.rem_euclid(IVec2::splat(CHUNK_SIZE));
+        let texel = ((CHUNK_SIZE - 1 - local.y) * CHUNK_SIZE + local.x) as usize * 4;
+        image.data[texel..texel + 4].copy_from_slice(&color);
+    }
+}
+
+/// Marks every painted cell dirty when the pattern resource is replaced
+fn repaint_all(board: Res<Board>, mut textures: ResMut<ChunkTextures>) {
+    textures
+        .dirty
+        .extend(board.grid.cells().map(|(cell, _)| cell));
+}
+
+/// Drops every chunk on a reset, then paints what a warmup after it left on the grid
+fn reset_textures(
+    mut commands: Commands,
+    mut reset_events: EventReader<ResetBoard>,
+    board: Res<Board>,
+    mut textures: ResMut<ChunkTextures>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if reset_events.read().count() == 0 {
+        return;
+    }
+
+    textures.dirty.clear();
+    for (_, (entity, texture)) in textures.chunks.drain() {
+        commands.entity(entity).despawn();
+        images.remove(&texture);
+    }
+    textures
+        .dirty
+        .extend(board.grid.cells().map(|(cell, _)| cell));
+}
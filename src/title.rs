@@ -0,0 +1,95 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{board::StepCounter, pattern::Pattern, presets::preset_of, AppState};
+
+/// How often the live title is refreshed, in seconds of wall time. Some window managers
+/// redraw the whole title bar on every change, so it isn't updated each frame.
+const TITLE_REFRESH: f64 = 0.25;
+
+/// Names the current rule in the window title. With `live` set it also shows the step
+/// count and whether the run is paused.
+pub struct TitlePlugin {
+    pub live: bool,
+}
+
+impl Plugin for TitlePlugin {
+    fn build(&self, app: &mut App) {
+        if self.live {
+            app.add_systems(Update, update_live_title);
+        } else {
+            // The window can show up after the pattern is set, so it gets the title then
+            app.add_systems(
+                Update,
+                update_title.run_if(resource_changed::<Pattern>.or_else(window_added)),
+            );
+        }
+    }
+}
+
+/// The rule, followed by its preset name if it is one
+fn rule_name(pattern: &Pattern) -> String {
+    let rule = pattern.to_string();
+    match preset_of(pattern) {
+        Some((_, name)) => format!("{rule} ({name})"),
+        None => rule,
+    }
+}
+
+/// `12345` as `12,345`
+fn with_separators(value: u64) -> String {
+    let digits = value.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    out
+}
+
+fn window_added(window_query: Query<(), Added<PrimaryWindow>>) -> bool {
+    !window_query.is_empty()
+}
+
+fn update_title(pattern: Res<Pattern>, mut window_query: Query<&mut Window, With<PrimaryWindow>>) {
+    let Ok(mut window) = window_query.get_single_mut() else {
+        return;
+    };
+
+    window.title = format!("Langton's ant — {}", rule_name(&pattern));
+}
+
+fn update_live_title(
+    time: Res<Time<Real>>,
+    pattern: Res<Pattern>,
+    step_counter: Res<StepCounter>,
+    state: Res<State<AppState>>,
+    mut last_refresh: Local<Option<f64>>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let now = time.elapsed_seconds_f64();
+    if last_refresh.is_some_and(|last| now - last < TITLE_REFRESH) {
+        return;
+    }
+    // The window can show up a few frames late, keep trying until it does
+    let Ok(mut window) = window_query.get_single_mut() else {
+        return;
+    };
+    *last_refresh = Some(now);
+
+    let state = match state.get() {
+        AppState::Running => "running",
+        AppState::Paused => "paused",
+        AppState::Menu => "menu",
+    };
+    let title = format!(
+        "Langton's ant — {} — {} steps — {state}",
+        rule_name(&pattern),
+        with_separators(step_counter.0)
+    );
+    // Only touch the window when the text changed, every write is sent to the OS
+    if window.title != title {
+        window.title = title;
+    }
+}
@@ -0,0 +1,171 @@
+use bevy::{input::touch::Touch, prelude::*, window::PrimaryWindow};
+use bevy_pancam::PanCam;
+
+use crate::board::TogglePause;
+
+/// Longest two-finger touch, in seconds, that still counts as a tap
+const TAP_DURATION: f64 = 0.3;
+/// How far, in logical pixels, a finger may drift during a tap
+const TAP_SLOP: f32 = 10.;
+/// How long the gesture hint stays up, in seconds
+const HINT_DURATION: f32 = 4.;
+
+/// One-finger drag pans, pinch zooms and a two-finger tap toggles pause.
+///
+/// Touches don't press mouse buttons, so `PanCam`'s own dragging never sees them. Gestures
+/// are recomputed from the current touches every frame, so fingers can come and go freely.
+pub struct TouchPlugin;
+
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TapGesture>()
+            .add_systems(Startup, setup_hint)
+            .add_systems(Update, (touch_camera, touch_pause, show_hint));
+    }
+}
+
+/// Touch sequence lasting from the first finger down until the last one is lifted
+#[derive(Resource, Default)]
+struct TapGesture {
+    started: Option<f64>,
+    max_touches: usize,
+    moved: bool,
+}
+
+#[derive(Component)]
+struct TouchHint;
+
+fn setup_hint(mut commands: Commands) {
+    let mut hint = TextBundle::from_section(
+        "Drag to pan, pinch to zoom, tap with two fingers to pause",
+        TextStyle {
+            font_size: 20.,
+            color: Color::WHITE,
+            ..default()
+        },
+    )
+    .with_style(Style {
+        position_type: PositionType::Absolute,
+        bottom: Val::Px(10.),
+        left: Val::Px(10.),
+        padding: UiRect::all(Val::Px(8.)),
+        ..default()
+    })
+    .with_background_color(Color::srgba(0., 0., 0., 0.8));
+    hint.visibility = Visibility::Hidden;
+    commands.spawn((hint, TouchHint));
+}
+
+/// Shows the hint on the first touch and removes it for good a few seconds later
+fn show_hint(
+    mut commands: Commands,
+    touches: Res<Touches>,
+    time: Res<Time<Real>>,
+    mut shown_for: Local<Option<f32>>,
+    mut hint_query: Query<(Entity, &mut Visibility), With<TouchHint>>,
+) {
+    let Ok((hint, mut visibility)) = hint_query.get_single_mut() else {
+        return;
+    };
+
+    match shown_for.as_mut() {
+        None if touches.any_just_pressed() => {
+            *visibility = Visibility::Visible;
+            *shown_for = Some(0.);
+        }
+        None => (),
+        Some(elapsed) => {
+            *elapsed += time.delta_seconds();
+            if *elapsed > HINT_DURATION {
+                commands.entity(hint).despawn_recursive();
+            }
+        }
+    }
+}
+
+fn touch_camera(
+    touches: Res<Touches>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut camera_query: Query<(&PanCam, &mut Transform, &mut OrthographicProjection)>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let window_size = window.size();
+
+    // Fingers that just landed have no motion yet, sorted so the pair is stable
+    let mut active: Vec<&Touch> = touches
+        .iter()
+        .filter(|touch| !touches.just_pressed(touch.id()))
+        .collect();
+    active.sort_by_key(|touch| touch.id());
+
+    for (cam, mut transform, mut projection) in camera_query.iter_mut() {
+        if !cam.enabled {
+            continue;
+        }
+        let world_per_pixel = projection.area.size() / window_size;
+
+        match active.as_slice() {
+            [touch] => {
+                // Window y grows downwards, world y upwards
+                let delta = touch.delta() * Vec2::new(1., -1.);
+                transform.translation -= (delta * world_per_pixel).extend(0.);
+            }
+            [first, second, ..] => {
+                let distance = first.position().distance(second.position());
+                let previous = first
+                    .previous_position()
+                    .distance(second.previous_position());
+                if distance <= 0. || previous <= 0. {
+                    continue;
+                }
+
+                let mut scale = (projection.scale * previous / distance).max(cam.min_scale);
+                if let Some(max_scale) = cam.max_scale {
+                    scale = scale.min(max_scale);
+                }
+
+                // Keep the world point under the midpoint of the fingers in place
+                let midpoint = (first.position() + second.position()) / 2.;
+                let offset = (midpoint - window_size / 2.) * Vec2::new(1., -1.);
+                transform.translation +=
+                    (offset * world_per_pixel * (1. - scale / projection.scale)).extend(0.);
+                projection.scale = scale;
+            }
+            [] => (),
+        }
+    }
+}
+
+fn touch_pause(
+    touches: Res<Touches>,
+    time: Res<Time<Real>>,
+    mut gesture: ResMut<TapGesture>,
+    mut toggle_events: EventWriter<TogglePause>,
+) {
+    let now = time.elapsed_seconds_f64();
+    let count = touches.iter().count();
+    if count > 0 && gesture.started.is_none() {
+        *gesture = TapGesture {
+            started: Some(now),
+            ..default()
+        };
+    }
+    gesture.max_touches = gesture.max_touches.max(count);
+    gesture.moved |= touches
+        .iter()
+        .any(|touch| touch.distance().length() > TAP_SLOP);
+
+    if count > 0 {
+        return;
+    }
+    let Some(started) = gesture.started.take() else {
+        return;
+    };
+
+    let tapped = touches.any_just_released() && !touches.any_just_canceled();
+    if tapped && gesture.max_touches == 2 && !gesture.moved && now - started < TAP_DURATION {
+        toggle_events.send(TogglePause);
+    }
+}
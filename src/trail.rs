@@ -0,0 +1,106 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    board::{AntId, ResetBoard, WorldShifted},
+    keybindings::{Action, KeyBindings},
+    pattern_input::pattern_input_closed,
+    Shortcuts, TILE_SIZE,
+};
+
+/// Breadcrumbs kept, the oldest one fades out first
+const TRAIL_LENGTH: usize = 256;
+
+/// Fading dots along the ants' recent path, drawn with gizmos on top of the tiles.
+/// Positions are sampled once per fixed tick, so with several steps per tick the dots
+/// space out. Purely visual, the simulation never reads them.
+pub struct TrailPlugin {
+    /// Whether the trail is drawn from the start, `Action::ToggleTrail` flips it
+    pub enabled: bool,
+}
+
+impl Plugin for TrailPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Trail {
+            enabled: self.enabled,
+            points: VecDeque::with_capacity(TRAIL_LENGTH),
+        })
+        .init_resource::<KeyBindings>()
+        .add_systems(FixedPostUpdate, record_trail)
+        .add_systems(
+            Update,
+            (
+                toggle_trail.run_if(pattern_input_closed).in_set(Shortcuts),
+                clear_trail,
+                shift_trail,
+                draw_trail,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct Trail {
+    enabled: bool,
+    points: VecDeque<Vec2>,
+}
+
+fn toggle_trail(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut trail: ResMut<Trail>,
+) {
+    if bindings.just_pressed(Action::ToggleTrail, &keys) {
+        trail.enabled = !trail.enabled;
+        // Turning it back on starts a fresh trail instead of showing a stale one
+        trail.points.clear();
+    }
+}
+
+fn clear_trail(mut reset_events: EventReader<ResetBoard>, mut trail: ResMut<Trail>) {
+    if reset_events.read().count() > 0 {
+        trail.points.clear();
+    }
+}
+
+fn shift_trail(mut shifted_events: EventReader<WorldShifted>, mut trail: ResMut<Trail>) {
+    for shifted in shifted_events.read() {
+        for point in trail.points.iter_mut() {
+            *point -= shifted.offset;
+        }
+    }
+}
+
+fn record_trail(
+    mut trail: ResMut<Trail>,
+    ant_query: Query<&Transform, (With<AntId>, Changed<Transform>)>,
+) {
+    if !trail.enabled {
+        return;
+    }
+
+    for transform in ant_query.iter() {
+        let point = transform.translation.truncate();
+        if trail.points.back() == Some(&point) {
+            continue;
+        }
+        if trail.points.len() == TRAIL_LENGTH {
+            trail.points.pop_front();
+        }
+        trail.points.push_back(point);
+    }
+}
+
+fn draw_trail(trail: Res<Trail>, mut gizmos: Gizmos) {
+    if !trail.enabled {
+        return;
+    }
+
+    let count = trail.points.len() as f32;
+    for (i, point) in trail.points.iter().enumerate() {
+        let alpha = (i + 1) as f32 / count;
+        gizmos.circle_2d(*point, TILE_SIZE * 0.15, Color::srgba(0., 0., 0., alpha));
+    }
+}
@@ -0,0 +1,261 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPlugin};
+
+use crate::{
+    board::{
+        Ant, AntId, AntSteps, Board, ResetBoard, SingleStep, StepCounter, StepsPerTick, TileCap,
+        TogglePause,
+    },
+    format_rate,
+    lattice::Lattice,
+    palette::Palette,
+    pattern::{Pattern, PatternLimits, TurnSymbols},
+    symmetry::SymmetryScore,
+    AppState, Shortcuts, SimRng, MAX_RATE, MIN_RATE,
+};
+
+/// Side panel mirroring the keyboard and CLI controls, listing the ants on lattice `L`
+pub struct UiPlugin<L: Lattice>(PhantomData<L>);
+
+impl<L: Lattice> Default for UiPlugin<L> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<L: Lattice> Plugin for UiPlugin<L> {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(EguiPlugin)
+            .init_resource::<PanelState>()
+            .init_resource::<EguiWantsKeyboard>()
+            .init_resource::<SimRng>()
+            .init_resource::<Palette>()
+            .init_resource::<TurnSymbols>()
+            .init_resource::<PatternLimits>()
+            .configure_sets(
+                Update,
+                Shortcuts.run_if(resource_equals(EguiWantsKeyboard(false))),
+            )
+            .add_systems(Update, control_panel::<L>)
+            .add_systems(PostUpdate, check_egui_wants_keyboard);
+    }
+}
+
+#[derive(Resource)]
+struct PanelState {
+    open: bool,
+    pattern: Option<String>,
+    error: Option<String>,
+    /// Why the last color picked wasn't applied
+    color_error: Option<String>,
+}
+
+impl Default for PanelState {
+    fn default() -> Self {
+        Self {
+            open: true,
+            pattern: None,
+            error: None,
+            color_error: None,
+        }
+    }
+}
+
+/// Keeps shortcuts from firing while a text field has focus
+#[derive(Resource, Default, PartialEq)]
+struct EguiWantsKeyboard(bool);
+
+fn check_egui_wants_keyboard(
+    mut contexts: EguiContexts,
+    mut wants_keyboard: ResMut<EguiWantsKeyboard>,
+) {
+    let wants = contexts.ctx_mut().wants_keyboard_input();
+    wants_keyboard.set_if_neq(EguiWantsKeyboard(wants));
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn control_panel<L: Lattice>(
+    mut contexts: EguiContexts,
+    mut panel: ResMut<PanelState>,
+    mut pattern: ResMut<Pattern>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    mut steps_per_tick: ResMut<StepsPerTick>,
+    mut single_step: ResMut<SingleStep>,
+    mut reset_events: EventWriter<ResetBoard>,
+    mut toggle_events: EventWriter<TogglePause>,
+    state: Res<State<AppState>>,
+    step_counter: Res<StepCounter>,
+    board: Res<Board>,
+    cap: Res<TileCap>,
+    symmetry: Option<Res<SymmetryScore>>,
+    palette: Res<Palette>,
+    // Bevy systems take at most 16 parameters
+    (symbols, limits, ant_query): (
+        Res<TurnSymbols>,
+        Res<PatternLimits>,
+        Query<(&AntId, &Ant<L>, &AntSteps)>,
+    ),
+    mut rng: ResMut<SimRng>,
+) {
+    let ctx = contexts.ctx_mut();
+
+    if !panel.open {
+        egui::Area::new(egui::Id::new("controls_toggle"))
+            .anchor(egui::Align2::LEFT_TOP, egui::vec2(4., 4.))
+            .show(ctx, |ui| {
+                if ui.button("Controls").clicked() {
+                    panel.open = true;
+                }
+            });
+        return;
+    }
+
+    let PanelState {
+        open,
+        pattern: pattern_text,
+        error,
+        color_error,
+    } = &mut *panel;
+    let pattern_text = pattern_text.get_or_insert_with(|| pattern.to_string());
+
+    egui::SidePanel::left("controls").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.heading("Langton's ant");
+            if ui.small_button("Hide").clicked() {
+                *open = false;
+            }
+        });
+
+        ui.separator();
+        ui.label("Pattern");
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(pattern_text);
+            if ui.button("Apply").clicked() {
+                match Pattern::parse_with_symbols(
+                    pattern_text.clone(),
+                    *palette,
+                    &symbols,
+                    &mut rng.0,
+                )
+                .and_then(|pattern| pattern.validated(&limits))
+                {
+                    Ok(new_pattern) => {
+                        info!(
+                            "pattern parsed: {new_pattern} ({} states)",
+                            new_pattern.turns.len()
+                        );
+                        *pattern = new_pattern;
+                        reset_events.send(ResetBoard);
+                        *error = None;
+                    }
+                    Err(err) => *error = Some(err.to_string()),
+                }
+            }
+        });
+        if let Some(err) = error {
+            ui.colored_label(egui::Color32::RED, err.as_str());
+        }
+
+        ui.separator();
+        let mut rate = 1. / fixed_time.timestep().as_secs_f64();
+        if ui
+            .add(
+                // The whole range `--rate` accepts, slower rates than `MIN_RATE` sit at the
+                // left end
+                egui::Slider::new(&mut rate, 0.0..=MAX_RATE)
+                    .logarithmic(true)
+                    .smallest_positive(MIN_RATE / 100.)
+                    .custom_formatter(|rate, _| format_rate(rate))
+                    .text("rate"),
+            )
+            .changed()
+            && rate > 0.
+        {
+            fixed_time.set_timestep_hz(rate);
+        }
+        ui.add(
+            egui::Slider::new(&mut steps_per_tick.0, 1..=u32::MAX)
+                .logarithmic(true)
+                .text("steps per tick"),
+        );
+
+        ui.horizontal(|ui| {
+            let paused = *state.get() == AppState::Paused;
+            if ui.button(if paused { "Run" } else { "Pause" }).clicked() {
+                toggle_events.send(TogglePause);
+            }
+            if ui.add_enabled(paused, egui::Button::new("Step")).clicked() {
+                single_step.0 = true;
+            }
+            if ui.button("Reset").clicked() {
+                reset_events.send(ResetBoard);
+            }
+        });
+
+        ui.separator();
+        ui.label("Colors");
+        let mut edited = None;
+        // Each state with its turn, and its distance when it moves more than one cell
+        let labels: Vec<String> = pattern
+            .turns
+            .iter()
+            .zip(&pattern.distances)
+            .enumerate()
+            .map(|(i, (turn, distance))| match distance {
+                1 => format!("state {i}: {turn}"),
+                _ => format!("state {i}: {turn}, {distance} cells"),
+            })
+            .collect();
+        for (index, (color, label)) in pattern.colors.iter().zip(labels).enumerate() {
+            let srgba = color.to_srgba();
+            let mut rgb = [srgba.red, srgba.green, srgba.blue];
+            ui.horizontal(|ui| {
+                ui.label(label);
+                if ui.color_edit_button_rgb(&mut rgb).changed() {
+                    edited = Some((index, Color::srgb(rgb[0], rgb[1], rgb[2])));
+                }
+            });
+        }
+        if let Some((index, color)) = edited {
+            // Rejected like a pattern naming the same color twice
+            let mut colors = pattern.colors.clone();
+            colors[index] = color;
+            match Pattern::check_colors(&colors) {
+                Ok(()) => {
+                    pattern.colors = colors;
+                    *color_error = None;
+                }
+                Err(err) => *color_error = Some(err.to_string()),
+            }
+        }
+        if let Some(err) = color_error {
+            ui.colored_label(egui::Color32::RED, err.as_str());
+        }
+
+        ui.separator();
+        ui.label(format!("Steps: {}", step_counter.0));
+        ui.label(format!("Painted cells: {}", board.grid.len()));
+        if cap.max.is_some() {
+            ui.label(format!("Forgotten cells: {}", cap.evicted));
+        }
+        if let Some((steps, score)) = symmetry.and_then(|symmetry| symmetry.latest) {
+            ui.label(format!("Symmetry at step {steps}"));
+            ui.label(format!("  vertical axis: {:.3}", score.vertical));
+            ui.label(format!("  horizontal axis: {:.3}", score.horizontal));
+            ui.label(format!("  half turn: {:.3}", score.rotation));
+        }
+
+        ui.separator();
+        ui.label("Ants");
+        let mut ants: Vec<_> = ant_query.iter().collect();
+        ants.sort_by_key(|(id, _, _)| **id);
+        for (id, ant, steps) in ants {
+            ui.label(format!(
+                "ant {}: {}, {:?}, {} steps",
+                id.0, ant.0.cell, ant.0.heading, steps.0
+            ));
+        }
+    });
+}
@@ -0,0 +1,125 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::{io::ErrorKind, path::PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use bevy::prelude::*;
+#[cfg(not(target_arch = "wasm32"))]
+use bevy_pancam::PanCam;
+
+/// Saves the camera position and zoom to `path` on exit and restores them on the next
+/// start. A missing file leaves the default view, an unreadable one is reported and
+/// ignored. Not built for the web, which has no file system.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct RememberViewPlugin {
+    pub path: PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Plugin for RememberViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ViewFile(self.path.clone()))
+            // The camera is spawned during `Startup`
+            .add_systems(PostStartup, restore_view.in_set(RestoreView))
+            .add_systems(Last, save_view.run_if(on_event::<AppExit>()));
+    }
+}
+
+/// Restores the saved view in `PostStartup`, anything setting up the view goes before it
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RestoreView;
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Resource)]
+struct ViewFile(PathBuf);
+
+/// Camera framing as stored in the view file
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SavedView {
+    pub translation: Vec2,
+    /// Projection scale, world units per screen pixel
+    pub scale: f32,
+}
+
+impl SavedView {
+    /// Reads `x`, `y` and `scale` from a TOML table
+    pub fn parse(text: &str) -> Result<Self> {
+        let table = text.parse::<toml::Table>()?;
+        let field = |name: &str| {
+            table
+                .get(name)
+                // Hand-edited files may well say `x = 0`
+                .and_then(|value| value.as_float().or(value.as_integer().map(|i| i as f64)))
+                .map(|value| value as f32)
+                .filter(|value| value.is_finite())
+                .ok_or_else(|| anyhow!("missing or invalid `{name}`"))
+        };
+
+        let view = Self {
+            translation: Vec2::new(field("x")?, field("y")?),
+            scale: field("scale")?,
+        };
+        if view.scale <= 0. {
+            bail!("`scale` must be positive");
+        }
+        Ok(view)
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut table = toml::Table::new();
+        table.insert("x".to_owned(), f64::from(self.translation.x).into());
+        table.insert("y".to_owned(), f64::from(self.translation.y).into());
+        table.insert("scale".to_owned(), f64::from(self.scale).into());
+        table.to_string()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn restore_view(
+    file: Res<ViewFile>,
+    mut camera_query: Query<(&PanCam, &mut Transform, &mut OrthographicProjection)>,
+) {
+    let text = match std::fs::read_to_string(&file.0) {
+        Ok(text) => text,
+        Err(err) if err.kind() == ErrorKind::NotFound => return,
+        Err(err) => {
+            warn!("failed to read {}: {err}", file.0.display());
+            return;
+        }
+    };
+    let view = match SavedView::parse(&text) {
+        Ok(view) => view,
+        Err(err) => {
+            warn!("ignoring saved view in {}: {err}", file.0.display());
+            return;
+        }
+    };
+
+    for (cam, mut transform, mut projection) in camera_query.iter_mut() {
+        // The zoom limits may have changed since the view was saved
+        let mut scale = view.scale.max(cam.min_scale);
+        if let Some(max_scale) = cam.max_scale {
+            scale = scale.min(max_scale);
+        }
+        transform.translation = view.translation.extend(transform.translation.z);
+        projection.scale = scale;
+    }
+    info!("restored view from {}", file.0.display());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_view(
+    file: Res<ViewFile>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<PanCam>>,
+) {
+    let Ok((transform, projection)) = camera_query.get_single() else {
+        return;
+    };
+
+    let view = SavedView {
+        translation: transform.translation.truncate(),
+        scale: projection.scale,
+    };
+    if let Err(err) = std::fs::write(&file.0, view.to_toml()) {
+        warn!("failed to save the view to {}: {err}", file.0.display());
+    }
+}
@@ -0,0 +1,100 @@
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
+use bevy_pancam::PanCamSystemSet;
+
+use crate::{format_rate, theme::foreground, MAX_RATE, MIN_RATE, RATE_STEP};
+
+/// Scroll distance in pixels that counts as one wheel notch, as `PanCam` counts it
+const PIXELS_PER_LINE: f32 = 100.;
+/// How long the rate stays on screen after the last change, in seconds
+const FEEDBACK_SECS: f32 = 1.;
+
+/// Shift and the mouse wheel change the rate by `RATE_STEP` per notch instead of
+/// zooming. The wheel events are taken before `PanCam` reads them, so zooming with the
+/// plain wheel is untouched.
+pub struct WheelRatePlugin;
+
+impl Plugin for WheelRatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ClearColor>()
+            .add_systems(Startup, setup_feedback)
+            .add_systems(
+                Update,
+                (wheel_rate.before(PanCamSystemSet), hide_feedback).chain(),
+            );
+    }
+}
+
+#[derive(Component)]
+struct RateFeedback {
+    /// Seconds left on screen
+    left: f32,
+}
+
+fn setup_feedback(mut commands: Commands, background: Res<ClearColor>) {
+    commands.spawn((
+        TextBundle {
+            visibility: Visibility::Hidden,
+            ..TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 24.,
+                    color: foreground(background.0),
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.),
+                left: Val::Percent(50.),
+                ..default()
+            })
+        },
+        RateFeedback { left: 0. },
+    ));
+}
+
+fn wheel_rate(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut scroll_events: ResMut<Events<MouseWheel>>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    mut feedback_query: Query<(&mut Text, &mut Visibility, &mut RateFeedback)>,
+) {
+    if !keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        return;
+    }
+    let notches: f32 = scroll_events
+        .drain()
+        // Some platforms turn a shifted vertical scroll into a horizontal one
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.x + event.y,
+            MouseScrollUnit::Pixel => (event.x + event.y) / PIXELS_PER_LINE,
+        })
+        .sum();
+    if notches == 0. {
+        return;
+    }
+
+    let rate = 1. / fixed_time.timestep().as_secs_f64();
+    let rate = (rate * RATE_STEP.powf(notches as f64)).clamp(MIN_RATE, MAX_RATE);
+    fixed_time.set_timestep_hz(rate);
+    for (mut text, mut visibility, mut feedback) in feedback_query.iter_mut() {
+        text.sections[0].value = format!("Rate: {}", format_rate(rate));
+        *visibility = Visibility::Visible;
+        feedback.left = FEEDBACK_SECS;
+    }
+}
+
+fn hide_feedback(time: Res<Time>, mut feedback_query: Query<(&mut Visibility, &mut RateFeedback)>) {
+    for (mut visibility, mut feedback) in feedback_query.iter_mut() {
+        if feedback.left <= 0. {
+            continue;
+        }
+        feedback.left -= time.delta_seconds();
+        if feedback.left <= 0. {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
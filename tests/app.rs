@@ -0,0 +1,1431 @@
+//! Runs the board plugin headless, without a window or GPU.
+
+use std::{
+    f32::consts::{FRAC_PI_2, PI},
+    time::Duration,
+};
+
+use bevy::{
+    audio::Pitch,
+    gizmos::GizmoPlugin,
+    input::{
+        keyboard::{Key, KeyboardInput},
+        mouse::{MouseScrollUnit, MouseWheel},
+        ButtonState,
+    },
+    prelude::*,
+    sprite::Mesh2dHandle,
+    state::app::StatesPlugin,
+    time::TimeUpdateStrategy,
+    window::PrimaryWindow,
+};
+use bevy_pancam::{PanCam, PanCamPlugin};
+use langtons_ant::{
+    ant_keys::AntKeysPlugin,
+    ant_labels::{AntLabel, AntLabelsPlugin},
+    board::{
+        ant_tint, Ant, AntId, AntSteps, Board, BoardPlugin, CollisionRule, MaxSteps, ResetBoard,
+        StartingAnts, StepCounter, StepsPerTick, StopOnOrigin, Tile, TileCap, TogglePause, Warmup,
+        WorldOrigin,
+    },
+    camera::{CameraKeysPlugin, InitialZoomPlugin},
+    fade::FadeInPlugin,
+    growth::{GrowthGraph, GrowthGraphPlugin},
+    heatmap::{HeatGradient, Heatmap, HeatmapPlugin},
+    highlight::contrasting,
+    hud::HudPlugin,
+    hue_cycle::{HueCycle, HueCyclePlugin},
+    icon::window_icon,
+    inspector::describe_cell,
+    keybindings::{Action, KeyBindings},
+    lattice::{Lattice, SquareLattice},
+    menu::{Menu, MenuButton, MenuPlugin},
+    minimap::{Minimap, MinimapPlugin},
+    pattern::{Pattern, PatternLimits, DEFAULT_MAX_STATES},
+    pattern_input::PatternInput,
+    presets::PresetsPlugin,
+    scheduler::{Scheduler, SchedulerPlugin},
+    share::share_command,
+    sim::{simulate, step, AntState, Collision, GridState, CHUNK_SIZE},
+    sound::SoundPlugin,
+    title::TitlePlugin,
+    view::{RememberViewPlugin, SavedView},
+    wheel_rate::WheelRatePlugin,
+    AppState, SimRng, RATE_STEP,
+};
+
+/// App with only the engine parts the board plugin needs
+fn engine_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin, AssetPlugin::default()))
+        // Gizmos register their shaders even without a renderer
+        .init_asset::<Shader>()
+        .add_plugins(GizmoPlugin)
+        .init_asset::<Mesh>()
+        .init_asset::<ColorMaterial>()
+        .init_asset::<Image>()
+        .init_state::<AppState>()
+        .init_resource::<ButtonInput<KeyCode>>()
+        .init_resource::<PatternInput>()
+        // One fixed tick per update, however fast the test runs
+        .insert_resource(Time::<Fixed>::from_hz(60.))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1. / 60.,
+        )));
+    app
+}
+
+/// App with the board plugin and only the engine parts it needs
+fn headless_app(pattern: &str) -> App {
+    let mut rng = SimRng::seeded(0);
+    let mut app = engine_app();
+    app.insert_resource(Board::default())
+        .insert_resource(StepsPerTick(1))
+        .insert_resource(Pattern::parse(pattern.to_owned(), &mut rng.0).unwrap())
+        .add_plugins(BoardPlugin::<SquareLattice>::default());
+    app
+}
+
+#[test]
+fn steps_spawn_tiles_and_move_the_ant() {
+    let mut app = headless_app("RL");
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let world = app.world_mut();
+    assert!(world.resource::<StepCounter>().0 > 0);
+
+    let tiles = world.query::<&Tile>().iter(world).count();
+    assert!(tiles > 0, "no tile was spawned");
+
+    let ant = world.query::<&Ant<SquareLattice>>().single(world).0;
+    assert_ne!(ant.cell, IVec2::ZERO, "the ant never left the origin");
+}
+
+#[test]
+fn board_plugin_inserts_its_pattern_unless_the_app_has_one() {
+    assert!(BoardPlugin::<SquareLattice>::with_pattern("RX").is_err());
+
+    let mut app = engine_app();
+    app.add_plugins(BoardPlugin::<SquareLattice>::with_pattern("LLRR*2").unwrap());
+    for _ in 0..10 {
+        app.update();
+    }
+    let pattern = app.world().resource::<Pattern>();
+    assert_eq!(pattern.to_string(), "LLRR*2");
+    assert_eq!(pattern.colors.len(), 4);
+    assert!(app.world().resource::<StepCounter>().0 > 0);
+
+    let mut rng = SimRng::seeded(0);
+    let mut app = engine_app();
+    app.insert_resource(Pattern::parse("RLR".to_owned(), &mut rng.0).unwrap())
+        .add_plugins(BoardPlugin::<SquareLattice>::with_pattern("RL").unwrap());
+    assert_eq!(app.world().resource::<Pattern>().to_string(), "RLR");
+}
+
+/// Colors the tiles are currently drawn with
+fn tile_colors(app: &mut App) -> Vec<Color> {
+    let world = app.world_mut();
+    let handles: Vec<Handle<ColorMaterial>> = world
+        .query_filtered::<&Handle<ColorMaterial>, With<Tile>>()
+        .iter(world)
+        .cloned()
+        .collect();
+    let materials = world.resource::<Assets<ColorMaterial>>();
+    handles
+        .iter()
+        .map(|handle| materials.get(handle).unwrap().color)
+        .collect()
+}
+
+/// What a seeded run ended with
+#[derive(Debug, PartialEq)]
+struct SeededRun {
+    colors: Vec<Color>,
+    /// Cell and drawn color of every tile, by cell
+    tiles: Vec<(IVec2, Color)>,
+    ants: Vec<(usize, IVec2)>,
+}
+
+/// Run seeded with `seed`, switching to the next preset halfway, which draws the colors
+/// of its pattern too
+fn seeded_run(seed: u64) -> SeededRun {
+    let mut rng = SimRng::seeded(seed);
+    let mut app = engine_app();
+    app.insert_resource(Board::default())
+        .insert_resource(StepsPerTick(3))
+        .insert_resource(Pattern::parse("RLR".to_owned(), &mut rng.0).unwrap())
+        .insert_resource(rng)
+        .add_plugins((BoardPlugin::<SquareLattice>::default(), PresetsPlugin));
+    for _ in 0..20 {
+        app.update();
+    }
+    tap(&mut app, KeyCode::Tab);
+    for _ in 0..20 {
+        app.update();
+    }
+
+    let colors = app.world().resource::<Pattern>().colors.clone();
+    let world = app.world_mut();
+    let tiles: Vec<(IVec2, Entity)> = world
+        .resource::<Board>()
+        .tiles
+        .iter()
+        .map(|(cell, tile)| (*cell, *tile))
+        .collect();
+    let materials = world.resource::<Assets<ColorMaterial>>();
+    let mut tiles: Vec<(IVec2, Color)> = tiles
+        .into_iter()
+        .map(|(cell, tile)| {
+            let handle = world.get::<Handle<ColorMaterial>>(tile).unwrap();
+            (cell, materials.get(handle).unwrap().color)
+        })
+        .collect();
+    tiles.sort_by_key(|(cell, _)| (cell.x, cell.y));
+    SeededRun {
+        colors,
+        tiles,
+        ants: ants(&mut app),
+    }
+}
+
+#[test]
+fn same_seed_gives_the_same_run() {
+    let run = seeded_run(7);
+    assert_eq!(run.colors.len(), 4, "the preset never applied");
+    assert!(!run.tiles.is_empty());
+    assert_eq!(seeded_run(7), run);
+    assert_ne!(seeded_run(8).colors, run.colors);
+}
+
+#[test]
+fn heatmap_tints_tiles_by_visits_until_toggled_off() {
+    let mut app = headless_app("RL");
+    app.add_plugins(HeatmapPlugin {
+        gradient: HeatGradient::Viridis,
+        shown: true,
+    });
+    for _ in 0..300 {
+        app.update();
+    }
+
+    let world = app.world();
+    let board = world.resource::<Board>();
+    let steps = world.resource::<StepCounter>().0;
+    assert_eq!(
+        board
+            .visits
+            .values()
+            .map(|visits| *visits as u64)
+            .sum::<u64>(),
+        steps
+    );
+    let heatmap = *world.resource::<Heatmap>();
+    let hottest = *board.visits.values().max().unwrap();
+    assert!(
+        hottest > 1 && heatmap.top > 0,
+        "{hottest} visits, top level {}",
+        heatmap.top
+    );
+    let mut expected: Vec<Color> = board
+        .grid
+        .cells()
+        .map(|(cell, _)| heatmap.color(board.visits[&cell]))
+        .collect();
+    let mut colors = tile_colors(&mut app);
+    let key = |color: &Color| format!("{color:?}");
+    expected.sort_by_key(key);
+    colors.sort_by_key(key);
+    assert_eq!(colors, expected);
+
+    tap(&mut app, KeyCode::KeyE);
+    let pattern = app.world().resource::<Pattern>().colors.clone();
+    assert!(tile_colors(&mut app)
+        .iter()
+        .all(|color| pattern.contains(color)));
+
+    app.world_mut().send_event(ResetBoard);
+    app.update();
+    assert!(app.world().resource::<Board>().visits.len() <= 1);
+}
+
+#[test]
+fn hue_cycle_restores_the_base_colors() {
+    let mut app = headless_app("RL");
+    app.add_plugins(HueCyclePlugin { enabled: true });
+    for _ in 0..30 {
+        app.update();
+    }
+
+    let base = app.world().resource::<Pattern>().colors.clone();
+    let cycled = tile_colors(&mut app);
+    assert!(!cycled.is_empty());
+    assert!(cycled.iter().all(|color| !base.contains(color)));
+
+    app.world_mut().resource_mut::<HueCycle>().0 = false;
+    app.update();
+    assert!(tile_colors(&mut app)
+        .iter()
+        .all(|color| base.contains(color)));
+}
+
+#[test]
+fn new_tiles_fade_in_to_their_state_color() {
+    let mut app = headless_app("RL");
+    app.insert_resource(ClearColor(Color::WHITE))
+        .add_plugins(FadeInPlugin {
+            duration: Duration::from_millis(100),
+        });
+    app.world_mut().spawn((Window::default(), PrimaryWindow));
+    app.update();
+    app.update();
+
+    let base = app.world().resource::<Pattern>().colors.clone();
+    let colors = tile_colors(&mut app);
+    assert!(!colors.is_empty());
+    assert!(colors.iter().any(|color| !base.contains(color)));
+
+    // Once paused no new fade starts, and ten updates outlast the 100 ms ones running
+    app.world_mut()
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Paused);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert!(tile_colors(&mut app)
+        .iter()
+        .all(|color| base.contains(color)));
+}
+
+#[test]
+fn inspector_describes_visited_and_unvisited_cells() {
+    let mut app = headless_app("RL");
+    app.update();
+    app.update();
+
+    let world = app.world();
+    let board = world.resource::<Board>();
+    let pattern = world.resource::<Pattern>();
+    let (cell, state) = board.grid.cells().next().expect("a painted cell");
+    let color = pattern.colors[state].to_srgba().to_hex();
+    let turn = pattern.turns[state];
+    assert_eq!(
+        describe_cell(&board.grid, pattern, None, cell),
+        format!("({}, {}): state {state} {turn} {color}", cell.x, cell.y)
+    );
+    let visits = [(cell, 3)].into_iter().collect();
+    assert_eq!(
+        describe_cell(&board.grid, pattern, Some(&visits), cell),
+        format!(
+            "({}, {}): state {state} {turn} {color}, 3 visits",
+            cell.x, cell.y
+        )
+    );
+    assert_eq!(
+        describe_cell(&board.grid, pattern, Some(&visits), IVec2::new(100, -7)),
+        "(100, -7): unvisited"
+    );
+}
+
+#[test]
+fn ant_highlight_contrasts_with_the_background() {
+    for background in [Color::WHITE, Color::BLACK, Color::srgb(0.2, 0.4, 0.9)] {
+        let highlight = contrasting(background);
+        let (a, b) = (Oklaba::from(background), Oklaba::from(highlight));
+        assert!(
+            (a.lightness - b.lightness).abs() > 0.25,
+            "{highlight:?} on {background:?}"
+        );
+    }
+}
+
+#[test]
+fn far_ants_recenter_the_world_keeping_relative_positions() {
+    let mut app = headless_app("RL");
+    for _ in 0..5 {
+        app.update();
+    }
+
+    // Jump far beyond the recentering distance, as after a very long run
+    let world = app.world_mut();
+    let mut ant = world.query::<&mut Ant<SquareLattice>>().single_mut(world);
+    ant.0.cell += IVec2::new(1_000_000, -3);
+    for _ in 0..5 {
+        app.update();
+    }
+
+    let world = app.world_mut();
+    let origin = world.resource::<WorldOrigin>().0;
+    let (ant, ant_transform) = world
+        .query::<(&Ant<SquareLattice>, &Transform)>()
+        .single(world);
+    let (ant_cell, ant_position) = (ant.0.cell, ant_transform.translation.truncate());
+    assert_ne!(origin, IVec2::ZERO, "the world was never recentered");
+    assert!(
+        ant_position.length() < 10_000.,
+        "ant still drawn at {ant_position}"
+    );
+
+    let tiles: Vec<(IVec2, Entity)> = world
+        .resource::<Board>()
+        .tiles
+        .iter()
+        .map(|(cell, tile)| (*cell, *tile))
+        .collect();
+    assert!(tiles.len() > 5, "tiles from before and after the jump");
+    for (cell, tile) in tiles {
+        let position = world.get::<Transform>(tile).unwrap().translation.truncate();
+        let expected = SquareLattice::cell_to_world(cell - ant_cell);
+        assert!(
+            (position - ant_position - expected).length() < 0.01,
+            "tile {cell} drawn at {position}, ant {ant_cell} at {ant_position}"
+        );
+    }
+}
+
+#[test]
+fn reset_brings_a_recentered_world_back_to_the_origin() {
+    let mut app = headless_app("RL");
+    app.update();
+    let world = app.world_mut();
+    let mut ant = world.query::<&mut Ant<SquareLattice>>().single_mut(world);
+    ant.0.cell += IVec2::new(1_000_000, -3);
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_ne!(app.world().resource::<WorldOrigin>().0, IVec2::ZERO);
+
+    app.world_mut().send_event(ResetBoard);
+    app.update();
+    let world = app.world_mut();
+    assert_eq!(world.resource::<WorldOrigin>().0, IVec2::ZERO);
+    let (ant, ant_transform) = world
+        .query::<(&Ant<SquareLattice>, &Transform)>()
+        .single(world);
+    let ant_position = ant_transform.translation.truncate();
+    assert!(
+        (ant_position - SquareLattice::cell_to_world(ant.0.cell)).length() < 0.01,
+        "ant {} drawn at {ant_position}",
+        ant.0.cell
+    );
+    assert!(ant_position.length() < 100., "ant drawn at {ant_position}");
+
+    for _ in 0..5 {
+        app.update();
+    }
+    let world = app.world_mut();
+    for transform in world.query_filtered::<&Transform, With<Tile>>().iter(world) {
+        let position = transform.translation.truncate();
+        assert!(position.length() < 100., "tile drawn at {position}");
+    }
+}
+
+/// Frequencies of the tones spawned so far, nothing plays them without `AudioPlugin`
+fn tones(app: &mut App) -> Vec<f32> {
+    let world = app.world_mut();
+    let handles: Vec<Handle<Pitch>> = world
+        .query::<&Handle<Pitch>>()
+        .iter(world)
+        .cloned()
+        .collect();
+    let pitches = world.resource::<Assets<Pitch>>();
+    handles
+        .iter()
+        .map(|handle| pitches.get(handle).unwrap().frequency)
+        .collect()
+}
+
+#[test]
+fn warmup_starts_the_app_where_as_many_steps_lead() {
+    const WARMUP: u64 = 12_000;
+    let mut app = headless_app("RL");
+    app.insert_resource(Warmup(WARMUP))
+        .insert_resource(State::new(AppState::Paused));
+    app.update();
+
+    let mut grid = GridState::default();
+    let mut ant = AntState::<SquareLattice>::default();
+    simulate(
+        &mut grid,
+        &mut ant,
+        app.world().resource::<Pattern>(),
+        WARMUP,
+    );
+
+    assert_eq!(app.world().resource::<StepCounter>().0, WARMUP);
+    assert_eq!(ants(&mut app), [(0, ant.cell)]);
+    let world = app.world_mut();
+    assert_eq!(world.query::<&AntSteps>().single(world).0, WARMUP);
+    assert_eq!(
+        world.resource::<Board>().grid.content_hash(),
+        grid.content_hash()
+    );
+    // The highway is well under way, the view follows the ant there
+    assert!(ant.cell.abs().max_element() > 30);
+    let origin = world.resource::<WorldOrigin>().0;
+    assert!(
+        (ant.cell - origin).abs().max_element() <= CHUNK_SIZE,
+        "{origin}"
+    );
+    assert_eq!(world.query::<&Tile>().iter(world).count(), grid.len());
+}
+
+#[test]
+fn clicks_are_rate_limited_and_auto_pauses_chime() {
+    let mut app = headless_app("RL");
+    app.init_asset::<Pitch>()
+        .add_plugins(SoundPlugin { volume: 0.5 });
+    for _ in 0..30 {
+        app.update();
+    }
+    // Half a second of ticks, at most 30 clicks a second
+    let clicks = tones(&mut app);
+    assert!((2..=16).contains(&clicks.len()), "{clicks:?}");
+    assert!(clicks.iter().all(|&frequency| frequency == clicks[0]));
+
+    let mut app = headless_app("RL");
+    app.init_asset::<Pitch>()
+        .insert_resource(StopOnOrigin(true))
+        .add_plugins(SoundPlugin { volume: 0.5 });
+    for _ in 0..10 {
+        app.update();
+    }
+    // Four right turns bring the ant back to the origin
+    let played = tones(&mut app);
+    assert_eq!(
+        played
+            .iter()
+            .filter(|&&frequency| frequency != played[0])
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn stop_on_origin_waits_for_a_return() {
+    let steps = |app: &App| app.world().resource::<StepCounter>().0;
+    let state = |app: &App| app.world().resource::<State<AppState>>().get().clone();
+
+    let mut app = headless_app("RL");
+    app.insert_resource(StopOnOrigin(true));
+    for _ in 0..20 {
+        app.update();
+    }
+    // Four right turns bring the ant back to the origin
+    assert_eq!(state(&app), AppState::Paused);
+    assert_eq!(steps(&app), 4);
+    assert_eq!(ants(&mut app)[0].1, IVec2::ZERO);
+
+    // A second ant stands where the first would step, which stays on the origin
+    let mut blocker = AntState::<SquareLattice>::default();
+    step(
+        &mut GridState::default(),
+        &mut blocker,
+        app.world().resource::<Pattern>(),
+    );
+    let mut app = headless_app("RL");
+    app.insert_resource(StopOnOrigin(true))
+        .insert_resource(CollisionRule(Collision::Block))
+        .insert_resource(StartingAnts(vec![AntState::default(), blocker]));
+    for _ in 0..20 {
+        app.update();
+        if steps(&app) == 1 {
+            assert_eq!(ants(&mut app)[0].1, IVec2::ZERO);
+            assert_eq!(state(&app), AppState::Running);
+        }
+    }
+    // It only pauses once it left and came back
+    assert_eq!(state(&app), AppState::Paused);
+    assert!(steps(&app) > 2);
+    assert!(ants(&mut app).iter().any(|(_, cell)| *cell == IVec2::ZERO));
+}
+
+#[test]
+fn copy_share_link_takes_control_and_shares_its_key() {
+    let bindings = KeyBindings::default();
+    let mut keys = ButtonInput::<KeyCode>::default();
+    keys.press(KeyCode::KeyC);
+    assert!(bindings.just_pressed(Action::CenterOnAnt, &keys));
+    assert!(!bindings.just_pressed(Action::CopyShareLink, &keys));
+
+    let mut keys = ButtonInput::<KeyCode>::default();
+    keys.press(KeyCode::ControlLeft);
+    keys.press(KeyCode::KeyC);
+    assert!(bindings.just_pressed(Action::CopyShareLink, &keys));
+    assert!(!bindings.just_pressed(Action::CenterOnAnt, &keys));
+
+    // Only keys pressed the same way conflict
+    let bindings = KeyBindings::parse("copy_share_link = \"S\"\ncenter_on_ant = \"S\"");
+    assert_eq!(bindings.key(Action::CopyShareLink), KeyCode::KeyS);
+    assert_eq!(bindings.key(Action::CenterOnAnt), KeyCode::KeyS);
+}
+
+#[test]
+fn unusable_icon_images_are_errors_not_panics() {
+    assert!(window_icon(include_bytes!("../assets/ant.png")).is_ok());
+
+    // A missing file reads as nothing, a damaged one as a cut off image
+    let png = include_bytes!("../assets/ant.png");
+    for bytes in [&[][..], &png[..png.len() / 2], b"not an image"] {
+        let err = window_icon(bytes).unwrap_err();
+        assert!(!err.to_string().is_empty());
+    }
+}
+
+#[test]
+fn ants_get_a_triangle_when_their_sprite_fails_to_load() {
+    // Without the image plugin nothing can load `ant.png`
+    let mut app = headless_app("RL");
+    for _ in 0..10 {
+        app.update();
+    }
+    let world = app.world_mut();
+    let ants: Vec<Entity> = world
+        .query_filtered::<Entity, With<AntId>>()
+        .iter(world)
+        .collect();
+    assert!(!ants.is_empty());
+    for ant in ants {
+        let children = world.get::<Children>(ant).expect("the ant has no marker");
+        assert!(children
+            .iter()
+            .any(|child| world.get::<Mesh2dHandle>(*child).is_some()));
+    }
+}
+
+#[test]
+fn static_title_is_set_on_a_window_that_shows_up_late() {
+    let mut app = headless_app("RLR");
+    app.add_plugins(TitlePlugin { live: false });
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let window = app
+        .world_mut()
+        .spawn((Window::default(), PrimaryWindow))
+        .id();
+    app.update();
+    let title = &app.world().get::<Window>(window).unwrap().title;
+    assert_eq!(title, "Langton's ant — RLR (chaotic growth)");
+}
+
+/// Presses `key` for a single update
+fn tap(app: &mut App, key: KeyCode) {
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(key);
+    app.update();
+    let mut keys = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+    keys.release(key);
+    keys.clear();
+}
+
+fn ants(app: &mut App) -> Vec<(usize, IVec2)> {
+    let world = app.world_mut();
+    let mut ants: Vec<(usize, IVec2)> = world
+        .query::<(&AntId, &Ant<SquareLattice>)>()
+        .iter(world)
+        .map(|(id, ant)| (id.0, ant.0.cell))
+        .collect();
+    ants.sort_by_key(|(id, _)| *id);
+    ants
+}
+
+#[test]
+fn ants_are_added_at_the_view_center_and_removed_newest_first() {
+    let mut app = headless_app("RL");
+    app.add_plugins(AntKeysPlugin::<SquareLattice>::default());
+    app.insert_resource(State::new(AppState::Paused));
+    let camera = app
+        .world_mut()
+        .spawn((Transform::from_xyz(200., 0., 0.), PanCam::default()))
+        .id();
+    app.update();
+
+    tap(&mut app, KeyCode::KeyA);
+    app.world_mut()
+        .get_mut::<Transform>(camera)
+        .unwrap()
+        .translation = Vec3::new(0., -200., 0.);
+    tap(&mut app, KeyCode::KeyA);
+    assert_eq!(
+        ants(&mut app),
+        [
+            (0, IVec2::ZERO),
+            (1, IVec2::new(10, 0)),
+            (2, IVec2::new(0, -10))
+        ]
+    );
+
+    tap(&mut app, KeyCode::KeyX);
+    assert_eq!(ants(&mut app), [(0, IVec2::ZERO), (1, IVec2::new(10, 0))]);
+
+    app.insert_resource(NextState::Pending(AppState::Running));
+    for _ in 0..5 {
+        app.update();
+    }
+    let moved = ants(&mut app);
+    assert_eq!(moved.len(), 2);
+    assert!(
+        moved[1].1 != IVec2::new(10, 0),
+        "the added ant never stepped"
+    );
+    let board = &app.world().resource::<Board>().grid;
+    assert!(board.get(IVec2::new(10, 0)).is_some());
+}
+
+#[test]
+fn toggle_pause_events_flip_the_state() {
+    let mut app = headless_app("RL");
+    app.update();
+    let state = |app: &App| app.world().resource::<State<AppState>>().get().clone();
+    assert_eq!(state(&app), AppState::Running);
+
+    app.world_mut().send_event(TogglePause);
+    app.update();
+    app.update();
+    assert_eq!(state(&app), AppState::Paused);
+    let steps = app.world().resource::<StepCounter>().0;
+    app.update();
+    assert_eq!(app.world().resource::<StepCounter>().0, steps);
+
+    app.world_mut().send_event(TogglePause);
+    app.update();
+    app.update();
+    assert_eq!(state(&app), AppState::Running);
+}
+
+#[test]
+fn ants_are_drawn_above_every_tile() {
+    let mut app = headless_app("RL");
+    for _ in 0..20 {
+        app.update();
+    }
+
+    let world = app.world_mut();
+    let ant_z = world
+        .query_filtered::<&Transform, With<AntId>>()
+        .single(world)
+        .translation
+        .z;
+    let tile_z: Vec<f32> = world
+        .query_filtered::<&Transform, With<Tile>>()
+        .iter(world)
+        .map(|transform| transform.translation.z)
+        .collect();
+    assert!(!tile_z.is_empty());
+    assert!(
+        tile_z.iter().all(|&z| z < ant_z),
+        "ant at {ant_z}, tiles at {tile_z:?}"
+    );
+}
+
+#[test]
+fn ant_rotation_matches_its_heading_after_a_million_steps() {
+    let mut app = headless_app("RL");
+    app.insert_resource(StepsPerTick(250_000));
+    // The first update starts the clock without a fixed tick
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_eq!(app.world().resource::<StepCounter>().0, 1_000_000);
+
+    let world = app.world_mut();
+    let (ant, transform) = world
+        .query::<(&Ant<SquareLattice>, &Transform)>()
+        .single(world);
+    let canonical = [0., FRAC_PI_2, PI, -FRAC_PI_2].map(Quat::from_rotation_z);
+    assert!(
+        canonical.contains(&transform.rotation),
+        "{}",
+        transform.rotation
+    );
+    assert_eq!(
+        transform.rotation,
+        SquareLattice::heading_to_rotation(ant.0.heading)
+    );
+}
+
+#[test]
+fn growth_samples_match_the_painted_cells() {
+    let mut app = headless_app("RL");
+    app.add_plugins(GrowthGraphPlugin { enabled: false });
+    // Enough ticks for the samples to be thinned out a few times
+    for _ in 0..2000 {
+        app.update();
+    }
+
+    let mut grid = GridState::default();
+    let mut ant = AntState::<SquareLattice>::default();
+    let mut painted = vec![0];
+    for _ in 0..app.world().resource::<StepCounter>().0 {
+        step(&mut grid, &mut ant, app.world().resource::<Pattern>());
+        painted.push(grid.len());
+    }
+
+    let samples = &app.world().resource::<GrowthGraph>().samples;
+    assert!(samples.len() > 100 && samples.len() <= 256);
+    assert!(samples.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    for (steps, cells) in samples {
+        assert_eq!(*cells, painted[*steps as usize], "sample at step {steps}");
+    }
+}
+
+#[test]
+fn rates_below_one_hertz_step_every_few_seconds() {
+    let mut app = headless_app("RL");
+    // Half a step per second, with updates 1/60 s apart
+    app.insert_resource(Time::<Fixed>::from_hz(0.5));
+    for _ in 0..100 {
+        app.update();
+    }
+    assert_eq!(app.world().resource::<StepCounter>().0, 0);
+    for _ in 0..30 {
+        app.update();
+    }
+    assert_eq!(app.world().resource::<StepCounter>().0, 1);
+    for _ in 0..120 {
+        app.update();
+    }
+    assert_eq!(app.world().resource::<StepCounter>().0, 2);
+}
+
+/// Steps per second the HUD shows
+fn hud_steps_per_second(app: &mut App) -> f64 {
+    let world = app.world_mut();
+    let text = world.query::<&Text>().single(world);
+    let readout = &text.sections[0].value;
+    readout
+        .split_once("Steps/s: ")
+        .and_then(|(_, steps)| steps.parse().ok())
+        .unwrap_or_else(|| panic!("no steps/s in {readout:?}"))
+}
+
+#[test]
+fn hud_steps_per_second_track_the_configured_rate() {
+    let mut app = headless_app("RL");
+    // 30 ticks a second of 4 steps, with updates 1/60 s apart
+    app.insert_resource(Time::<Fixed>::from_hz(30.))
+        .insert_resource(StepsPerTick(4))
+        .add_plugins(HudPlugin);
+    for _ in 0..120 {
+        app.update();
+    }
+    let steps_per_second = hud_steps_per_second(&mut app);
+    assert!((steps_per_second - 120.).abs() <= 6., "{steps_per_second}");
+
+    // A faster rate shows at the next refresh
+    app.insert_resource(StepsPerTick(20));
+    for _ in 0..60 {
+        app.update();
+    }
+    let steps_per_second = hud_steps_per_second(&mut app);
+    assert!((steps_per_second - 600.).abs() <= 30., "{steps_per_second}");
+}
+
+/// Scale the camera gets from `InitialZoomPlugin` in a window 1200 pixels wide
+fn initial_scale(cells_across: f32, cam: PanCam) -> f32 {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(InitialZoomPlugin { cells_across });
+    app.world_mut().spawn((
+        Window {
+            resolution: (1200., 800.).into(),
+            ..default()
+        },
+        PrimaryWindow,
+    ));
+    app.world_mut()
+        .spawn((OrthographicProjection::default(), cam));
+    app.update();
+
+    let world = app.world_mut();
+    world.query::<&OrthographicProjection>().single(world).scale
+}
+
+#[test]
+fn initial_zoom_fits_the_cells_across_within_the_limits() {
+    // 30 cells of 20 units over 1200 pixels
+    assert_eq!(initial_scale(30., PanCam::default()), 0.5);
+    let limited = || PanCam {
+        min_scale: 1.,
+        max_scale: Some(2.),
+        ..default()
+    };
+    assert_eq!(initial_scale(30., limited()), 1.);
+    assert_eq!(initial_scale(600., limited()), 2.);
+}
+
+/// App remembering the view in `path`, with a camera framed at the default view
+fn view_app(path: &std::path::Path) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(RememberViewPlugin {
+            path: path.to_owned(),
+        });
+    app.world_mut().spawn((
+        Transform::default(),
+        OrthographicProjection::default(),
+        PanCam::default(),
+    ));
+    app
+}
+
+fn view(app: &mut App) -> SavedView {
+    let world = app.world_mut();
+    let (transform, projection) = world
+        .query::<(&Transform, &OrthographicProjection)>()
+        .single(world);
+    SavedView {
+        translation: transform.translation.truncate(),
+        scale: projection.scale,
+    }
+}
+
+#[test]
+fn remembered_view_comes_back_on_the_next_start() {
+    let path = std::env::temp_dir().join(format!("langtons-ant-view-{}.toml", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let framed = SavedView {
+        translation: Vec2::new(-123.25, 4567.5),
+        scale: 0.37,
+    };
+
+    // Nothing saved yet, the default view stays
+    let mut app = view_app(&path);
+    app.update();
+    assert_eq!(view(&mut app).translation, Vec2::ZERO);
+    let world = app.world_mut();
+    let (mut transform, mut projection) = world
+        .query::<(&mut Transform, &mut OrthographicProjection)>()
+        .single_mut(world);
+    transform.translation = framed.translation.extend(0.);
+    projection.scale = framed.scale;
+    app.world_mut().send_event(AppExit::Success);
+    app.update();
+
+    let mut app = view_app(&path);
+    app.update();
+    let restored = view(&mut app);
+    std::fs::remove_file(&path).unwrap();
+    assert!(
+        restored.translation.distance(framed.translation) < 1e-3,
+        "{restored:?}"
+    );
+    assert!((restored.scale - framed.scale).abs() < 1e-6, "{restored:?}");
+    assert_eq!(SavedView::parse(&framed.to_toml()).unwrap(), framed);
+}
+
+#[test]
+fn tiles_match_the_grid_after_every_frame() {
+    let mut app = headless_app("LLRR");
+    // Ten fixed ticks per update, the tiles catch up once per frame
+    app.insert_resource(Time::<Fixed>::from_hz(600.));
+    for _ in 0..60 {
+        app.update();
+
+        let world = app.world_mut();
+        let colors: Vec<(Entity, Color)> = world
+            .query_filtered::<(Entity, &Handle<ColorMaterial>), With<Tile>>()
+            .iter(world)
+            .map(|(tile, handle)| {
+                let material = world.resource::<Assets<ColorMaterial>>().get(handle);
+                (tile, material.unwrap().color)
+            })
+            .collect();
+        let board = world.resource::<Board>();
+        let pattern = world.resource::<Pattern>();
+        assert_eq!(colors.len(), board.grid.len());
+        assert!(board.dirty.is_empty());
+        for (cell, state) in board.grid.cells() {
+            let tile = board.tiles[&cell];
+            let (_, color) = colors.iter().find(|(entity, _)| *entity == tile).unwrap();
+            assert_eq!(*color, pattern.colors[state], "tile of {cell}");
+        }
+    }
+    assert!(app.world().resource::<StepCounter>().0 >= 500);
+}
+
+#[test]
+fn ants_get_their_own_tint_label_and_step_count() {
+    let mut app = headless_app("RL");
+    app.add_plugins((
+        AntKeysPlugin::<SquareLattice>::default(),
+        AntLabelsPlugin { shown: true },
+    ));
+    app.world_mut().spawn((
+        Camera2dBundle {
+            projection: OrthographicProjection {
+                scale: 3.,
+                ..default()
+            },
+            ..default()
+        },
+        PanCam::default(),
+    ));
+    for _ in 0..5 {
+        app.update();
+    }
+    tap(&mut app, KeyCode::KeyA);
+    for _ in 0..5 {
+        app.update();
+    }
+
+    let world = app.world_mut();
+    let steps = world.resource::<StepCounter>().0;
+    let mut ants: Vec<(usize, Color, u64)> = world
+        .query::<(&AntId, &Sprite, &AntSteps)>()
+        .iter(world)
+        .map(|(id, sprite, steps)| (id.0, sprite.color, steps.0))
+        .collect();
+    ants.sort_by_key(|(id, _, _)| *id);
+    assert_eq!(ants.len(), 2);
+    assert_eq!(ants[0].1, Color::WHITE);
+    assert_eq!(ants[1].1, ant_tint(1));
+    assert_ne!(ants[0].1, ants[1].1);
+    assert_eq!(ants[0].2, steps);
+    assert!(
+        ants[1].2 > 0 && ants[1].2 < steps,
+        "{} of {steps}",
+        ants[1].2
+    );
+
+    let mut labels: Vec<(String, f32)> = world
+        .query_filtered::<(&Text, &Transform), With<AntLabel>>()
+        .iter(world)
+        .map(|(text, transform)| (text.sections[0].value.clone(), transform.scale.x))
+        .collect();
+    labels.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(labels, [("0".to_owned(), 3.), ("1".to_owned(), 3.)]);
+
+    tap(&mut app, KeyCode::KeyL);
+    let world = app.world_mut();
+    assert!(world
+        .query_filtered::<&Visibility, With<AntLabel>>()
+        .iter(world)
+        .all(|visibility| *visibility == Visibility::Hidden));
+
+    app.world_mut().send_event(ResetBoard);
+    app.update();
+    let world = app.world_mut();
+    let steps: Vec<u64> = world
+        .query::<&AntSteps>()
+        .iter(world)
+        .map(|steps| steps.0)
+        .collect();
+    assert!(steps.iter().all(|steps| *steps <= 1), "{steps:?}");
+}
+
+#[test]
+fn hidden_ants_stay_hidden_across_resets_and_pauses() {
+    let mut app = headless_app("RL");
+    app.add_plugins(AntKeysPlugin::<SquareLattice>::default());
+    app.world_mut()
+        .spawn((Camera2dBundle::default(), PanCam::default()));
+    app.update();
+    let visibilities = |app: &mut App| {
+        let world = app.world_mut();
+        world
+            .query_filtered::<&Visibility, With<AntId>>()
+            .iter(world)
+            .copied()
+            .collect::<Vec<_>>()
+    };
+
+    tap(&mut app, KeyCode::KeyH);
+    tap(&mut app, KeyCode::KeyA);
+    app.world_mut().send_event(TogglePause);
+    app.world_mut().send_event(ResetBoard);
+    app.update();
+    app.world_mut().send_event(TogglePause);
+    tap(&mut app, KeyCode::KeyA);
+    assert_eq!(visibilities(&mut app), [Visibility::Hidden; 2]);
+
+    tap(&mut app, KeyCode::KeyH);
+    assert_eq!(visibilities(&mut app), [Visibility::Inherited; 2]);
+}
+
+#[test]
+fn tile_cap_forgets_the_least_recently_visited_cells() {
+    let mut app = headless_app("RL");
+    app.insert_resource(TileCap {
+        max: Some(100),
+        evicted: 0,
+    });
+    for _ in 0..2000 {
+        app.update();
+
+        let world = app.world_mut();
+        let tiles = world.query::<&Tile>().iter(world).count();
+        let steps = world.resource::<StepCounter>().0;
+        let board = world.resource::<Board>();
+        if steps == 0 {
+            continue;
+        }
+        assert!(board.grid.len() <= 100);
+        assert_eq!(tiles, board.grid.len());
+        assert_eq!(board.last_visit.len(), board.grid.len());
+        assert_eq!(board.last_visit.values().max(), Some(&steps));
+        let bounds = board.grid.bounds().unwrap();
+        assert!(board.grid.cells().all(|(cell, _)| bounds.contains(cell)));
+    }
+    assert!(app.world().resource::<TileCap>().evicted > 0);
+
+    app.world_mut().send_event(ResetBoard);
+    app.update();
+    assert_eq!(app.world().resource::<TileCap>().evicted, 0);
+}
+
+#[test]
+fn shift_wheel_changes_the_rate_instead_of_zooming() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, PanCamPlugin, WheelRatePlugin))
+        .init_resource::<ButtonInput<KeyCode>>()
+        .init_resource::<ButtonInput<MouseButton>>()
+        .add_event::<MouseWheel>()
+        .insert_resource(Time::<Fixed>::from_hz(60.));
+    let entity = app.world_mut().spawn(Window::default()).id();
+    app.world_mut().entity_mut(entity).insert(PrimaryWindow);
+    app.world_mut().spawn((
+        OrthographicProjection::default(),
+        Transform::default(),
+        PanCam::default(),
+    ));
+
+    let scroll = |app: &mut App, lines: f32| {
+        app.world_mut().send_event(MouseWheel {
+            unit: MouseScrollUnit::Line,
+            x: 0.,
+            y: lines,
+            window: entity,
+        });
+        app.update();
+        let world = app.world_mut();
+        let scale = world.query::<&OrthographicProjection>().single(world).scale;
+        let rate = 1. / world.resource::<Time<Fixed>>().timestep().as_secs_f64();
+        (scale, rate)
+    };
+
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::ShiftLeft);
+    let (scale, rate) = scroll(&mut app, 2.);
+    assert_eq!(scale, 1.);
+    assert!((rate - 60. * RATE_STEP * RATE_STEP).abs() < 1e-3, "{rate}");
+    // Clamped to the slowest rate
+    let (_, rate) = scroll(&mut app, -100.);
+    assert!((rate - 0.1).abs() < 1e-6, "{rate}");
+
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .release(KeyCode::ShiftLeft);
+    let (scale, rate) = scroll(&mut app, 1.);
+    assert!(scale < 1.);
+    assert!((rate - 0.1).abs() < 1e-6, "{rate}");
+}
+
+#[test]
+fn camera_lock_keeps_the_wheel_from_zooming_until_unlocked() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, PanCamPlugin, CameraKeysPlugin))
+        .init_resource::<ButtonInput<KeyCode>>()
+        .init_resource::<PatternInput>()
+        .init_resource::<ButtonInput<MouseButton>>()
+        .add_event::<MouseWheel>();
+    let entity = app.world_mut().spawn(Window::default()).id();
+    app.world_mut().entity_mut(entity).insert(PrimaryWindow);
+    app.world_mut().spawn((
+        OrthographicProjection::default(),
+        Transform::default(),
+        PanCam::default(),
+    ));
+
+    let scroll = |app: &mut App| {
+        app.world_mut().send_event(MouseWheel {
+            unit: MouseScrollUnit::Line,
+            x: 0.,
+            y: 1.,
+            window: entity,
+        });
+        app.update();
+        let world = app.world_mut();
+        world.query::<&OrthographicProjection>().single(world).scale
+    };
+    let indicator = |app: &mut App| {
+        let world = app.world_mut();
+        *world.query::<(&Text, &Visibility)>().single(world).1
+    };
+
+    app.update();
+    assert_eq!(indicator(&mut app), Visibility::Hidden);
+    tap(&mut app, KeyCode::KeyZ);
+    assert_eq!(indicator(&mut app), Visibility::Inherited);
+    assert_eq!(scroll(&mut app), 1.);
+
+    tap(&mut app, KeyCode::KeyZ);
+    assert_eq!(indicator(&mut app), Visibility::Hidden);
+    assert!(scroll(&mut app) < 1.);
+}
+
+#[test]
+fn minimap_shows_the_ant_and_stops_repainting_while_hidden() {
+    let mut app = headless_app("RL");
+    app.add_plugins(MinimapPlugin::<SquareLattice>::new(true));
+    for _ in 0..60 {
+        app.update();
+    }
+
+    let texels = |app: &mut App| {
+        let world = app.world_mut();
+        let handle = world.resource::<Minimap>().image.clone();
+        world
+            .resource::<Assets<Image>>()
+            .get(&handle)
+            .unwrap()
+            .data
+            .clone()
+    };
+    let painted = texels(&mut app);
+    let colors: Vec<&[u8]> = painted.chunks_exact(4).collect();
+    assert!(colors.contains(&&[255, 0, 0, 255][..]), "no ant marker");
+    let cell_color = app.world().resource::<Pattern>().colors[1]
+        .to_srgba()
+        .to_u8_array();
+    assert!(colors.contains(&&cell_color[..]), "no painted cell");
+
+    tap(&mut app, KeyCode::KeyN);
+    let world = app.world_mut();
+    let visibility = *world.query::<(&UiImage, &Visibility)>().single(world).1;
+    assert_eq!(visibility, Visibility::Hidden);
+    for _ in 0..60 {
+        app.update();
+    }
+    assert_eq!(texels(&mut app), painted);
+}
+
+#[test]
+fn both_schedulers_step_the_same_over_the_same_time() {
+    let steps = |scheduler| {
+        let mut app = headless_app("RL");
+        // Frames longer than a tick and out of step with it, so some frames run two
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1. / 45.,
+        )))
+        .add_plugins(SchedulerPlugin { scheduler });
+        for _ in 0..90 {
+            app.update();
+        }
+        app.world().resource::<StepCounter>().0
+    };
+    let fixed = steps(Scheduler::Fixed);
+    // Two seconds at 60 Hz, give or take the first frame without a tick
+    assert!((118..=120).contains(&fixed), "{fixed}");
+    assert_eq!(steps(Scheduler::Accumulator), fixed);
+}
+
+#[test]
+fn max_steps_pauses_once_and_resuming_carries_on() {
+    let mut app = headless_app("RL");
+    app.insert_resource(MaxSteps(Some(7)))
+        .insert_resource(StepsPerTick(5));
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(app.world().resource::<StepCounter>().0, 7);
+    assert_eq!(
+        *app.world().resource::<State<AppState>>().get(),
+        AppState::Paused
+    );
+
+    app.world_mut().send_event(TogglePause);
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(app.world().resource::<StepCounter>().0 > 7);
+}
+
+#[test]
+fn shared_command_replaces_the_run_options_and_keeps_the_rest() {
+    let pattern = Pattern::parse("R*2L:red".to_owned(), &mut SimRng::seeded(0).0).unwrap();
+    let args: Vec<String> = [
+        "-pLLRR", "--grid", "hex", "--seed=5", "--stdin", "-r", "120", "--trail", "--colors",
+        "blue",
+    ]
+    .map(String::from)
+    .to_vec();
+    let green = pattern.colors[0].to_srgba().to_hex();
+    assert_eq!(
+        share_command(&args, &pattern, 42, 59.999_999_9, 1234),
+        format!(
+            "langtons-ant --grid hex --trail --pattern 'R*2L' --colors '{green},#FF0000' \
+             --seed 42 --rate 60 --max-steps 1234"
+        )
+    );
+}
+
+#[test]
+fn menu_holds_the_run_until_a_pattern_starts() {
+    let mut app = headless_app("RL");
+    app.add_event::<KeyboardInput>().add_plugins(MenuPlugin);
+    for _ in 0..5 {
+        app.update();
+    }
+    let state = |app: &App| app.world().resource::<State<AppState>>().get().clone();
+    let steps = |app: &App| app.world().resource::<StepCounter>().0;
+    assert!(steps(&app) > 0);
+
+    // Escape resets the board and nothing steps behind the menu
+    tap(&mut app, KeyCode::Escape);
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_eq!(state(&app), AppState::Menu);
+    assert_eq!(steps(&app), 0);
+
+    let world = app.world_mut();
+    let (button, _) = world
+        .query::<(Entity, &MenuButton)>()
+        .iter(world)
+        .find(|(_, button)| **button == MenuButton::Preset(2))
+        .expect("a button per preset");
+    world.entity_mut(button).insert(Interaction::Pressed);
+    app.update();
+    assert_eq!(app.world().resource::<Menu>().text, "LLRR");
+
+    let press = |app: &mut App, logical_key| {
+        app.world_mut().send_event(KeyboardInput {
+            key_code: KeyCode::Enter,
+            logical_key,
+            state: ButtonState::Pressed,
+            window: Entity::PLACEHOLDER,
+        });
+        app.update();
+        app.update();
+    };
+    // A pattern that can't run keeps the menu up with the reason
+    app.world_mut().resource_mut::<Menu>().text = "RRR".to_owned();
+    press(&mut app, Key::Enter);
+    assert_eq!(state(&app), AppState::Menu);
+    assert!(app.world().resource::<Menu>().error.is_some());
+
+    app.world_mut().resource_mut::<Menu>().text = "LLRR".to_owned();
+    press(&mut app, Key::Enter);
+    assert_eq!(state(&app), AppState::Running);
+    assert_eq!(app.world().resource::<Pattern>().to_string(), "LLRR");
+    let world = app.world_mut();
+    assert_eq!(world.query::<&MenuButton>().iter(world).count(), 0);
+    app.update();
+    assert!(steps(&app) > 0);
+}
+
+/// App running `pattern` with the start menu open
+fn menu_app(pattern: &str) -> App {
+    let mut app = headless_app(pattern);
+    app.add_event::<KeyboardInput>().add_plugins(MenuPlugin);
+    app.update();
+    tap(&mut app, KeyCode::Escape);
+    app.update();
+    app
+}
+
+/// Types `text` on the menu and presses Enter, returning the state the app ends up in
+fn start_from_menu(app: &mut App, text: String) -> AppState {
+    app.world_mut().resource_mut::<Menu>().text = text;
+    app.world_mut().send_event(KeyboardInput {
+        key_code: KeyCode::Enter,
+        logical_key: Key::Enter,
+        state: ButtonState::Pressed,
+        window: Entity::PLACEHOLDER,
+    });
+    app.update();
+    app.update();
+    app.world().resource::<State<AppState>>().get().clone()
+}
+
+#[test]
+fn runs_started_from_the_menu_warm_up_in_the_given_colors() {
+    const WARMUP: u64 = 500;
+    let mut app = headless_app("RL");
+    app.add_event::<KeyboardInput>()
+        .add_plugins(MenuPlugin)
+        // Slow enough that no tick follows the warmup within the test
+        .insert_resource(Time::<Fixed>::from_hz(0.5))
+        .insert_resource(State::new(AppState::Menu))
+        .insert_resource(Warmup(WARMUP))
+        .insert_resource(Menu {
+            colors: Some("red green blue".to_owned()),
+            ..default()
+        });
+    app.update();
+    // Nothing warms up behind the menu
+    assert_eq!(app.world().resource::<StepCounter>().0, 0);
+    assert_eq!(app.world().resource::<Board>().grid.len(), 0);
+
+    assert_eq!(
+        start_from_menu(&mut app, "RLR".to_owned()),
+        AppState::Running
+    );
+    let pattern = app.world().resource::<Pattern>().clone();
+    let colors: Vec<String> = pattern
+        .colors
+        .iter()
+        .map(|color| color.to_srgba().to_hex())
+        .collect();
+    assert_eq!(colors, ["#FF0000", "#008000", "#0000FF"]);
+    assert_eq!(app.world().resource::<StepCounter>().0, WARMUP);
+
+    let mut grid = GridState::default();
+    let mut ant = AntState::<SquareLattice>::default();
+    simulate(&mut grid, &mut ant, &pattern, WARMUP);
+    assert_eq!(ants(&mut app), [(0, ant.cell)]);
+    app.update();
+    let world = app.world_mut();
+    assert_eq!(world.query::<&AntSteps>().single(world).0, WARMUP);
+    assert_eq!(
+        world.resource::<Board>().grid.content_hash(),
+        grid.content_hash()
+    );
+    assert_eq!(world.query::<&Tile>().iter(world).count(), grid.len());
+}
+
+#[test]
+fn runtime_patterns_past_the_chunked_limit_are_turned_away() {
+    let mut app = menu_app("RL");
+    let board = Board::chunked();
+    app.insert_resource(PatternLimits::new::<SquareLattice>(
+        DEFAULT_MAX_STATES,
+        &board.grid,
+    ))
+    .insert_resource(board);
+    let alternating = |states: usize| (0..states).map(|i| ["R", "L"][i % 2]).collect();
+
+    // State 255 would be packed as 0, an unvisited cell
+    assert_eq!(start_from_menu(&mut app, alternating(256)), AppState::Menu);
+    let error = app.world().resource::<Menu>().error.clone().unwrap();
+    assert!(error.contains("chunked"), "{error}");
+    assert_eq!(app.world().resource::<Pattern>().turns.len(), 2);
+
+    assert_eq!(
+        start_from_menu(&mut app, alternating(255)),
+        AppState::Running
+    );
+    assert_eq!(app.world().resource::<Pattern>().turns.len(), 255);
+}
+
+#[test]
+fn runtime_patterns_with_turns_off_the_lattice_are_turned_away() {
+    let mut app = menu_app("RL");
+    assert_eq!(
+        start_from_menu(&mut app, "R45 L".to_owned()),
+        AppState::Menu
+    );
+    let error = app.world().resource::<Menu>().error.clone().unwrap();
+    assert!(error.contains("R45"), "{error}");
+
+    assert_eq!(
+        start_from_menu(&mut app, "R90 L".to_owned()),
+        AppState::Running
+    );
+}
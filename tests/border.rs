@@ -0,0 +1,90 @@
+//! Ants walking into the world border.
+
+use bevy::math::{IVec2, UVec2};
+use langtons_ant::{
+    lattice::{Direction, SquareLattice},
+    pattern::Pattern,
+    sim::{step, AntState, Border, EdgeMode, GridState},
+    SimRng,
+};
+
+/// Cells from (-2, -2) to (2, 2)
+fn bounded(mode: EdgeMode) -> GridState {
+    GridState::default().with_border(Border::new(UVec2::new(5, 5), mode))
+}
+
+fn ant(x: i32, y: i32, heading: Direction) -> AntState<SquareLattice> {
+    AntState {
+        cell: IVec2::new(x, y),
+        heading,
+        mirrored: false,
+        inverse: false,
+    }
+}
+
+fn pattern(text: &str) -> Pattern {
+    Pattern::parse(text.to_owned(), &mut SimRng::seeded(0).0).unwrap()
+}
+
+#[test]
+fn stop_keeps_the_ant_on_its_edge_cell_facing_out() {
+    let pattern = pattern("RL");
+    let mut grid = bounded(EdgeMode::Stop);
+    // Turning right on the east edge points it out of the world
+    let mut ant = ant(2, 0, Direction::North);
+    step(&mut grid, &mut ant, &pattern);
+    assert_eq!(ant.cell, IVec2::new(2, 0));
+    assert_eq!(ant.heading, Direction::East);
+    assert_eq!(grid.get(IVec2::new(2, 0)), Some(1));
+
+    // The rule still applies to the cell it stayed on
+    step(&mut grid, &mut ant, &pattern);
+    assert_eq!(ant.cell, IVec2::new(2, 1));
+    assert_eq!(ant.heading, Direction::North);
+    assert_eq!(grid.get(IVec2::new(2, 0)), Some(0));
+}
+
+#[test]
+fn bounce_turns_the_ant_around_on_its_edge_cell() {
+    let pattern = pattern("RL");
+    let mut grid = bounded(EdgeMode::Bounce);
+    let mut ant = ant(2, 0, Direction::North);
+    step(&mut grid, &mut ant, &pattern);
+    assert_eq!(ant.cell, IVec2::new(2, 0));
+    assert_eq!(ant.heading, Direction::West);
+    assert_eq!(grid.get(IVec2::new(2, 0)), Some(1));
+
+    step(&mut grid, &mut ant, &pattern);
+    assert_eq!(ant.cell, IVec2::new(2, -1));
+    assert_eq!(ant.heading, Direction::South);
+}
+
+#[test]
+fn long_moves_end_on_the_last_cell_inside() {
+    let pattern = pattern("R*3L");
+    for (mode, heading) in [
+        (EdgeMode::Stop, Direction::East),
+        (EdgeMode::Bounce, Direction::West),
+    ] {
+        let mut grid = bounded(mode);
+        let mut ant = ant(0, 0, Direction::North);
+        step(&mut grid, &mut ant, &pattern);
+        assert_eq!(ant.cell, IVec2::new(2, 0), "{mode:?}");
+        assert_eq!(ant.heading, heading, "{mode:?}");
+    }
+}
+
+#[test]
+fn ants_never_leave_the_border() {
+    let pattern = pattern("RL");
+    for mode in [EdgeMode::Stop, EdgeMode::Bounce] {
+        let mut grid = bounded(mode);
+        let border = grid.border().unwrap();
+        let mut ant = AntState::<SquareLattice>::default();
+        for _ in 0..10_000 {
+            step(&mut grid, &mut ant, &pattern);
+            assert!(border.contains(ant.cell), "{mode:?}: {}", ant.cell);
+        }
+        assert!(grid.cells().all(|(cell, _)| border.contains(cell)));
+    }
+}
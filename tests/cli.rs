@@ -0,0 +1,384 @@
+//! Runs the binary in the modes that finish before opening a window.
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use langtons_ant::{
+    lattice::{HexLattice, SquareLattice},
+    pattern::{Pattern, MANY_STATES},
+    presets::{preset_rule, PRESETS},
+    sim::{simulate, AntState, GridState},
+    SimRng,
+};
+
+#[test]
+fn out_of_range_rates_are_rejected() {
+    for rate in ["0", "-1", "NaN", "inf", "100001"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_langtons-ant"))
+            .arg(format!("--rate={rate}"))
+            .output()
+            .unwrap();
+
+        assert!(!output.status.success(), "--rate {rate} was accepted");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("rate must be above 0 and at most 100000"),
+            "unexpected error for {rate}: {stderr}"
+        );
+    }
+}
+
+#[test]
+fn count_only_prints_cells_and_bounding_box() {
+    let count = |steps: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_langtons-ant"))
+            .args([
+                "--count-only",
+                "--steps",
+                steps,
+                "--pattern",
+                "RL",
+                "--quiet",
+            ])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert!(output.stderr.is_empty());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    // Four right turns paint a 2x2 square
+    assert_eq!(count("4"), "4 2x2\n");
+
+    let mut grid = GridState::default();
+    let mut ant = AntState::<SquareLattice>::default();
+    let pattern = Pattern::parse("RL".to_owned(), &mut SimRng::seeded(0).0).unwrap();
+    simulate(&mut grid, &mut ant, &pattern, 500);
+    let size = grid.bounds().unwrap().size() + 1;
+    assert_eq!(
+        count("500"),
+        format!("{} {}x{}\n", grid.len(), size.x, size.y)
+    );
+}
+
+#[test]
+fn count_only_reports_progress_up_to_the_last_step() {
+    let output = Command::new(env!("CARGO_BIN_EXE_langtons-ant"))
+        .args(["--count-only", "--steps", "1000"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let (lines, summary) = stderr.split_at(stderr.find("pattern:").unwrap());
+    let lines: Vec<&str> = lines.lines().collect();
+    assert_eq!(lines.len(), 20);
+    assert!(lines[0].starts_with("step 50/1000 (5%)"), "{}", lines[0]);
+    assert!(
+        lines[19].starts_with("step 1000/1000 (100%)"),
+        "{}",
+        lines[19]
+    );
+    assert!(summary.contains("steps:        1000\n"), "{summary}");
+}
+
+#[test]
+fn print_symmetry_prints_three_fractions() {
+    let symmetry = |mirror: bool| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_langtons-ant"));
+        command.args([
+            "--print-symmetry",
+            "--steps",
+            "1000",
+            "--pattern",
+            "RLR",
+            "--quiet",
+        ]);
+        if mirror {
+            command.arg("--mirror");
+        }
+        let output = command.output().unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert_eq!(symmetry(true), "1.000 1.000 1.000\n");
+    let scores: Vec<f64> = symmetry(false)
+        .split_whitespace()
+        .map(|score| score.parse().unwrap())
+        .collect();
+    assert_eq!(scores.len(), 3);
+    assert!(
+        scores.iter().all(|score| (0. ..=1.).contains(score)),
+        "{scores:?}"
+    );
+}
+
+#[test]
+fn print_hash_is_stable_and_matches_the_library() {
+    let hash = |steps: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_langtons-ant"))
+            .args([
+                "--print-hash",
+                "--steps",
+                steps,
+                "--pattern",
+                "RL",
+                "--quiet",
+            ])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let first = hash("500");
+    assert_eq!(hash("500"), first);
+    assert_ne!(hash("501"), first);
+
+    // Storage doesn't matter, only the painted cells and their states
+    let pattern = Pattern::parse("RL".to_owned(), &mut SimRng::seeded(0).0).unwrap();
+    for mut grid in [GridState::default(), GridState::chunked()] {
+        let mut ant = AntState::<SquareLattice>::default();
+        simulate(&mut grid, &mut ant, &pattern, 500);
+        assert_eq!(first, format!("{:016x}\n", grid.content_hash()));
+    }
+}
+
+#[test]
+fn hex_grid_runs_headless_like_the_library() {
+    let output = Command::new(env!("CARGO_BIN_EXE_langtons-ant"))
+        .args([
+            "--grid",
+            "hex",
+            "--pattern",
+            "RLLR",
+            "--steps",
+            "2000",
+            "--quiet",
+        ])
+        .args(["--print-hash", "--count-only"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let pattern = Pattern::parse("RLLR".to_owned(), &mut SimRng::seeded(0).0).unwrap();
+    let mut grid = GridState::default();
+    let mut ant = AntState::<HexLattice>::default();
+    simulate(&mut grid, &mut ant, &pattern, 2000);
+    let size = grid.bounds().unwrap().size() + 1;
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        format!(
+            "{} {}x{}\n{:016x}\n",
+            grid.len(),
+            size.x,
+            size.y,
+            grid.content_hash()
+        )
+    );
+}
+
+#[test]
+fn presets_select_their_rule() {
+    assert_eq!(preset_rule("classic"), Some("RL"));
+    assert_eq!(preset_rule("nope"), None);
+
+    let hash = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_langtons-ant"))
+            .args(["--print-hash", "--steps", "300", "--quiet"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        output.stdout
+    };
+    assert_eq!(hash(&["--preset", "classic"]), hash(&["--pattern", "RL"]));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_langtons-ant"))
+        .arg("--list-presets")
+        .output()
+        .unwrap();
+    let listed = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(listed.lines().count(), PRESETS.len());
+    assert!(listed.lines().next().unwrap().starts_with("classic"));
+}
+
+#[test]
+fn dash_reads_the_pattern_from_stdin() {
+    let hash = |args: &[&str], stdin: &str| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_langtons-ant"))
+            .args(["--print-hash", "--steps", "300", "--quiet"])
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(stdin.as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success());
+        output.stdout
+    };
+
+    let expected = hash(&["--pattern", "LLRR"], "");
+    assert_eq!(hash(&["--pattern", "-"], "LLRR # symmetric\r\n"), expected);
+    assert_eq!(hash(&["--stdin"], "  LLRR\n\n"), expected);
+}
+
+#[test]
+fn exported_runs_resume_where_they_stopped() {
+    let dir = std::env::temp_dir().join(format!("langtons-ant-rle-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (half, again) = (dir.join("half.rle"), dir.join("again.rle"));
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_langtons-ant"))
+            .arg("--quiet")
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{args:?} failed");
+        output.stdout
+    };
+    let path = |path: &std::path::Path| path.to_str().unwrap().to_owned();
+
+    run(&[
+        "--pattern",
+        "LLRR",
+        "--mirror",
+        "--steps",
+        "600",
+        "--export-rle",
+        &path(&half),
+    ]);
+    let resumed = run(&[
+        "--print-hash",
+        "--steps",
+        "600",
+        "--import-rle",
+        &path(&half),
+    ]);
+    let straight = run(&[
+        "--print-hash",
+        "--steps",
+        "1200",
+        "--pattern",
+        "LLRR",
+        "--mirror",
+    ]);
+    assert_eq!(resumed, straight);
+
+    // Reading and writing again changes nothing
+    run(&[
+        "--steps",
+        "0",
+        "--import-rle",
+        &path(&half),
+        "--export-rle",
+        &path(&again),
+    ]);
+    assert_eq!(
+        std::fs::read_to_string(&half).unwrap(),
+        std::fs::read_to_string(&again).unwrap()
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn unseeded_runs_report_a_seed_that_repeats_them() {
+    let seed_of = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_langtons-ant"))
+            .args(["--count-only", "--steps", "10"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        let line = stderr
+            .lines()
+            .find(|line| line.starts_with("seed:"))
+            .unwrap();
+        line["seed:".len()..].trim().parse::<u64>().unwrap()
+    };
+
+    let seed = seed_of(&[]);
+    assert_ne!(seed_of(&[]), seed, "two unseeded runs drew the same seed");
+    assert_eq!(seed_of(&["--seed", &seed.to_string()]), seed);
+
+    // The colors only come from the seed
+    let colors = || {
+        Pattern::parse("RLRLLR".to_owned(), &mut SimRng::seeded(seed).0)
+            .unwrap()
+            .colors
+    };
+    assert_eq!(colors(), colors());
+}
+
+#[test]
+fn validate_lists_the_states_of_valid_patterns() {
+    let output = Command::new(env!("CARGO_BIN_EXE_langtons-ant"))
+        .args(["--validate", "--pattern", "R*2L:red", "--pattern-b", "LLRR"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 1 + 2 + 1 + 4, "{stdout}");
+    assert_eq!(lines[0], "R*2L: 2 states");
+    assert!(lines[1].starts_with("   0  R*2"), "{stdout}");
+    assert_eq!(lines[2], "   1  L       #FF0000");
+    assert_eq!(lines[3], "LLRR: 4 states");
+}
+
+#[test]
+fn patterns_with_many_states_are_warned_about_on_stderr() {
+    let stderr = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_langtons-ant"))
+            .args(["--pattern", &"RL".repeat(MANY_STATES / 2 + 1)])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{args:?} was rejected");
+        String::from_utf8(output.stderr).unwrap()
+    };
+    for args in [&["--validate"][..], &["--count-only", "--steps", "10"]] {
+        let stderr = stderr(args);
+        assert!(
+            stderr.contains("warning: the pattern has 66 states"),
+            "{args:?}: {stderr}"
+        );
+    }
+    assert!(!stderr(&["--validate", "--quiet"]).contains("warning"));
+}
+
+#[test]
+fn validate_fails_on_invalid_input() {
+    for (args, message) in [
+        (&["--pattern", "RRR"][..], "turns the same way"),
+        (
+            &["--pattern", "RL", "--colors", "red nope"],
+            "unknown color `nope`",
+        ),
+        (&["--pattern", "R45L", "--grid", "hex"], "R45"),
+        (&["--pattern", "RL", "--pattern-b", ""], "at least 2"),
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_langtons-ant"))
+            .arg("--validate")
+            .args(args)
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(1), "{args:?} was accepted");
+        assert!(output.stdout.is_empty());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains(message),
+            "unexpected error for {args:?}: {stderr}"
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! Collision rules between ants stepping on the same grid.
+
+use bevy::math::IVec2;
+use langtons_ant::{
+    lattice::{Direction, SquareLattice},
+    pattern::Pattern,
+    sim::{step_colliding, AntState, Collision, GridState},
+    SimRng,
+};
+
+fn ant(x: i32, y: i32, heading: Direction) -> AntState<SquareLattice> {
+    AntState {
+        cell: IVec2::new(x, y),
+        heading,
+        mirrored: false,
+        inverse: false,
+    }
+}
+
+/// On fresh cells both ants turn right: one heads east into the other's cell, the
+/// other heads west into the first one's
+fn head_on() -> [AntState<SquareLattice>; 2] {
+    [ant(0, 0, Direction::North), ant(1, 0, Direction::South)]
+}
+
+/// Both ants turn right into the empty cell (1, 0), arriving from the west and the north
+fn same_cell() -> [AntState<SquareLattice>; 2] {
+    [ant(0, 0, Direction::North), ant(1, 1, Direction::East)]
+}
+
+/// Steps `ants` once on an empty grid, returning where they end up and who was annihilated
+fn step(
+    mut ants: [AntState<SquareLattice>; 2],
+    collision: Collision,
+) -> ([AntState<SquareLattice>; 2], Vec<usize>) {
+    let pattern = Pattern::parse("RL".to_owned(), &mut SimRng::seeded(0).0).unwrap();
+    let mut grid = GridState::default();
+    let (deltas, annihilated) = step_colliding(&mut grid, &mut ants, &pattern, collision);
+    assert_eq!(deltas.len(), 2, "every ant paints its cell");
+    assert_eq!(grid.len(), 2);
+    (ants, annihilated)
+}
+
+#[test]
+fn pass_lets_ants_swap_cells() {
+    let (ants, annihilated) = step(head_on(), Collision::Pass);
+    assert_eq!(ants[0], ant(1, 0, Direction::East));
+    assert_eq!(ants[1], ant(0, 0, Direction::West));
+    assert!(annihilated.is_empty());
+}
+
+#[test]
+fn pass_lets_ants_share_a_cell() {
+    let (ants, annihilated) = step(same_cell(), Collision::Pass);
+    assert_eq!(ants[0], ant(1, 0, Direction::East));
+    assert_eq!(ants[1], ant(1, 0, Direction::South));
+    assert!(annihilated.is_empty());
+}
+
+#[test]
+fn block_turns_both_ants_walking_into_each_other() {
+    let (ants, annihilated) = step(head_on(), Collision::Block);
+    assert_eq!(ants[0], ant(0, 0, Direction::West));
+    assert_eq!(ants[1], ant(1, 0, Direction::East));
+    assert!(annihilated.is_empty());
+}
+
+#[test]
+fn block_turns_the_later_ant_reaching_a_taken_cell() {
+    let (ants, annihilated) = step(same_cell(), Collision::Block);
+    assert_eq!(ants[0], ant(1, 0, Direction::East));
+    assert_eq!(ants[1], ant(1, 1, Direction::North));
+    assert!(annihilated.is_empty());
+}
+
+#[test]
+fn annihilate_removes_ants_meeting_head_on() {
+    let (_, annihilated) = step(head_on(), Collision::Annihilate);
+    assert_eq!(annihilated, [0, 1]);
+}
+
+#[test]
+fn annihilate_removes_ants_landing_on_the_same_cell() {
+    let (_, annihilated) = step(same_cell(), Collision::Annihilate);
+    assert_eq!(annihilated, [0, 1]);
+}
+
+/// Steps the ants of `same_cell` twice with three states, so a cell changed twice in one
+/// step would end up in state 2 rather than 1
+fn converge_and_step_on(collision: Collision) -> (Vec<AntState<SquareLattice>>, GridState) {
+    let pattern = Pattern::parse("RLR".to_owned(), &mut SimRng::seeded(0).0).unwrap();
+    let mut grid = GridState::default();
+    let mut ants = same_cell().to_vec();
+    for _ in 0..2 {
+        let (_, annihilated) = step_colliding(&mut grid, &mut ants, &pattern, collision);
+        for index in annihilated.into_iter().rev() {
+            ants.remove(index);
+        }
+    }
+    (ants, grid)
+}
+
+#[test]
+fn ants_converging_on_a_cell_change_it_once() {
+    // Both reach (1, 0), then leave it with a single change between them
+    let (ants, grid) = converge_and_step_on(Collision::Pass);
+    assert_eq!(grid.get(IVec2::new(1, 0)), Some(1));
+    assert_eq!(
+        ants,
+        [ant(1, -1, Direction::South), ant(0, 0, Direction::West)]
+    );
+
+    // Only the first gets there, the second turns around on its own cell and steps there
+    // again
+    let (ants, grid) = converge_and_step_on(Collision::Block);
+    assert_eq!(grid.get(IVec2::new(1, 0)), Some(1));
+    assert_eq!(grid.get(IVec2::new(1, 1)), Some(2));
+    assert_eq!(
+        ants,
+        [ant(1, -1, Direction::South), ant(0, 1, Direction::West)]
+    );
+
+    // Both are gone before stepping on it
+    let (ants, grid) = converge_and_step_on(Collision::Annihilate);
+    assert!(ants.is_empty());
+    assert_eq!(grid.get(IVec2::new(1, 0)), None);
+    assert_eq!(grid.len(), 2);
+}
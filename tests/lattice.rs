@@ -0,0 +1,174 @@
+//! Geometry shared by the lattices.
+
+use bevy::math::{IVec2, Vec2};
+use langtons_ant::{
+    lattice::{HexDirection, HexLattice, Lattice, SquareLattice, TriangularLattice},
+    pattern::Turn,
+    TILE_SIZE,
+};
+
+/// Cells around the origin every lattice is checked on
+fn cells() -> impl Iterator<Item = IVec2> {
+    (-3..=3).flat_map(|x| (-3..=3).map(move |y| IVec2::new(x, y)))
+}
+
+/// What every `Lattice` has to get right: turns undo each other and go all the way round,
+/// stepping out and back returns to the cell, and the images and world positions map back
+fn conforms<L: Lattice>() {
+    let full_turn = 360 / L::TURN_DEGREES;
+    for &heading in L::HEADINGS {
+        assert_eq!(L::turn(L::turn(heading, Turn::Left), Turn::Right), heading);
+        assert_eq!(L::turn(L::turn(heading, Turn::Right), Turn::Left), heading);
+        assert_eq!(L::reverse(L::reverse(heading)), heading);
+        assert_ne!(L::reverse(heading), heading);
+
+        // Right turns run through every heading before coming back
+        let mut turned = heading;
+        let mut seen = Vec::new();
+        for _ in 0..full_turn {
+            seen.push(turned);
+            turned = L::turn(turned, Turn::Right);
+        }
+        assert_eq!(turned, heading);
+        assert!(L::HEADINGS.iter().all(|heading| seen.contains(heading)));
+        assert_eq!(
+            L::turn(heading, Turn::Degrees(180)),
+            L::reverse(heading),
+            "{heading:?}"
+        );
+    }
+
+    for cell in cells() {
+        assert_eq!(L::world_to_cell(L::cell_to_world(cell)), cell);
+        for &heading in L::HEADINGS {
+            let next = L::neighbor(cell, heading);
+            assert_ne!(next, cell);
+            assert_eq!(
+                L::neighbor(next, L::reverse(heading)),
+                cell,
+                "{cell} {heading:?}"
+            );
+
+            let (image, image_heading) = L::reflect(cell, heading);
+            assert_eq!(L::reflect(image, image_heading), (cell, heading));
+            let (image, image_heading) = L::half_turn(cell, heading);
+            assert_eq!(L::half_turn(image, image_heading), (cell, heading));
+        }
+    }
+}
+
+/// Every outline is closed and each corner, nudged towards the center, lies in its cell
+fn outlines_enclose_their_cells<L: Lattice>() {
+    for x in -3..=3 {
+        for y in -3..=3 {
+            let cell = IVec2::new(x, y);
+            let outline = L::cell_outline(cell);
+            assert_eq!(outline.first(), outline.last(), "{cell} isn't closed");
+
+            let center = L::cell_to_world(cell);
+            for corner in &outline {
+                let inside: Vec2 = corner.lerp(center, 0.1);
+                assert_eq!(L::world_to_cell(inside), cell, "corner {corner} of {cell}");
+            }
+        }
+    }
+}
+
+#[test]
+fn square_outlines() {
+    outlines_enclose_their_cells::<SquareLattice>();
+}
+
+#[test]
+fn hex_outlines() {
+    outlines_enclose_their_cells::<HexLattice>();
+}
+
+#[test]
+fn triangular_outlines() {
+    outlines_enclose_their_cells::<TriangularLattice>();
+}
+
+#[test]
+fn square_conformance() {
+    conforms::<SquareLattice>();
+}
+
+#[test]
+fn hex_conformance() {
+    conforms::<HexLattice>();
+}
+
+#[test]
+fn triangular_conformance() {
+    conforms::<TriangularLattice>();
+}
+
+#[test]
+fn six_turns_the_same_way_circle_a_triangle_vertex() {
+    for turn in [Turn::Left, Turn::Right] {
+        for start in cells() {
+            let mut cell = start;
+            let mut heading = TriangularLattice::start_heading(start);
+            let mut visited = Vec::new();
+            for _ in 0..6 {
+                heading = TriangularLattice::turn(heading, turn);
+                let next = TriangularLattice::neighbor(cell, heading);
+                // Every move crosses an edge, so the cells share one
+                let distance = TriangularLattice::cell_to_world(next)
+                    .distance(TriangularLattice::cell_to_world(cell));
+                assert!((distance - TriangularLattice::SIDE / 3_f32.sqrt()).abs() < 1e-3);
+                cell = next;
+                visited.push(cell);
+            }
+            assert_eq!(cell, start, "{turn} from {start}");
+            assert_eq!(heading, TriangularLattice::start_heading(start));
+            visited.sort_by_key(|cell| (cell.x, cell.y));
+            visited.dedup();
+            assert_eq!(visited.len(), 6, "{turn} from {start} visits {visited:?}");
+        }
+    }
+}
+
+#[test]
+fn hex_neighbors_surround_the_cell_counter_clockwise() {
+    assert_eq!(
+        HexLattice::HEADINGS
+            .iter()
+            .map(|&heading| HexLattice::neighbor(IVec2::ZERO, heading))
+            .collect::<Vec<_>>(),
+        [
+            IVec2::new(1, 0),
+            IVec2::new(0, 1),
+            IVec2::new(-1, 1),
+            IVec2::new(-1, 0),
+            IVec2::new(0, -1),
+            IVec2::new(1, -1),
+        ]
+    );
+
+    for cell in cells() {
+        let center = HexLattice::cell_to_world(cell);
+        for (index, &heading) in HexLattice::HEADINGS.iter().enumerate() {
+            let neighbor = HexLattice::neighbor(cell, heading);
+            assert_eq!(HexLattice::radius(neighbor - cell), 1);
+
+            // `TILE_SIZE` away, at the heading's angle from east
+            let offset = HexLattice::cell_to_world(neighbor) - center;
+            assert!(
+                (offset.length() - TILE_SIZE).abs() < 1e-3,
+                "{cell} {heading:?}"
+            );
+            let angle = (index as f32 * 60.).to_radians();
+            assert!(offset.normalize().distance(Vec2::from_angle(angle)) < 1e-4);
+
+            // Just past the shared edge is the neighbour
+            let across = center + offset * 0.6;
+            assert_eq!(HexLattice::world_to_cell(across), neighbor);
+        }
+    }
+    assert_eq!(
+        HexLattice::neighbor(IVec2::new(2, -3), HexDirection::NorthWest),
+        IVec2::new(1, -2)
+    );
+}
@@ -0,0 +1,480 @@
+//! Pattern parsing and state colors.
+
+use bevy::{
+    color::palettes::basic::{BLUE, GREEN, RED},
+    prelude::*,
+};
+use langtons_ant::{
+    board::initial_ants,
+    lattice::{Direction, HexLattice, SquareLattice, TriangularLattice},
+    palette::{check_cvd_safe, Cvd, Palette, MIN_CVD_DISTANCE},
+    pattern::{Pattern, PatternLimits, Turn, TurnSymbols, DEFAULT_MAX_STATES, MANY_STATES},
+    sim::{simulate, step, AntState, GridState},
+    SimRng,
+};
+
+/// Smallest Oklab distance between any two colors
+fn min_distance(colors: &[Color]) -> f32 {
+    let mut min = f32::MAX;
+    for (i, a) in colors.iter().enumerate() {
+        for b in &colors[i + 1..] {
+            let (a, b) = (Oklaba::from(*a), Oklaba::from(*b));
+            let distance = Vec3::new(a.lightness - b.lightness, a.a - b.a, a.b - b.b).length();
+            min = min.min(distance);
+        }
+    }
+    min
+}
+
+#[test]
+fn large_patterns_get_distinct_colors() {
+    let mut rng = SimRng::seeded(0);
+    let pattern = Pattern::parse("RLRRLLRLRRLLLRLR".to_owned(), &mut rng.0).unwrap();
+    assert_eq!(pattern.colors.len(), 16);
+    let distance = min_distance(&pattern.colors);
+    assert!(distance > 0.06, "two colors only {distance} apart");
+}
+
+#[test]
+fn pinned_colors_override_the_palette() {
+    let mut rng = SimRng::seeded(0);
+    let pattern =
+        Pattern::parse_with_palette("R:#ff0000 L R:navy".to_owned(), Palette::Random, &mut rng.0)
+            .unwrap();
+    assert_eq!(pattern.to_string(), "RLR");
+    assert_eq!(pattern.colors[0], Color::srgb(1., 0., 0.));
+    assert_eq!(
+        pattern.colors[2],
+        Color::from(Srgba::hex("000080").unwrap())
+    );
+
+    // Pinning leaves the other states with the colors they would get anyway
+    let mut rng = SimRng::seeded(0);
+    let unpinned = Pattern::parse("RLR".to_owned(), &mut rng.0).unwrap();
+    assert_eq!(pattern.colors[1], unpinned.colors[1]);
+}
+
+#[test]
+fn named_colors_map_to_the_css_colors() {
+    let parse = |text: &str| Pattern::parse(text.to_owned(), &mut SimRng::seeded(0).0).unwrap();
+    let hex = |pattern: &Pattern| -> Vec<String> {
+        pattern
+            .colors
+            .iter()
+            .map(|color| color.to_srgba().to_hex())
+            .collect()
+    };
+    let expected = ["#FF0000", "#008000", "#0000FF"];
+
+    for spec in ["red green blue", "Red,GREEN, blue"] {
+        let mut pattern = parse("RLR");
+        pattern.set_colors(spec).unwrap();
+        assert_eq!(hex(&pattern), expected, "{spec:?}");
+        assert_eq!(
+            pattern.colors,
+            [Color::from(RED), Color::from(GREEN), Color::from(BLUE)]
+        );
+    }
+    assert_eq!(hex(&parse("R:red L:green R:blue")), expected);
+
+    let mut pattern = parse("RLR");
+    let err = pattern.set_colors("red reed blue").unwrap_err();
+    assert!(err.to_string().contains("unknown color `reed`"), "{err}");
+    let err = pattern.set_colors("red green").unwrap_err();
+    assert!(err.to_string().contains("one color per state"), "{err}");
+}
+
+#[test]
+fn color_blind_palette_starts_with_okabe_ito() {
+    let mut rng = SimRng::seeded(0);
+    let pattern =
+        Pattern::parse_with_palette("RLR".to_owned(), Palette::ColorBlindSafe, &mut rng.0).unwrap();
+    assert_eq!(
+        pattern.colors,
+        [
+            Color::srgb(0.902, 0.624, 0.),
+            Color::srgb(0.337, 0.706, 0.914),
+            Color::srgb(0., 0.620, 0.451),
+        ]
+    );
+}
+
+#[test]
+fn turn_angles_parse_and_print_back() {
+    let pattern = Pattern::parse("R45 L30 R L".to_owned(), &mut SimRng::seeded(0).0).unwrap();
+    assert_eq!(
+        pattern.turns,
+        [
+            Turn::Degrees(45),
+            Turn::Degrees(-30),
+            Turn::Right,
+            Turn::Left
+        ]
+    );
+    assert_eq!(pattern.to_string(), "R45L30RL");
+}
+
+#[test]
+fn turn_angles_must_land_on_a_heading() {
+    let parse = |text: &str| Pattern::parse(text.to_owned(), &mut SimRng::seeded(0).0).unwrap();
+
+    assert!(parse("R90 L180").check_turns::<SquareLattice>().is_ok());
+    assert!(parse("R45 L").check_turns::<SquareLattice>().is_err());
+    assert!(parse("L120 R0").check_turns::<HexLattice>().is_ok());
+    assert!(parse("R L30").check_turns::<HexLattice>().is_err());
+    assert!(parse("R180 L60").check_turns::<TriangularLattice>().is_ok());
+    assert!(parse("R120 L").check_turns::<TriangularLattice>().is_err());
+
+    // A jump leaves the triangles' adjacency after the first cell
+    assert!(parse("R*2L").check_turns::<SquareLattice>().is_ok());
+    assert!(parse("R*2L").check_turns::<HexLattice>().is_ok());
+    let err = parse("R*2L")
+        .check_turns::<TriangularLattice>()
+        .unwrap_err();
+    assert!(format!("{err:#}").contains("one cell at a time"), "{err:#}");
+}
+
+#[test]
+fn distances_parse_and_print_back() {
+    let parse = |text: &str| Pattern::parse(text.to_owned(), &mut SimRng::seeded(0).0);
+    let pattern = parse("R*2 L R45*3:red").unwrap();
+    assert_eq!(pattern.turns, [Turn::Right, Turn::Left, Turn::Degrees(45)]);
+    assert_eq!(pattern.distances, [2, 1, 3]);
+    assert_eq!(pattern.to_string(), "R*2LR45*3");
+    assert_eq!(parse(&pattern.to_string()).unwrap().distances, [2, 1, 3]);
+
+    for (text, error) in [
+        ("R*0L", "0 cells"),
+        ("R*L", "followed by a distance"),
+        ("R*256L", "too large"),
+        ("*2RL", "must follow a turn"),
+        ("2RL", "must follow a turn"),
+    ] {
+        let err = parse(text).unwrap_err();
+        assert!(err.to_string().contains(error), "{text}: {err}");
+    }
+}
+
+#[test]
+fn whitespace_and_comments_are_skipped() {
+    let parse = |text: &str| Pattern::parse(text.to_owned(), &mut SimRng::seeded(0).0).unwrap();
+    for (text, turns) in [
+        ("R L # comment", "RL"),
+        ("  R\tL\n R  ", "RLR"),
+        ("R L # L R", "RL"),
+        ("L R*2 R # long second step", "LR*2R"),
+    ] {
+        let pattern = parse(text);
+        assert_eq!(pattern.to_string(), turns, "{text:?}");
+        assert_eq!(pattern.colors.len(), pattern.turns.len());
+    }
+
+    // A `#` after `:` is a hex color, the one after the space starts the comment
+    let pattern = parse("R:#ff0000 L # red, then random");
+    assert_eq!(pattern.turns, [Turn::Right, Turn::Left]);
+    assert_eq!(pattern.colors[0], Color::srgb(1., 0., 0.));
+
+    // The same states and colors as without the spacing and the comment
+    assert_eq!(parse(" L  L R R # LLRR").colors, parse("LLRR").colors);
+
+    // The comment runs past line breaks to the end
+    let commented_out = Pattern::parse("# RL\nRL".to_owned(), &mut SimRng::seeded(0).0);
+    assert!(commented_out
+        .unwrap_err()
+        .to_string()
+        .contains("at least 2"));
+}
+
+#[test]
+fn long_moves_skip_the_cells_in_between() {
+    let pattern = Pattern::parse("R*3L".to_owned(), &mut SimRng::seeded(0).0).unwrap();
+    let mut grid = GridState::default();
+    let mut ant = AntState::<SquareLattice>::default();
+    step(&mut grid, &mut ant, &pattern);
+    // Turned right from north, three cells east, only the cell left is painted
+    assert_eq!(ant.cell, IVec2::new(3, 0));
+    assert_eq!(grid.len(), 1);
+    assert_eq!(grid.get(IVec2::ZERO), Some(1));
+
+    // The state of the cell left decides the distance, state 1 moves one cell
+    ant.cell = IVec2::ZERO;
+    step(&mut grid, &mut ant, &pattern);
+    assert_eq!(ant.cell, IVec2::new(0, 1));
+}
+
+#[test]
+fn identity_state_is_walked_over_unchanged() {
+    let pattern = Pattern::parse("R i:#000000".to_owned(), &mut SimRng::seeded(0).0).unwrap();
+    assert_eq!(pattern.turns, [Turn::Right, Turn::Identity]);
+    assert_eq!(pattern.to_string(), "Ri");
+    assert!(pattern.check_turns::<SquareLattice>().is_ok());
+    assert!(pattern.check_turns::<TriangularLattice>().is_err());
+
+    // Four right turns paint a square of inert cells and bring the ant back to the origin
+    let mut grid = GridState::default();
+    let mut ant = AntState::<SquareLattice> {
+        cell: IVec2::ZERO,
+        heading: Direction::North,
+        mirrored: false,
+        inverse: false,
+    };
+    simulate(&mut grid, &mut ant, &pattern, 4);
+    assert_eq!((ant.cell, grid.get(IVec2::ZERO)), (IVec2::ZERO, Some(1)));
+
+    let delta = step(&mut grid, &mut ant, &pattern);
+    assert_eq!(delta.turn, Turn::Identity);
+    assert_eq!(grid.get(IVec2::ZERO), Some(1));
+    assert_eq!((ant.cell, ant.heading), (IVec2::Y, Direction::North));
+}
+
+#[test]
+fn new_checks_states_and_colors() {
+    let turns = vec![Turn::Right, Turn::Left];
+    let colors = vec![Color::BLACK, Color::WHITE];
+    let pattern = Pattern::new(turns.clone(), colors.clone()).unwrap();
+    assert_eq!(pattern.to_string(), "RL");
+    assert_eq!(pattern.colors, colors);
+
+    let too_short = Pattern::new(vec![Turn::Right], vec![Color::BLACK]).unwrap_err();
+    assert!(too_short.to_string().contains("at least 2"));
+
+    let missing_color = Pattern::new(turns.clone(), vec![Color::BLACK]).unwrap_err();
+    assert_eq!(
+        missing_color.to_string(),
+        "expected one color per state (2), got 1"
+    );
+
+    let shared_color = Pattern::new(turns, vec![Color::WHITE, Color::WHITE]).unwrap_err();
+    assert_eq!(
+        shared_color.to_string(),
+        "states 0 and 1 share the color #FFFFFF"
+    );
+
+    // The control panel checks a picked color the same way
+    let picked = [
+        Color::BLACK,
+        Color::WHITE,
+        Color::srgb(1., 0., 0.),
+        Color::WHITE,
+    ];
+    assert_eq!(
+        Pattern::check_colors(&picked).unwrap_err().to_string(),
+        "states 1 and 3 share the color #FFFFFF"
+    );
+    assert!(Pattern::check_colors(&picked[..3]).is_ok());
+}
+
+#[test]
+fn gradient_palettes_run_from_dark_to_light() {
+    let mut rng = SimRng::seeded(0);
+    for palette in [Palette::Viridis, Palette::Magma] {
+        let pattern =
+            Pattern::parse_with_palette("RLLRRL".to_owned(), palette, &mut rng.0).unwrap();
+        let lightness: Vec<f32> = pattern
+            .colors
+            .iter()
+            .map(|color| Oklaba::from(*color).lightness)
+            .collect();
+        assert!(
+            lightness.windows(2).all(|pair| pair[0] < pair[1]),
+            "{palette:?}: {lightness:?}"
+        );
+    }
+
+    let gray = Pattern::parse_with_palette("RL".to_owned(), Palette::Grayscale, &mut rng.0)
+        .unwrap()
+        .colors;
+    assert_eq!(
+        gray,
+        [Color::srgb(0.85, 0.85, 0.85), Color::srgb(0., 0., 0.)]
+    );
+}
+
+#[test]
+fn custom_turn_symbols() {
+    let symbols = TurnSymbols::parse("A=+90, B=-90\nC = 180").unwrap();
+    let parse = |text: &str| {
+        Pattern::parse_with_symbols(
+            text.to_owned(),
+            Palette::Random,
+            &symbols,
+            &mut SimRng::seeded(0).0,
+        )
+    };
+
+    let pattern = parse("AB C:#ff0000 R").unwrap();
+    assert_eq!(
+        pattern.turns,
+        [
+            Turn::Degrees(90),
+            Turn::Degrees(-90),
+            Turn::Degrees(180),
+            Turn::Right
+        ]
+    );
+    assert_eq!(pattern.colors[2], Color::srgb(1., 0., 0.));
+    assert!(pattern.check_turns::<SquareLattice>().is_ok());
+
+    let err = parse("ABD").unwrap_err().to_string();
+    assert!(err.contains("`D` is not defined"), "{err}");
+
+    assert!(TurnSymbols::parse("R=+90").is_err());
+    assert!(TurnSymbols::parse("AB=90").is_err());
+    assert!(TurnSymbols::parse("A=90, A=-90").is_err());
+    assert!(TurnSymbols::parse("A=left").is_err());
+}
+
+#[test]
+fn okabe_ito_stays_apart_with_color_vision_deficiencies() {
+    let mut rng = SimRng::seeded(0);
+    let pattern =
+        Pattern::parse_with_palette("RLRLRLRL".to_owned(), Palette::ColorBlindSafe, &mut rng.0)
+            .unwrap();
+    assert!(check_cvd_safe(&pattern.colors).is_ok());
+
+    for cvd in Cvd::ALL {
+        let simulated: Vec<Color> = pattern
+            .colors
+            .iter()
+            .map(|color| cvd.simulate(*color))
+            .collect();
+        assert!(min_distance(&simulated) >= MIN_CVD_DISTANCE, "{cvd:?}");
+    }
+
+    // Red and green that are easy to tell apart with full color vision
+    let red_green = [Color::srgb(0.8, 0.2, 0.1), Color::srgb(0.3, 0.6, 0.1)];
+    assert!(min_distance(&red_green) > 0.25);
+    let err = check_cvd_safe(&red_green).unwrap_err().to_string();
+    assert!(err.contains("Deuteranopia"), "{err}");
+}
+
+#[test]
+fn anti_ant_steps_states_backwards_with_flipped_turns() {
+    let pattern = Pattern::parse("RLR".to_owned(), &mut SimRng::seeded(0).0).unwrap();
+    let mut grid = GridState::default();
+    let ant = |cell: IVec2, inverse| AntState::<SquareLattice> {
+        cell,
+        heading: Direction::North,
+        mirrored: false,
+        inverse,
+    };
+
+    // Unvisited counts as state 0, stepping back wraps to the last state
+    let mut anti_ant = ant(IVec2::Y, true);
+    step(&mut grid, &mut anti_ant, &pattern);
+    assert_eq!(grid.get(IVec2::Y), Some(2));
+    assert_eq!(
+        (anti_ant.cell, anti_ant.heading),
+        (IVec2::new(-1, 1), Direction::West)
+    );
+
+    // What the forward ant paints the anti-ant takes back, turning the other way
+    let mut forward = ant(IVec2::ZERO, false);
+    step(&mut grid, &mut forward, &pattern);
+    assert_eq!(grid.get(IVec2::ZERO), Some(1));
+    assert_eq!(forward.heading, Direction::East);
+    let mut anti_ant = ant(IVec2::ZERO, true);
+    step(&mut grid, &mut anti_ant, &pattern);
+    assert_eq!(grid.get(IVec2::ZERO), Some(0));
+    assert_eq!(anti_ant.heading, Direction::West);
+
+    let ants = initial_ants::<SquareLattice>(false, true);
+    assert_eq!(ants.len(), 2);
+    assert!(!ants[0].inverse && ants[1].inverse);
+}
+
+#[test]
+fn patterns_past_the_state_limit_are_rejected() {
+    let mut rng = SimRng::seeded(0);
+    let at_limit = Pattern::parse("RL".repeat(DEFAULT_MAX_STATES / 2), &mut rng.0).unwrap();
+    let warning = at_limit.check_states(DEFAULT_MAX_STATES).unwrap().unwrap();
+    assert!(warning.contains("hard to tell apart"), "{warning}");
+
+    let past_limit = Pattern::parse(
+        format!("{}R", "RL".repeat(DEFAULT_MAX_STATES / 2)),
+        &mut rng.0,
+    )
+    .unwrap();
+    let err = past_limit.check_states(DEFAULT_MAX_STATES).unwrap_err();
+    assert!(err.to_string().contains("--max-states"), "{err}");
+    past_limit.check_states(DEFAULT_MAX_STATES + 1).unwrap();
+
+    let small = Pattern::parse("RLR".to_owned(), &mut rng.0).unwrap();
+    assert!(small.check_states(2).is_err());
+    assert_eq!(small.check_states(3).unwrap(), None);
+
+    // Warned about only past `MANY_STATES`
+    let many = |states: usize| Pattern::parse("RL".repeat(states / 2), &mut SimRng::seeded(0).0);
+    assert_eq!(
+        many(MANY_STATES)
+            .unwrap()
+            .check_states(MANY_STATES)
+            .unwrap(),
+        None
+    );
+    assert!(many(MANY_STATES + 2)
+        .unwrap()
+        .check_states(DEFAULT_MAX_STATES)
+        .unwrap()
+        .is_some());
+}
+
+#[test]
+fn validation_checks_the_turns_on_the_lattice_of_the_limits() {
+    let parse = |text: &str| Pattern::parse(text.to_owned(), &mut SimRng::seeded(0).0).unwrap();
+    let square = PatternLimits::default();
+    let triangular =
+        PatternLimits::new::<TriangularLattice>(DEFAULT_MAX_STATES, &GridState::default());
+
+    parse("R90 L").validate(&square).unwrap();
+    let err = parse("R45 L").validate(&square).unwrap_err();
+    assert!(format!("{err:#}").contains("R45"), "{err:#}");
+    parse("R i L").validate(&square).unwrap();
+    assert!(parse("R i L").validate(&triangular).is_err());
+}
+
+#[test]
+fn patterns_that_always_turn_the_same_way_are_rejected() {
+    let mut rng = SimRng::seeded(0);
+    for pattern in ["RRRR", "LL", "R*2R*2"] {
+        let err = Pattern::parse(pattern.to_owned(), &mut rng.0).unwrap_err();
+        assert!(
+            err.to_string().contains("turns the same way"),
+            "{pattern}: {err}"
+        );
+    }
+    for pattern in ["RL", "RR*2", "Ri"] {
+        Pattern::parse(pattern.to_owned(), &mut rng.0).unwrap();
+    }
+}
+
+#[test]
+fn states_cycle_back_to_the_first_for_any_length() {
+    let mut rng = SimRng::seeded(0);
+    for text in ["RL", "RLR", "LRRLL"] {
+        let pattern = Pattern::parse(text.to_owned(), &mut rng.0).unwrap();
+        let states = pattern.turns.len();
+
+        // Stepping from 0 visits every state once, with its own turn, and lands on 0
+        let mut state = 0;
+        for expected in 0..states {
+            let (next, turn) = pattern.next(state);
+            assert_eq!(turn, pattern.turns[expected], "{text} state {expected}");
+            assert_eq!(next, (expected + 1) % states, "{text} state {expected}");
+            state = next;
+        }
+        assert_eq!(state, 0, "{text} didn't wrap");
+
+        // And backwards, from the last state to the first and round to the last again
+        for state in 0..states {
+            let (previous, turn) = pattern.previous(pattern.next(state).0);
+            assert_eq!((previous, turn), (state, pattern.turns[state]), "{text}");
+        }
+    }
+
+    // An inert state in the cycle is never left
+    let pattern = Pattern::parse("RiL".to_owned(), &mut rng.0).unwrap();
+    assert_eq!(pattern.next(0).0, 1);
+    assert_eq!(pattern.next(1), (1, Turn::Identity));
+    assert_eq!(pattern.next(2).0, 0);
+}
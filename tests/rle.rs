@@ -0,0 +1,119 @@
+//! Run-length encoded snapshots.
+
+use bevy::{math::IVec2, utils::HashMap};
+use langtons_ant::{
+    board::initial_ants,
+    lattice::{HexLattice, SquareLattice},
+    pattern::Pattern,
+    rle::{SavedAnt, Snapshot},
+    sim::{simulate_all_counting, Collision, GridState, VisitCounts},
+    SimRng,
+};
+
+fn cells(snapshot: &Snapshot) -> HashMap<IVec2, usize> {
+    snapshot.cells.iter().copied().collect()
+}
+
+#[test]
+fn snapshots_round_trip() {
+    let mut rng = SimRng::seeded(0);
+    let pattern = Pattern::parse("LLRRRLRLRLLR".to_owned(), &mut rng.0).unwrap();
+    let mut grid = GridState::default();
+    let mut ants = initial_ants::<SquareLattice>(true, false);
+    let mut visits = VisitCounts::default();
+    simulate_all_counting(
+        &mut grid,
+        &mut ants,
+        &pattern,
+        Collision::Pass,
+        5000,
+        &mut visits,
+    );
+
+    let snapshot = Snapshot {
+        rule: pattern.to_string(),
+        cells: grid.cells().collect(),
+        ants: ants.iter().map(SavedAnt::from_state).collect(),
+        visits: visits.iter().map(|(cell, count)| (*cell, *count)).collect(),
+    };
+    let text = snapshot.to_rle().unwrap();
+    assert!(text.lines().all(|line| line.len() <= 70), "{text}");
+
+    let read = Snapshot::parse(&text).unwrap();
+    assert_eq!(read.rule, "LLRRRLRLRLLR");
+    assert_eq!(cells(&read), cells(&snapshot));
+    assert_eq!(read.ants, snapshot.ants);
+    let read_ants: Vec<_> = read
+        .ants
+        .iter()
+        .map(|ant| ant.to_state::<SquareLattice>().unwrap())
+        .collect();
+    assert_eq!(read_ants, ants);
+    let read_visits: VisitCounts = read.visits.into_iter().collect();
+    assert_eq!(read_visits, visits);
+    assert_eq!(
+        visits.values().map(|count| *count as u64).sum::<u64>(),
+        4 * 5000
+    );
+}
+
+#[test]
+fn states_past_the_letters_take_two_characters() {
+    let snapshot = Snapshot {
+        rule: "R".repeat(60),
+        cells: vec![
+            (IVec2::new(0, 0), 23),
+            (IVec2::new(1, 0), 24),
+            (IVec2::new(2, 0), 59),
+        ],
+        ants: Vec::new(),
+        visits: Vec::new(),
+    };
+    let text = snapshot.to_rle().unwrap();
+    assert!(text.ends_with("XpAqL!\n"), "{text}");
+    assert_eq!(cells(&Snapshot::parse(&text).unwrap()), cells(&snapshot));
+
+    let too_many = Snapshot {
+        cells: vec![(IVec2::ZERO, 255)],
+        ..snapshot
+    };
+    assert!(too_many.to_rle().is_err());
+}
+
+#[test]
+fn reads_plain_two_state_files() {
+    // A glider as Golly writes it, with the rule swapped for a pattern
+    let text = "#N Glider\n#C comment\nx = 3, y = 3, rule = RL\nbob$2bo$3o!\n";
+    let snapshot = Snapshot::parse(text).unwrap();
+    let mut cells: Vec<_> = snapshot.cells.iter().map(|(cell, _)| *cell).collect();
+    cells.sort_by_key(|cell| (-cell.y, cell.x));
+    assert_eq!(
+        cells,
+        [(1, 0), (2, -1), (0, -2), (1, -2), (2, -2)].map(|(x, y)| IVec2::new(x, y))
+    );
+    assert!(snapshot.cells.iter().all(|(_, state)| *state == 0));
+    assert!(snapshot.ants.is_empty());
+}
+
+#[test]
+fn bad_snapshots_are_rejected() {
+    for (text, error) in [
+        ("A!", "name = value"),
+        ("x = 2, y = 1\nAA!", "no rule"),
+        ("x = 1, y = 1, rule = RL\nAA!", "bounding box"),
+        ("x = 1, y = 1, rule = RL\nZ!", "unexpected `Z`"),
+        ("#A 0 0 Up\nx = 0, y = 0, rule = RL\n!", ""),
+    ] {
+        match Snapshot::parse(text) {
+            Ok(snapshot) => {
+                // Headings are only checked against a lattice
+                assert!(error.is_empty(), "{text:?} was accepted");
+                assert!(snapshot.ants[0].to_state::<HexLattice>().is_err());
+            }
+            Err(err) => assert!(
+                format!("{err:#}").contains(error),
+                "unexpected error for {text:?}: {err:#}"
+            ),
+        }
+    }
+}
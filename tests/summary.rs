@@ -0,0 +1,55 @@
+//! The summary printed when a run ends.
+
+use bevy::math::IVec2;
+use langtons_ant::{
+    board::initial_ants,
+    lattice::SquareLattice,
+    pattern::Pattern,
+    sim::{simulate_all, Collision, GridState},
+    summary::RunSummary,
+    SimRng,
+};
+
+fn summary(rule: &str, steps: u64) -> RunSummary {
+    let pattern = Pattern::parse(rule.to_owned(), &mut SimRng::seeded(0).0).unwrap();
+    let mut grid = GridState::default();
+    let mut ants = initial_ants::<SquareLattice>(false, false);
+    simulate_all(&mut grid, &mut ants, &pattern, Collision::Pass, steps);
+    RunSummary::new(&pattern, 7, steps, &grid, &ants, Collision::Pass)
+}
+
+#[test]
+fn highways_are_found_once_they_start() {
+    // `RL` is chaotic for about 10000 steps before its highway starts
+    assert_eq!(summary("RL", 1000).highway, None);
+    let highway = summary("RL", 12_000).highway.unwrap();
+    assert_eq!(highway.period, 104);
+    assert_eq!(highway.shift.abs(), IVec2::splat(2));
+}
+
+#[test]
+fn summaries_list_the_run() {
+    let summary = summary("RL", 12_000);
+    assert_eq!(summary.steps, 12_000);
+    let text = summary.to_string();
+    for line in [
+        "pattern:      RL",
+        "seed:         7",
+        "steps:        12000",
+        &format!("painted:      {} cells", summary.painted),
+        "highway:      period 104",
+    ] {
+        assert!(text.contains(line), "{line:?} missing from\n{text}");
+    }
+
+    let empty = RunSummary {
+        seed: 0,
+        steps: 0,
+        painted: 0,
+        bounds: None,
+        highway: None,
+        ..summary
+    };
+    assert!(empty.to_string().contains("bounding box: empty"));
+    assert!(empty.to_string().ends_with("highway:      none"));
+}
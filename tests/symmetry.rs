@@ -0,0 +1,92 @@
+//! Symmetry scores of the painted cells.
+
+use bevy::math::IVec2;
+use langtons_ant::{
+    board::initial_ants,
+    lattice::{HexLattice, Lattice, SquareLattice, TriangularLattice},
+    pattern::Pattern,
+    sim::{simulate_all, step_colliding, Collision, GridState},
+    symmetry::Symmetry,
+    SimRng,
+};
+
+fn grid(cells: &[(i32, i32, usize)]) -> GridState {
+    let mut grid = GridState::default();
+    for &(x, y, state) in cells {
+        grid.set(IVec2::new(x, y), state);
+    }
+    grid
+}
+
+#[test]
+fn small_grids_score_without_dividing_by_zero() {
+    let perfect = Symmetry {
+        vertical: 1.,
+        horizontal: 1.,
+        rotation: 1.,
+    };
+    assert_eq!(Symmetry::of(&GridState::default()), perfect);
+    assert_eq!(Symmetry::of(&grid(&[(5, -3, 1)])), perfect);
+    // Two cells side by side mirror into each other across the edge between them
+    assert_eq!(Symmetry::of(&grid(&[(0, 0, 0), (1, 0, 0)])), perfect);
+}
+
+#[test]
+fn scores_count_the_cells_matching_their_image() {
+    // An L: the corner and the bottom arm's end match their left-right images
+    let l_shape = grid(&[(0, 0, 0), (0, 1, 0), (0, 2, 0), (1, 0, 0), (2, 0, 0)]);
+    let symmetry = Symmetry::of(&l_shape);
+    assert_eq!(symmetry.vertical, symmetry.horizontal);
+    assert!(symmetry.vertical < 1.);
+
+    // Different states don't match even where both cells are painted
+    assert_eq!(Symmetry::of(&grid(&[(0, 0, 0), (1, 0, 1)])).vertical, 0.);
+}
+
+#[test]
+fn mirrored_ants_paint_a_symmetric_board() {
+    let mut rng = SimRng::seeded(0);
+    let pattern = Pattern::parse("RLR".to_owned(), &mut rng.0).unwrap();
+    let mut grid = GridState::default();
+    let mut ants = initial_ants::<SquareLattice>(true, false);
+    simulate_all(&mut grid, &mut ants, &pattern, Collision::Pass, 2000);
+
+    let symmetry = Symmetry::of(&grid);
+    assert_eq!((symmetry.vertical, symmetry.horizontal), (1., 1.));
+    assert_eq!(symmetry.rotation, 1.);
+
+    // A lone ant's highway leads off in one direction
+    let mut grid = GridState::default();
+    let mut ants = initial_ants::<SquareLattice>(false, false);
+    simulate_all(&mut grid, &mut ants, &pattern, Collision::Pass, 2000);
+    let symmetry = Symmetry::of(&grid);
+    assert!(symmetry.vertical < 1. && symmetry.horizontal < 1. && symmetry.rotation < 1.);
+}
+
+/// Steps the four mirror ants one step at a time, checking after every step that they
+/// are still each other's images and that every painted cell has its images painted the
+/// same
+fn mirror_ants_stay_symmetric<L: Lattice>(pattern: &str, steps: usize) {
+    let pattern = Pattern::parse(pattern.to_owned(), &mut SimRng::seeded(0).0).unwrap();
+    let mut grid = GridState::default();
+    let mut ants = initial_ants::<L>(true, false);
+    let image = |cell: IVec2| L::reflect(cell, L::INITIAL_HEADING).0;
+    let half_turn = |cell: IVec2| L::half_turn(cell, L::INITIAL_HEADING).0;
+    for step in 1..=steps {
+        step_colliding(&mut grid, &mut ants, &pattern, Collision::Pass);
+        assert_eq!(ants, ants[0].mirror_images(), "step {step}");
+        for (cell, state) in grid.cells() {
+            for image in [image(cell), half_turn(cell), half_turn(image(cell))] {
+                assert_eq!(grid.get(image), Some(state), "{cell} at step {step}");
+            }
+        }
+    }
+}
+
+#[test]
+fn mirrored_ants_keep_the_board_symmetric_at_every_step() {
+    mirror_ants_stay_symmetric::<SquareLattice>("RLR", 1000);
+    mirror_ants_stay_symmetric::<SquareLattice>("LLRR", 1000);
+    mirror_ants_stay_symmetric::<HexLattice>("RLLR", 1000);
+    mirror_ants_stay_symmetric::<TriangularLattice>("RLR", 1000);
+}
@@ -0,0 +1,39 @@
+//! Settings the `--theme` names stand for.
+
+use bevy::{color::Luminance, prelude::Color};
+use clap::ValueEnum;
+use langtons_ant::{
+    palette::Palette,
+    theme::{foreground, TextureFilter, Theme},
+};
+
+#[test]
+fn dark_theme_clears_to_a_dark_background_with_viridis() {
+    let theme = Theme::from_str("dark", false).unwrap();
+    assert_eq!(theme, Theme::Dark);
+    assert!(theme.background().luminance() < 0.1);
+    assert_eq!(theme.palette(), Palette::Viridis);
+    assert_eq!(theme.filter(), TextureFilter::Nearest);
+}
+
+#[test]
+fn classic_theme_keeps_the_defaults() {
+    let theme = Theme::default();
+    assert_eq!(theme.background(), Color::WHITE);
+    assert_eq!(theme.palette(), Palette::default());
+    assert_eq!(theme.filter(), TextureFilter::default());
+}
+
+#[test]
+fn overlay_text_contrasts_with_every_theme_background() {
+    // WCAG contrast ratio, 4.5 is the minimum for body text
+    let contrast = |a: Color, b: Color| {
+        let (a, b) = (a.luminance() + 0.05, b.luminance() + 0.05);
+        a.max(b) / a.min(b)
+    };
+    for theme in Theme::value_variants() {
+        let background = theme.background();
+        let ratio = contrast(foreground(background), background);
+        assert!(ratio >= 4.5, "{theme:?} text contrast is {ratio}");
+    }
+}
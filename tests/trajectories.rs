@@ -0,0 +1,449 @@
+//! Known trajectories of single ants on every lattice, so a refactor of the
+//! stepping can't change what an ant does without a test noticing, and random runs that
+//! check what must hold on every step.
+//!
+//! After an intended change of behavior, run
+//! `REGENERATE_TRAJECTORIES=1 cargo test --test trajectories -- --nocapture`
+//! and paste the tables it prints over the ones here.
+
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use bevy::math::{IVec2, Quat, UVec2, Vec3};
+use langtons_ant::{
+    lattice::{
+        Direction, HexDirection, HexLattice, Lattice, SquareLattice, TriDirection,
+        TriangularLattice,
+    },
+    pattern::Pattern,
+    sim::{simulate_all, step, step_colliding, AntState, Border, Collision, EdgeMode, GridState},
+    SimRng,
+};
+use rand::{seq::SliceRandom, Rng};
+
+/// Steps `simulate_all` takes at a time, like the progress batches of a headless run
+const BATCH: u64 = 997;
+
+struct Trajectory<H> {
+    pattern: &'static str,
+    steps: u64,
+    cell: IVec2,
+    heading: H,
+    hash: u64,
+}
+
+const TRAJECTORIES: [Trajectory<Direction>; 4] = [
+    Trajectory {
+        pattern: "RL",
+        steps: 10_000,
+        cell: IVec2::new(-16, 10),
+        heading: Direction::North,
+        hash: 0x09161a12b3080fd9,
+    },
+    Trajectory {
+        pattern: "LLRR",
+        steps: 50_000,
+        cell: IVec2::new(2, 2),
+        heading: Direction::South,
+        hash: 0x044d74a8140d43f9,
+    },
+    Trajectory {
+        pattern: "RLR",
+        steps: 25_000,
+        cell: IVec2::new(32, 6),
+        heading: Direction::South,
+        hash: 0xfb3f3b733fb91fca,
+    },
+    Trajectory {
+        pattern: "LRRRRRLLR",
+        steps: 30_000,
+        cell: IVec2::new(-4, -8),
+        heading: Direction::North,
+        hash: 0x219156cf679d7bcb,
+    },
+];
+
+const HEX_TRAJECTORIES: [Trajectory<HexDirection>; 3] = [
+    Trajectory {
+        pattern: "RL",
+        steps: 10_000,
+        cell: IVec2::new(-10, -10),
+        heading: HexDirection::East,
+        hash: 0x51209d1809c4fb2f,
+    },
+    Trajectory {
+        pattern: "RLLR",
+        steps: 100_000,
+        cell: IVec2::new(-5, -17),
+        heading: HexDirection::East,
+        hash: 0x361616d7217f3baf,
+    },
+    Trajectory {
+        pattern: "LRRRRRLLR",
+        steps: 30_000,
+        cell: IVec2::new(-6, 18),
+        heading: HexDirection::SouthWest,
+        hash: 0xc396f1ae9792d291,
+    },
+];
+
+/// 100k steps of patterns with distances and inert states on every lattice, recorded
+/// before unvisited cells were stepped as state 0 through `Pattern::next`
+const LONG_TRAJECTORIES: [Trajectory<Direction>; 4] = [
+    Trajectory {
+        pattern: "RL",
+        steps: 100_000,
+        cell: IVec2::new(-1748, -1726),
+        heading: Direction::North,
+        hash: 0x5a33845360ff4a70,
+    },
+    Trajectory {
+        pattern: "R*2L",
+        steps: 100_000,
+        cell: IVec2::new(-52, 4),
+        heading: Direction::North,
+        hash: 0x01fd572c1de1ebe5,
+    },
+    Trajectory {
+        pattern: "RiL",
+        steps: 100_000,
+        cell: IVec2::new(0, 96),
+        heading: Direction::North,
+        hash: 0x70a18260ac2279a5,
+    },
+    Trajectory {
+        pattern: "LRRRRRLLR",
+        steps: 100_000,
+        cell: IVec2::new(16, 10),
+        heading: Direction::South,
+        hash: 0x4e55d79c88140764,
+    },
+];
+
+const HEX_LONG_TRAJECTORIES: [Trajectory<HexDirection>; 2] = [
+    Trajectory {
+        pattern: "RL",
+        steps: 100_000,
+        cell: IVec2::new(-3, -3),
+        heading: HexDirection::East,
+        hash: 0xdc9c50a96d53afca,
+    },
+    Trajectory {
+        pattern: "RiL",
+        steps: 100_000,
+        cell: IVec2::new(-97, -2),
+        heading: HexDirection::West,
+        hash: 0x324a48b4b53d21ea,
+    },
+];
+
+const TRIANGULAR_LONG_TRAJECTORIES: [Trajectory<TriDirection>; 2] = [
+    Trajectory {
+        pattern: "RL",
+        steps: 100_000,
+        cell: IVec2::new(3, -3),
+        heading: TriDirection::North,
+        hash: 0xaf26da3e14399419,
+    },
+    Trajectory {
+        pattern: "LLRR",
+        steps: 100_000,
+        cell: IVec2::new(1, -1),
+        heading: TriDirection::North,
+        hash: 0xbf36c5fd3a83e125,
+    },
+];
+
+fn pattern(text: &str) -> Pattern {
+    Pattern::parse(text.to_owned(), &mut SimRng::seeded(0).0).unwrap()
+}
+
+/// One `step` at a time on a hash map grid
+fn unbatched<L: Lattice>(pattern: &Pattern, steps: u64) -> (AntState<L>, GridState) {
+    let mut grid = GridState::default();
+    let mut ant = AntState::default();
+    for _ in 0..steps {
+        step(&mut grid, &mut ant, pattern);
+    }
+    (ant, grid)
+}
+
+/// `BATCH` steps at a time through `simulate_all` on a chunked grid
+fn batched<L: Lattice>(pattern: &Pattern, steps: u64) -> (AntState<L>, GridState) {
+    let mut grid = GridState::chunked();
+    let mut ants = vec![AntState::default()];
+    let mut done = 0;
+    while done < steps {
+        let batch = BATCH.min(steps - done);
+        simulate_all(&mut grid, &mut ants, pattern, Collision::Pass, batch);
+        done += batch;
+    }
+    (ants[0], grid)
+}
+
+/// `n` with an underscore between every three digits, as in a Rust literal
+fn grouped(n: u64) -> String {
+    let digits = n.to_string();
+    let mut grouped = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push('_');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Prints `trajectories` as a `name` table with their current outcomes
+fn print_table<L: Lattice>(
+    name: &str,
+    heading_type: &str,
+    trajectories: &[Trajectory<L::Heading>],
+) {
+    println!(
+        "const {name}: [Trajectory<{heading_type}>; {}] = [",
+        trajectories.len()
+    );
+    for known in trajectories {
+        let (ant, grid) = unbatched::<L>(&pattern(known.pattern), known.steps);
+        println!("    Trajectory {{");
+        println!("        pattern: {:?},", known.pattern);
+        println!("        steps: {},", grouped(known.steps));
+        println!("        cell: IVec2::new({}, {}),", ant.cell.x, ant.cell.y);
+        println!("        heading: {heading_type}::{:?},", ant.heading);
+        println!("        hash: {:#018x},", grid.content_hash());
+        println!("    }},");
+    }
+    println!("];");
+}
+
+fn check_trajectories<L: Lattice>(trajectories: &[Trajectory<L::Heading>]) {
+    for known in trajectories {
+        let (ant, grid) = unbatched::<L>(&pattern(known.pattern), known.steps);
+        let found = (ant.cell, ant.heading, grid.content_hash());
+        assert_eq!(
+            found,
+            (known.cell, known.heading, known.hash),
+            "{} after {} steps",
+            known.pattern,
+            known.steps
+        );
+    }
+}
+
+fn check_batched<L: Lattice>(trajectories: &[Trajectory<L::Heading>]) {
+    for known in trajectories {
+        let pattern = pattern(known.pattern);
+        let (ant, grid) = unbatched::<L>(&pattern, known.steps);
+        let (batched_ant, batched_grid) = batched::<L>(&pattern, known.steps);
+        assert_eq!(batched_ant, ant, "{}", known.pattern);
+        assert_eq!(batched_grid.len(), grid.len(), "{}", known.pattern);
+        assert_eq!(
+            batched_grid.content_hash(),
+            grid.content_hash(),
+            "{}",
+            known.pattern
+        );
+    }
+}
+
+#[test]
+fn ants_follow_their_known_trajectories() {
+    if std::env::var_os("REGENERATE_TRAJECTORIES").is_some() {
+        print_table::<SquareLattice>("TRAJECTORIES", "Direction", &TRAJECTORIES);
+        print_table::<HexLattice>("HEX_TRAJECTORIES", "HexDirection", &HEX_TRAJECTORIES);
+        print_table::<SquareLattice>("LONG_TRAJECTORIES", "Direction", &LONG_TRAJECTORIES);
+        print_table::<HexLattice>(
+            "HEX_LONG_TRAJECTORIES",
+            "HexDirection",
+            &HEX_LONG_TRAJECTORIES,
+        );
+        print_table::<TriangularLattice>(
+            "TRIANGULAR_LONG_TRAJECTORIES",
+            "TriDirection",
+            &TRIANGULAR_LONG_TRAJECTORIES,
+        );
+        return;
+    }
+    check_trajectories::<SquareLattice>(&TRAJECTORIES);
+    check_trajectories::<HexLattice>(&HEX_TRAJECTORIES);
+}
+
+#[test]
+fn long_trajectories_are_unchanged() {
+    check_trajectories::<SquareLattice>(&LONG_TRAJECTORIES);
+    check_trajectories::<HexLattice>(&HEX_LONG_TRAJECTORIES);
+    check_trajectories::<TriangularLattice>(&TRIANGULAR_LONG_TRAJECTORIES);
+}
+
+#[test]
+fn batched_stepping_agrees_with_single_steps() {
+    check_batched::<SquareLattice>(&TRAJECTORIES);
+    check_batched::<HexLattice>(&HEX_TRAJECTORIES);
+}
+
+/// Steps after which the ant sprite must still face exactly where the ant goes
+const LONG_RUN: u64 = 1_000_000;
+
+/// The sprite of an ant far into a run points at the next cell it steps to
+fn sprite_faces_the_next_cell<L: Lattice>() -> Quat {
+    let (ant, _) = batched::<L>(&pattern("RL"), LONG_RUN);
+    let rotation = L::heading_to_rotation(ant.heading);
+    let tip = (rotation * Vec3::Y).truncate();
+    let next = L::cell_to_world(L::neighbor(ant.cell, ant.heading)) - L::cell_to_world(ant.cell);
+    assert!(tip.angle_between(next).abs() < 1e-4, "{tip} {next}");
+    rotation
+}
+
+#[test]
+fn sprite_rotation_follows_the_heading_after_a_million_steps() {
+    let rotation = sprite_faces_the_next_cell::<SquareLattice>();
+    let canonical = [0., FRAC_PI_2, PI, -FRAC_PI_2].map(Quat::from_rotation_z);
+    assert!(canonical.contains(&rotation), "{rotation}");
+    sprite_faces_the_next_cell::<HexLattice>();
+    sprite_faces_the_next_cell::<TriangularLattice>();
+}
+
+/// Random cases each lattice runs through `holds_invariants`
+const CASES: u64 = 64;
+
+/// Steps of every random case
+const CASE_STEPS: usize = 1500;
+
+/// A random run: pattern, starting ants, collision rule and grid, all drawn from `seed`
+struct Case<L: Lattice> {
+    pattern: Pattern,
+    ants: Vec<AntState<L>>,
+    collision: Collision,
+    grid: GridState,
+}
+
+impl<L: Lattice> Case<L> {
+    fn new(seed: u64) -> Self {
+        let mut rng = SimRng::seeded(seed).0;
+        let pattern = loop {
+            let states = rng.gen_range(2..=12);
+            let text: String = (0..states)
+                .map(|_| match rng.gen_range(0..10) {
+                    0 => "i".to_owned(),
+                    1 => format!("R*{}", rng.gen_range(2..=3)),
+                    2..=5 => "L".to_owned(),
+                    _ => "R".to_owned(),
+                })
+                .collect();
+            // Patterns turning only one way, only inert or off the lattice are turned away
+            if let Ok(pattern) = Pattern::parse(text, &mut rng) {
+                if pattern.check_turns::<L>().is_ok() {
+                    break pattern;
+                }
+            }
+        };
+        let ants = (0..rng.gen_range(1..=3))
+            .map(|_| {
+                let cell = IVec2::new(rng.gen_range(-4..=4), rng.gen_range(-4..=4));
+                AntState {
+                    cell,
+                    heading: L::start_heading(cell),
+                    mirrored: rng.gen_bool(0.2),
+                    inverse: rng.gen_bool(0.2),
+                }
+            })
+            .collect();
+        let collision = *[Collision::Pass, Collision::Block, Collision::Annihilate]
+            .choose(&mut rng)
+            .unwrap();
+        let grid = if rng.gen_bool(0.5) {
+            GridState::default()
+        } else {
+            GridState::chunked()
+        };
+        let grid = match rng.gen_range(0..3) {
+            0 => grid.with_border(Border::new(UVec2::new(16, 12), EdgeMode::Stop)),
+            1 => grid.with_border(Border::new(UVec2::new(12, 16), EdgeMode::Bounce)),
+            _ => grid,
+        };
+        Self {
+            pattern,
+            ants,
+            collision,
+            grid,
+        }
+    }
+
+    /// Runs the case, checking every step, and returns the ants and the grid's hash.
+    /// Ants moving a single cell must stay or move to one of `neighbors` of their cell.
+    fn run(mut self, neighbors: fn(IVec2) -> Vec<IVec2>) -> (Vec<AntState<L>>, u64) {
+        let states = self.pattern.turns.len();
+        for _ in 0..CASE_STEPS {
+            let (deltas, annihilated) = step_colliding(
+                &mut self.grid,
+                &mut self.ants,
+                &self.pattern,
+                self.collision,
+            );
+            assert_eq!(deltas.len(), self.ants.len());
+            for (delta, ant) in deltas.iter().zip(&self.ants) {
+                assert!(delta.new_state < states, "{} {delta:?}", self.pattern);
+                assert!(delta.old_state.is_none_or(|state| state < states));
+                if delta.distance == 1 && ant.cell != delta.cell {
+                    assert!(
+                        neighbors(delta.cell).contains(&ant.cell),
+                        "{} stepped from {} to {}",
+                        self.pattern,
+                        delta.cell,
+                        ant.cell
+                    );
+                }
+            }
+            for index in annihilated.into_iter().rev() {
+                self.ants.remove(index);
+            }
+            for ant in &self.ants {
+                assert!(L::HEADINGS.contains(&ant.heading));
+                if let Some(border) = self.grid.border() {
+                    assert!(border.contains(ant.cell), "{} left {border:?}", ant.cell);
+                }
+            }
+        }
+        assert!(self.grid.cells().all(|(_, state)| state < states));
+        assert_eq!(self.grid.cells().count(), self.grid.len());
+        (self.ants, self.grid.content_hash())
+    }
+}
+
+/// Cells across an edge of `cell` where every heading crosses one, as on squares and hexes
+fn neighbors<L: Lattice>(cell: IVec2) -> Vec<IVec2> {
+    L::HEADINGS
+        .iter()
+        .map(|&heading| L::neighbor(cell, heading))
+        .collect()
+}
+
+/// Cells across an edge of the triangle `cell`: its two sides and its flat edge, below
+/// it when it points up and above it when it points down
+fn triangle_neighbors(cell: IVec2) -> Vec<IVec2> {
+    let flat = if (cell.x + cell.y).rem_euclid(2) == 0 {
+        IVec2::NEG_Y
+    } else {
+        IVec2::Y
+    };
+    vec![cell + IVec2::X, cell - IVec2::X, cell + flat]
+}
+
+/// Random runs keep every cell in a state of the pattern and every ant on a heading of
+/// the lattice and inside the border, step across edges of the lattice, and replaying a
+/// run from its seed repeats it
+fn holds_invariants<L: Lattice>(neighbors: fn(IVec2) -> Vec<IVec2>) {
+    for seed in 0..CASES {
+        let (ants, hash) = Case::<L>::new(seed).run(neighbors);
+        let (replayed_ants, replayed_hash) = Case::<L>::new(seed).run(neighbors);
+        assert_eq!(replayed_ants, ants, "seed {seed}");
+        assert_eq!(replayed_hash, hash, "seed {seed}");
+    }
+}
+
+#[test]
+fn random_runs_hold_the_invariants_on_every_lattice() {
+    holds_invariants::<SquareLattice>(neighbors::<SquareLattice>);
+    holds_invariants::<HexLattice>(neighbors::<HexLattice>);
+    holds_invariants::<TriangularLattice>(triangle_neighbors);
+}